@@ -0,0 +1,202 @@
+use std::path::{Path, PathBuf};
+
+use unsynn::{ErrorKind, TokenTree};
+
+/// Failure to parse a Move file, with the location of the offending token.
+///
+/// Its [`Display`](std::fmt::Display) implementation renders the location and an excerpt of the
+/// source, e.g.:
+/// ```text
+/// expected `Item`, found `}`
+///  --> sources/pool.move:12:5
+///    |
+/// 12 |     }
+///    |     ^
+/// ```
+#[derive(Clone, Debug)]
+pub struct ParseError {
+    message: String,
+    path: Option<PathBuf>,
+    /// 1-based
+    line: usize,
+    /// 1-based, in characters
+    column: usize,
+    /// Source line containing the error, if known
+    excerpt: Option<String>,
+    /// Number of characters to highlight in the excerpt
+    width: usize,
+}
+
+impl ParseError {
+    /// Locate `error` in `source`, which must be the text that was tokenized.
+    pub(crate) fn from_unsynn(error: &unsynn::Error, source: &str) -> Self {
+        let expected = short_type_name(error.expected_type_name());
+        let at = error.failed_at();
+        let found = at.as_ref().map_or_else(
+            || "end of input".to_owned(),
+            |tt| format!("`{}`", summary(tt)),
+        );
+        let message = match &error.kind {
+            // Usually a list that stopped early, like the fields of a struct
+            _ if expected == "EndOfStream" => format!("unexpected {found}"),
+            ErrorKind::Other { reason } => {
+                format!("expected `{expected}`, {reason}; found {found}")
+            }
+            ErrorKind::Dynamic(err) => format!("expected `{expected}`, {err}; found {found}"),
+            _ => format!("expected `{expected}`, found {found}"),
+        };
+        match at {
+            Some(tt) => Self::at_span(message, tt.span(), source),
+            None => Self::at_end(message, source),
+        }
+    }
+
+    /// Locate a tokenizer error in `source`.
+    pub(crate) fn from_lex(error: &proc_macro2::LexError, source: &str) -> Self {
+        Self::at_span(error.to_string(), error.span(), source)
+    }
+
+    fn at_span(message: String, span: proc_macro2::Span, source: &str) -> Self {
+        let start = span.start();
+        let end = span.end();
+        let excerpt = source
+            .lines()
+            .nth(start.line.saturating_sub(1))
+            .map(str::to_owned);
+        let width = if end.line == start.line {
+            end.column.saturating_sub(start.column).max(1)
+        } else {
+            excerpt.as_ref().map_or(1, |line| {
+                line.chars().count().saturating_sub(start.column).max(1)
+            })
+        };
+        Self {
+            message,
+            path: None,
+            line: start.line,
+            column: start.column + 1,
+            excerpt,
+            width,
+        }
+    }
+
+    fn at_end(message: String, source: &str) -> Self {
+        let (line, excerpt) = source
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .last()
+            .map_or((1, None), |(i, line)| (i + 1, Some(line.to_owned())));
+        let column = excerpt.as_ref().map_or(0, |line| line.chars().count()) + 1;
+        Self {
+            message,
+            path: None,
+            line,
+            column,
+            excerpt,
+            width: 1,
+        }
+    }
+
+    /// Set the path of the file that failed to parse, for display purposes.
+    #[must_use]
+    pub fn with_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    /// What went wrong, without location information.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// 1-based line of the offending token.
+    pub const fn line(&self) -> usize {
+        self.line
+    }
+
+    /// 1-based column (in characters) of the offending token.
+    pub const fn column(&self) -> usize {
+        self.column
+    }
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Self {
+            message,
+            path,
+            line,
+            column,
+            excerpt,
+            width,
+        } = self;
+        writeln!(f, "{message}")?;
+        let gutter = " ".repeat(line.to_string().len());
+        let path = path
+            .as_ref()
+            .map_or_else(|| "<input>".into(), |p| p.display().to_string());
+        write!(f, "{gutter}--> {path}:{line}:{column}")?;
+        if let Some(excerpt) = excerpt {
+            // Keep tabs so that the marker lines up with the excerpt
+            let padding: String = excerpt
+                .chars()
+                .take(column - 1)
+                .map(|c| if c == '\t' { '\t' } else { ' ' })
+                .collect();
+            let marker = "^".repeat(*width);
+            write!(
+                f,
+                "\n{gutter} |\n{line} | {excerpt}\n{gutter} | {padding}{marker}"
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Strip module paths from a type name, e.g., `alloc::vec::Vec<move_syn::Item>` -> `Vec<Item>`.
+///
+/// Also hides the bookkeeping parameters of delimited containers.
+fn short_type_name(name: &str) -> String {
+    let short = strip_paths(name);
+    let regex = regex::Regex::new(r"Operator<'(.)'>(, \w+, \d+, \w+)?>").expect("Valid regex");
+    regex.replace_all(&short, "'$1'>").into_owned()
+}
+
+fn strip_paths(name: &str) -> String {
+    let mut short = String::with_capacity(name.len());
+    let mut segment = String::new();
+    for c in name.chars() {
+        if c.is_alphanumeric() || c == '_' || c == ':' {
+            segment.push(c);
+        } else {
+            short.push_str(segment.rsplit("::").next().unwrap_or_default());
+            segment.clear();
+            short.push(c);
+        }
+    }
+    short.push_str(segment.rsplit("::").next().unwrap_or_default());
+    short
+}
+
+/// Avoid dumping whole groups into the error message.
+fn summary(tt: &TokenTree) -> String {
+    match tt {
+        TokenTree::Group(group) => {
+            let (open, close) = match group.delimiter() {
+                proc_macro2::Delimiter::Parenthesis => ("(", ")"),
+                proc_macro2::Delimiter::Brace => ("{", "}"),
+                proc_macro2::Delimiter::Bracket => ("[", "]"),
+                proc_macro2::Delimiter::None => ("", ""),
+            };
+            format!("{open} ... {close}")
+        }
+        _ => tt.to_string(),
+    }
+}
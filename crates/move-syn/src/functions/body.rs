@@ -0,0 +1,730 @@
+//! Move 2024 function bodies.
+//!
+//! The grammar is based on the [Move compiler's parser], but only cares about building a faithful
+//! tree for analysis (finding call sites, abort codes, emitted events, ...). In particular, binary
+//! operations are kept as a flat chain of operands, without operator precedence.
+//!
+//! [Move compiler's parser]: https://github.com/MystenLabs/sui/blob/129788902da4afc54a10af4ae45971a57ef080be/external-crates/move/crates/move-compiler/src/parser/syntax.rs
+use unsynn::*;
+
+pub use crate::HexPrefix;
+use crate::{Import, SyntaxIdent, UseFun, kw};
+
+unsynn! {
+    /// Identifier that is not a reserved Move keyword.
+    pub keyword Name != [
+        "abort", "as", "break", "const", "continue", "copy", "else", "false", "fun", "if", "let",
+        "loop", "match", "move", "mut", "return", "true", "use", "while",
+    ];
+
+    // === Blocks and statements ===
+
+    /// A `{ ... }` sequence of statements, e.g., a function body.
+    pub struct Block(BraceGroupContaining<Vec<Stmt>>);
+
+    /// A single item in a [`Block`].
+    pub enum Stmt {
+        /// `let pattern: T = expr;`
+        Let(Let),
+        /// `use fun path as Type.method;`
+        UseFun(UseFun),
+        /// `use address::module;`
+        Use(Import),
+        /// An expression optionally followed by `;`.
+        Expr(ExprStmt),
+        /// A stray `;`.
+        Empty(Semicolon),
+    }
+
+    /// `let pattern: T = expr;`
+    pub struct Let {
+        let_kw: kw::Let,
+        pub pattern: Pattern,
+        pub ty: Option<Cons<Colon, LocalType>>,
+        pub init: Option<Cons<Assign, Expr>>,
+        semicolon: Option<Semicolon>,
+    }
+
+    // === Expressions ===
+
+    /// A chain of operands joined by binary operators or casts.
+    ///
+    /// Operator precedence is not resolved: `a + b * c` is kept as `[a, (+, b), (*, c)]`.
+    pub struct Expr {
+        pub first: Term,
+        pub rest: Vec<Operation>,
+    }
+
+    /// Right-hand side of a binary operation or cast.
+    pub enum Operation {
+        /// `as T`
+        Cast(Cons<kw::As, LocalType>),
+        /// `<op> term`
+        Binary(BinOp, Term),
+    }
+
+    /// Binary operators, including assignment.
+    pub enum BinOp {
+        OrOr(OrOr),
+        AndAnd(AndAnd),
+        Eq(Equal),
+        Ne(NotEqual),
+        Le(Le),
+        Ge(Ge),
+        Shl(Shl),
+        Shr(Shr),
+        BitOr(Or),
+        BitAnd(And),
+        Xor(Caret),
+        Add(Plus),
+        Sub(Minus),
+        Mul(Star),
+        Div(Slash),
+        Mod(Percent),
+        Lt(Lt),
+        Gt(Gt),
+        Assign(PunctAlone<'='>),
+    }
+
+    /// A [`Primary`] expression with its prefix and postfix operators.
+    pub struct Term {
+        pub prefix: Vec<UnaryOp>,
+        pub primary: Primary,
+        pub postfix: Vec<Postfix>,
+    }
+
+    /// Prefix operators.
+    pub enum UnaryOp {
+        Not(Bang),
+        MutRef(Cons<And, kw::Mut>),
+        Ref(And),
+        Deref(Star),
+        Move(kw::Move),
+        Copy(kw::Copy),
+    }
+
+    /// Postfix operators: method calls, field accesses and indexing.
+    pub enum Postfix {
+        /// `.method(args)`, `.method<T>(args)` or `.macro!(args)`
+        MethodCall(MethodCall),
+        /// `.field` or `.0`
+        Field(Cons<Dot, Either<Name, Literal>>),
+        /// `[index]`
+        Index(BracketGroupContaining<CommaDelimitedVec<Expr>>),
+    }
+
+    /// `.method(args)`, `.method<T>(args)` or `.macro!(args)`
+    pub struct MethodCall {
+        dot: Dot,
+        pub name: Name,
+        bang: Option<Bang>,
+        pub type_args: Option<LocalTypeArgs>,
+        pub args: CallArgs,
+    }
+
+    /// Expressions that can be operands of operators without parentheses.
+    pub enum Primary {
+        Value(Value),
+        /// `vector[...]` or `vector<T>[...]`
+        Vector(VectorLit),
+        Block(Block),
+        /// `'label: { ... }`, `'label: loop { ... }`, `'label: while (...) { ... }`
+        Labeled(Labeled),
+        If(If),
+        While(While),
+        Loop(Loop),
+        Match(Match),
+        Return(Return),
+        Abort(Abort),
+        Break(Break),
+        Continue(Continue),
+        Lambda(Lambda),
+        /// `()`, `(expr)`, `(expr: T)` or `(a, b, ...)`
+        Paren(ParenthesisGroupContaining<CommaDelimitedVec<Annotated>>),
+        /// `path!(args)`
+        MacroCall(MacroCall),
+        /// `path(args)` or `$f(args)`
+        Call(Call),
+        /// `Path { field: expr, ... }`
+        Pack(Pack),
+        /// Local variable, constant or any other path.
+        Name(NameAccessChain),
+    }
+
+    /// Literal values.
+    pub enum Value {
+        /// `@0x1` or `@std`
+        Address(Cons<At, Either<Literal, Ident>>),
+        Bool(Either<kw::True, kw::False>),
+        /// `x"00ff"`
+        HexString(Cons<HexPrefix, LiteralString>),
+        /// Numbers (e.g., `1`, `0xff`, `1u64`) and byte strings (`b"..."`).
+        Literal(Cons<Literal, Except<PathSep>>),
+    }
+
+    /// `vector[...]` or `vector<T>[...]`
+    pub struct VectorLit {
+        vector_kw: kw::Vector,
+        pub type_args: Option<LocalTypeArgs>,
+        pub elements: BracketGroupContaining<CommaDelimitedVec<Expr>>,
+    }
+
+    /// `expr` or `expr: T`
+    pub struct Annotated {
+        pub expr: Expr,
+        pub ty: Option<Cons<Colon, LocalType>>,
+    }
+
+    /// `'label`
+    pub struct Label {
+        tick: LifetimeTick,
+        pub ident: Ident,
+    }
+
+    /// `'label: body`
+    pub struct Labeled {
+        pub label: Label,
+        colon: Colon,
+        pub body: LabeledBody,
+    }
+
+    pub enum LabeledBody {
+        Block(Block),
+        Loop(Loop),
+        While(While),
+    }
+
+    /// `if (cond) expr` or `if (cond) expr else expr`
+    pub struct If {
+        if_kw: kw::If,
+        pub cond: ParenthesisGroupContaining<Box<Expr>>,
+        pub then: Box<Expr>,
+        pub else_: Option<Cons<kw::Else, Box<Expr>>>,
+    }
+
+    /// `while (cond) expr`
+    pub struct While {
+        while_kw: kw::While,
+        pub cond: ParenthesisGroupContaining<Box<Expr>>,
+        pub body: Box<Expr>,
+    }
+
+    /// `loop expr`
+    pub struct Loop {
+        loop_kw: kw::Loop,
+        pub body: Box<Expr>,
+    }
+
+    /// `match (expr) { arms }`
+    pub struct Match {
+        match_kw: kw::Match,
+        pub subject: ParenthesisGroupContaining<Box<Expr>>,
+        pub arms: BraceGroupContaining<Vec<Cons<MatchArm, Option<Comma>>>>,
+    }
+
+    /// `pattern => expr` or `pattern if (guard) => expr`
+    pub struct MatchArm {
+        pub pattern: Pattern,
+        pub guard: Option<Cons<kw::If, Box<Expr>>>,
+        arrow: FatArrow,
+        pub body: Expr,
+    }
+
+    /// `return`, `return expr` or `return 'label expr`
+    pub struct Return {
+        return_kw: kw::Return,
+        pub label: Option<Label>,
+        pub value: Option<Box<Expr>>,
+    }
+
+    /// `abort` or `abort code`
+    pub struct Abort {
+        abort_kw: kw::Abort,
+        code: Option<Box<Expr>>,
+    }
+
+    /// `break`, `break expr` or `break 'label expr`
+    pub struct Break {
+        break_kw: kw::Break,
+        pub label: Option<Label>,
+        pub value: Option<Box<Expr>>,
+    }
+
+    /// `continue` or `continue 'label`
+    pub struct Continue {
+        continue_kw: kw::Continue,
+        pub label: Option<Label>,
+    }
+
+    /// `|params| body` or `|params| -> T body`
+    pub struct Lambda {
+        open: Or,
+        pub params: CommaDelimitedVec<LambdaParam>,
+        close: Or,
+        pub ret: Option<Cons<RArrow, LocalType>>,
+        pub body: Box<Expr>,
+    }
+
+    /// `pattern` or `pattern: T`
+    pub struct LambdaParam {
+        pub pattern: PatternAtom,
+        pub ty: Option<Cons<Colon, LocalType>>,
+    }
+
+    /// `path!(args)` or `path!<T>(args)`
+    pub struct MacroCall {
+        pub path: NameAccessChain,
+        bang: Bang,
+        pub type_args: Option<LocalTypeArgs>,
+        pub args: CallArgs,
+    }
+
+    /// `path(args)`, `path<T>(args)` or `$f(args)`
+    pub struct Call {
+        pub path: NameAccessChain,
+        pub args: CallArgs,
+    }
+
+    /// `(arg, ...)`
+    pub struct CallArgs(ParenthesisGroupContaining<CommaDelimitedVec<Expr>>);
+
+    /// `Path { field, field: expr, ... }`
+    pub struct Pack {
+        pub path: NameAccessChain,
+        pub fields: BraceGroupContaining<CommaDelimitedVec<FieldInit>>,
+    }
+
+    /// `field` or `field: expr`
+    pub struct FieldInit {
+        pub name: Name,
+        pub value: Option<Cons<Colon, Expr>>,
+    }
+
+    /// NameAccessChain =
+    ///     <LeadingNameAccess> <OptionalTypeArgs> ( "::" <Identifier> <OptionalTypeArgs> )^n
+    pub struct NameAccessChain {
+        pub leading: LeadingNameAccess,
+        pub type_args: Option<PathTypeArgs>,
+        pub rest: Vec<PathSegment>,
+    }
+
+    /// LeadingNameAccess = <NumericalAddress> | <Identifier> | <SyntaxIdentifier>
+    pub enum LeadingNameAccess {
+        Address(Cons<Literal, Expect<PathSep>>),
+        Syntax(SyntaxIdent),
+        Name(Name),
+    }
+
+    /// `::ident` or `::ident<T>`
+    pub struct PathSegment {
+        sep: PathSep,
+        pub ident: Ident,
+        pub type_args: Option<PathTypeArgs>,
+    }
+
+    /// Type arguments in expression paths.
+    ///
+    /// To disambiguate from comparisons, they must be followed by `(`, `{` or `::`.
+    pub struct PathTypeArgs {
+        pub args: LocalTypeArgs,
+        lookahead: Expect<Either<ParenthesisGroup, BraceGroup, PathSep>>,
+    }
+
+    // === Patterns ===
+
+    /// `pattern | pattern | ...`
+    pub struct Pattern {
+        pub first: PatternAtom,
+        pub rest: Vec<Cons<Or, PatternAtom>>,
+    }
+
+    pub enum PatternAtom {
+        /// `mut x`
+        Mut(Cons<kw::Mut, Name>),
+        /// `x @ pattern`
+        At {
+            binding: Name,
+            at: At,
+            pattern: Box<PatternAtom>,
+        },
+        Value(Value),
+        /// `(a, b, ...)`
+        Tuple(ParenthesisGroupContaining<CommaDelimitedVec<Pattern>>),
+        /// `Path`, `Path(a, ..)` or `Path { a, b: c, .. }`
+        Unpack {
+            path: NameAccessChain,
+            fields: Option<UnpackFields>,
+        },
+    }
+
+    pub enum UnpackFields {
+        Positional(ParenthesisGroupContaining<CommaDelimitedVec<Either<DotDot, Pattern>>>),
+        Named(BraceGroupContaining<CommaDelimitedVec<Either<DotDot, FieldPattern>>>),
+    }
+
+    /// `field`, `mut field` or `field: pattern`
+    pub struct FieldPattern {
+        mut_: Option<kw::Mut>,
+        pub name: Name,
+        pub pattern: Option<Cons<Colon, Pattern>>,
+    }
+
+    // === Types ===
+
+    /// Types in function bodies, i.e., in annotations and type arguments.
+    pub enum LocalType {
+        /// `&T` or `&mut T`
+        Ref(Cons<And, Option<kw::Mut>, Box<LocalType>>),
+        /// `()` or `(A, B, ...)`
+        Tuple(ParenthesisGroupContaining<CommaDelimitedVec<LocalType>>),
+        /// `|A, B| -> C`
+        Lambda {
+            open: Or,
+            params: CommaDelimitedVec<LocalType>,
+            close: Or,
+            ret: Option<Cons<RArrow, Box<LocalType>>>,
+        },
+        /// `$T`
+        Syntax(SyntaxIdent),
+        /// `T`, `module::T<A>`, `0x2::module::T`, `_`
+        Path {
+            leading: Either<Literal, Ident>,
+            rest: Vec<Cons<PathSep, Ident>>,
+            type_args: Option<LocalTypeArgs>,
+        },
+    }
+
+    /// `<A, B, ...>`
+    pub struct LocalTypeArgs {
+        lt: Lt,
+        pub args: CommaDelimitedVec<LocalType>,
+        gt: Gt,
+    }
+}
+
+/// A [`Primary`] expression ending in a block.
+#[derive(Debug)]
+struct BlockLike(Expr);
+
+impl Parser for BlockLike {
+    fn parser(tokens: &mut TokenIter) -> Result<Self> {
+        let at = tokens.clone().next();
+        // Fail early to avoid parsing other expressions twice
+        Expect::<BlockLikeStart>::parser(tokens)?;
+        let primary = Primary::parser(tokens)?;
+        if primary.ends_with_block() {
+            Ok(Self(primary.into()))
+        } else {
+            Error::other(
+                at,
+                tokens,
+                "expected an expression ending in a block".to_owned(),
+            )
+        }
+    }
+}
+
+impl ToTokens for BlockLike {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        self.0.to_tokens(tokens);
+    }
+}
+
+unsynn! {
+    /// An expression in statement position, optionally followed by `;`.
+    ///
+    /// Like in Rust, expressions ending in a block (`if`, `while`, `loop`, `match`, `{ ... }`)
+    /// also end the statement when in statement position, unless followed by something that can
+    /// only continue the expression (e.g., `.method()`, `+ 1`).
+    pub struct ExprStmt {
+        expr: StmtExpr,
+        semicolon: Option<Semicolon>,
+    }
+
+    enum StmtExpr {
+        BlockLike(BlockLike, Except<Continuation>),
+        Other(Expr),
+    }
+
+    /// Tokens that can start an expression ending in a block.
+    enum BlockLikeStart {
+        Block(BraceGroup),
+        Label(LifetimeTick),
+        If(kw::If),
+        While(kw::While),
+        Loop(kw::Loop),
+        Match(kw::Match),
+    }
+
+    /// Tokens that can only continue an expression, never start a new one.
+    enum Continuation {
+        Dot(Dot),
+        Index(BracketGroup),
+        As(kw::As),
+        OrOr(OrOr),
+        AndAnd(AndAnd),
+        Eq(Equal),
+        Ne(NotEqual),
+        Le(Le),
+        Ge(Ge),
+        Shl(Shl),
+        Shr(Shr),
+        BitOr(Or),
+        Xor(Caret),
+        Add(Plus),
+        Sub(Minus),
+        Div(Slash),
+        Mod(Percent),
+        Lt(Lt),
+        Gt(Gt),
+        Assign(Assign),
+    }
+}
+
+// === Accessors ===
+
+impl Block {
+    pub fn stmts(&self) -> impl Iterator<Item = &Stmt> {
+        self.0.content.iter()
+    }
+
+    /// Call `f` on every [`Term`] in this block, in source order, including nested ones.
+    pub fn visit_terms<'a>(&'a self, mut f: impl FnMut(&'a Term)) {
+        self.walk(&mut f);
+    }
+}
+
+impl ExprStmt {
+    pub const fn expr(&self) -> &Expr {
+        match &self.expr {
+            StmtExpr::BlockLike(BlockLike(expr), _) | StmtExpr::Other(expr) => expr,
+        }
+    }
+
+    /// Whether the expression is terminated by a `;`.
+    pub const fn has_semicolon(&self) -> bool {
+        self.semicolon.is_some()
+    }
+}
+
+impl Expr {
+    /// Call `f` on every [`Term`] in this expression, in source order, including nested ones.
+    pub fn visit_terms<'a>(&'a self, mut f: impl FnMut(&'a Term)) {
+        self.walk(&mut f);
+    }
+
+    /// The operands of this expression, ignoring operators and casts.
+    pub fn operands(&self) -> impl Iterator<Item = &Term> {
+        std::iter::once(&self.first).chain(self.rest.iter().filter_map(|op| match op {
+            Operation::Binary(_, term) => Some(term),
+            Operation::Cast(_) => None,
+        }))
+    }
+}
+
+impl From<Primary> for Expr {
+    fn from(primary: Primary) -> Self {
+        Self {
+            first: Term {
+                prefix: vec![],
+                primary,
+                postfix: vec![],
+            },
+            rest: vec![],
+        }
+    }
+}
+
+impl Expr {
+    fn ends_with_block(&self) -> bool {
+        self.rest.is_empty() && self.first.prefix.is_empty() && self.first.postfix.is_empty() && {
+            self.first.primary.ends_with_block()
+        }
+    }
+}
+
+impl Primary {
+    fn ends_with_block(&self) -> bool {
+        match self {
+            Self::Block(_) | Self::Labeled(_) | Self::Match(_) => true,
+            Self::If(If { then, else_, .. }) => else_
+                .as_ref()
+                .map_or_else(|| then.ends_with_block(), |e| e.second.ends_with_block()),
+            Self::While(While { body, .. }) | Self::Loop(Loop { body, .. }) => {
+                body.ends_with_block()
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Abort {
+    /// The abort code expression, if any.
+    pub fn code(&self) -> Option<&Expr> {
+        self.code.as_deref()
+    }
+}
+
+impl Call {
+    /// Whether this is a call to a macro lambda parameter, e.g., `$f(x)`.
+    pub const fn is_syntax_call(&self) -> bool {
+        matches!(self.path.leading, LeadingNameAccess::Syntax(_))
+    }
+}
+
+impl MethodCall {
+    /// Whether this is a macro method call, e.g., `v.do!(|x| ...)`.
+    pub const fn is_macro(&self) -> bool {
+        self.bang.is_some()
+    }
+}
+
+impl CallArgs {
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = &Expr> {
+        self.0.content.iter().map(|d| &d.value)
+    }
+}
+
+impl NameAccessChain {
+    /// The identifiers in this path, excluding a leading numerical address.
+    ///
+    /// E.g., `0x2::coin::value` yields `coin` and `value`; `$f` yields `f`.
+    pub fn idents(&self) -> impl Iterator<Item = &Ident> {
+        let leading = match &self.leading {
+            LeadingNameAccess::Address(_) => None,
+            LeadingNameAccess::Syntax(syntax) => Some(&syntax.ident),
+            LeadingNameAccess::Name(name) => Some(name.as_ref()),
+        };
+        leading
+            .into_iter()
+            .chain(self.rest.iter().map(|segment| &segment.ident))
+    }
+
+    /// The last identifier in this path, e.g., the function name in a call.
+    pub fn last(&self) -> Option<&Ident> {
+        self.idents().last()
+    }
+}
+
+// === Traversal ===
+
+trait Walk {
+    fn walk<'a>(&'a self, f: &mut dyn FnMut(&'a Term));
+}
+
+impl<T: Walk> Walk for Box<T> {
+    fn walk<'a>(&'a self, f: &mut dyn FnMut(&'a Term)) {
+        (**self).walk(f);
+    }
+}
+
+impl<T: Walk> Walk for Option<T> {
+    fn walk<'a>(&'a self, f: &mut dyn FnMut(&'a Term)) {
+        if let Some(inner) = self {
+            inner.walk(f);
+        }
+    }
+}
+
+impl<T: Walk, D, P, const MIN: usize, const MAX: usize> Walk for DelimitedVec<T, D, P, MIN, MAX> {
+    fn walk<'a>(&'a self, f: &mut dyn FnMut(&'a Term)) {
+        for d in self.iter() {
+            d.value.walk(f);
+        }
+    }
+}
+
+impl Walk for Block {
+    fn walk<'a>(&'a self, f: &mut dyn FnMut(&'a Term)) {
+        for stmt in self.stmts() {
+            match stmt {
+                Stmt::Let(Let {
+                    init: Some(init), ..
+                }) => init.second.walk(f),
+                Stmt::Expr(stmt) => stmt.expr().walk(f),
+                Stmt::Let(_) | Stmt::UseFun(_) | Stmt::Use(_) | Stmt::Empty(_) => (),
+            }
+        }
+    }
+}
+
+impl Walk for Expr {
+    fn walk<'a>(&'a self, f: &mut dyn FnMut(&'a Term)) {
+        self.first.walk(f);
+        for op in &self.rest {
+            if let Operation::Binary(_, term) = op {
+                term.walk(f);
+            }
+        }
+    }
+}
+
+impl Walk for Term {
+    fn walk<'a>(&'a self, f: &mut dyn FnMut(&'a Term)) {
+        f(self);
+        self.primary.walk(f);
+        for postfix in &self.postfix {
+            match postfix {
+                Postfix::MethodCall(call) => call.args.0.content.walk(f),
+                Postfix::Index(index) => index.content.walk(f),
+                Postfix::Field(_) => (),
+            }
+        }
+    }
+}
+
+impl Walk for Primary {
+    fn walk<'a>(&'a self, f: &mut dyn FnMut(&'a Term)) {
+        match self {
+            Self::Value(_) | Self::Continue(_) | Self::Name(_) => (),
+            Self::Vector(vector) => vector.elements.content.walk(f),
+            Self::Block(block) => block.walk(f),
+            Self::Labeled(labeled) => match &labeled.body {
+                LabeledBody::Block(block) => block.walk(f),
+                LabeledBody::Loop(loop_) => loop_.body.walk(f),
+                LabeledBody::While(while_) => {
+                    while_.cond.content.walk(f);
+                    while_.body.walk(f);
+                }
+            },
+            Self::If(if_) => {
+                if_.cond.content.walk(f);
+                if_.then.walk(f);
+                if let Some(else_) = &if_.else_ {
+                    else_.second.walk(f);
+                }
+            }
+            Self::While(while_) => {
+                while_.cond.content.walk(f);
+                while_.body.walk(f);
+            }
+            Self::Loop(loop_) => loop_.body.walk(f),
+            Self::Match(match_) => {
+                match_.subject.content.walk(f);
+                for arm in &match_.arms.content {
+                    if let Some(guard) = &arm.first.guard {
+                        guard.second.walk(f);
+                    }
+                    arm.first.body.walk(f);
+                }
+            }
+            Self::Return(Return { value, .. }) | Self::Break(Break { value, .. }) => value.walk(f),
+            Self::Abort(abort) => abort.code.walk(f),
+            Self::Lambda(lambda) => lambda.body.walk(f),
+            Self::Paren(paren) => {
+                for annotated in paren.content.iter() {
+                    annotated.value.expr.walk(f);
+                }
+            }
+            Self::MacroCall(call) => call.args.0.content.walk(f),
+            Self::Call(call) => call.args.0.content.walk(f),
+            Self::Pack(pack) => {
+                for field in pack.fields.content.iter() {
+                    if let Some(value) = &field.value.value {
+                        value.second.walk(f);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -3,6 +3,8 @@ use unsynn::*;
 
 use crate::{Generics, kw};
 
+#[cfg(feature = "fun-body")]
+pub mod body;
 #[cfg(feature = "fun-sig")]
 mod signature;
 
@@ -17,7 +19,7 @@ unsynn! {
         generics: Option<Generics>,
         args: Arguments,
         ret: Option<Returns>,
-        body: BraceGroup,
+        body: Body,
     }
 
     pub struct NativeFun {
@@ -31,6 +33,12 @@ unsynn! {
     }
 }
 
+cfg_if!(if #[cfg(feature = "fun-body")] {
+    pub(crate) use self::body::Block as Body;
+} else {
+    pub(crate) type Body = BraceGroup;
+});
+
 cfg_if!(if #[cfg(feature = "fun-sig")] {
     use self::signature::Arguments;
     use self::signature::Returns;
@@ -63,6 +71,22 @@ impl Function {
     pub const fn generics(&self) -> Option<&Generics> {
         self.generics.as_ref()
     }
+
+    #[cfg(feature = "fun-body")]
+    pub const fn body(&self) -> &body::Block {
+        &self.body
+    }
+}
+
+impl crate::MacroFun {
+    pub const fn ident(&self) -> &Ident {
+        &self.ident
+    }
+
+    #[cfg(feature = "fun-body")]
+    pub const fn body(&self) -> &body::Block {
+        &self.body
+    }
 }
 
 impl NativeFun {
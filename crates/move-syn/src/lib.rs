@@ -5,47 +5,63 @@
 
 use std::borrow::Cow;
 use std::collections::HashMap;
+use std::ops::Range;
 
 pub use unsynn;
 use unsynn::*;
 
+mod error;
 mod functions;
 #[cfg(test)]
 mod tests;
 mod vis;
 
+pub use self::error::ParseError;
 #[cfg(feature = "fun-sig")]
 pub use self::functions::FunctionArg;
+#[cfg(feature = "fun-body")]
+pub use self::functions::body;
 pub use self::functions::{Function, NativeFun};
 pub use self::vis::Visibility;
 
+/// Parse the contents of a Move file.
+///
+/// Takes care of [sanitizing](sanitize_for_tokenizer) the contents first. On failure, the error
+/// points to the offending token inside the item that failed to parse.
+pub fn parse_file(content: &str) -> std::result::Result<File, ParseError> {
+    let sanitized = sanitize_for_tokenizer(content);
+    let tokens: TokenStream = sanitized
+        .parse()
+        .map_err(|e| ParseError::from_lex(&e, &sanitized))?;
+    tokens.to_token_iter().parse_all().map_err(|e| {
+        let error = first_item_error(&tokens).unwrap_or(e);
+        ParseError::from_unsynn(&error, &sanitized)
+    })
+}
+
 /// Process raw Move code so that it can be used as input to Rust's tokenizer.
 ///
 /// Move's and Rust's tokens are very similar, with the exception of raw identifiers for which Move
 /// uses the syntax "`ident`".
 ///
-/// This function the backticks around identifiers, if found. Thus, we can re-use Rust's tokenizer
-/// afterwards, implemented by the [`proc_macro2`] crate. This is relevant because
-/// [`unsynn!`]-generated types requires Rust's [`TokenStream`] as input for parsing.
+/// This function replaces the backticks around identifiers with whitespace, if found. Thus, we can
+/// re-use Rust's tokenizer afterwards, implemented by the [`proc_macro2`] crate. This is relevant
+/// because [`unsynn!`]-generated types requires Rust's [`TokenStream`] as input for parsing.
+///
+/// Lines and columns are preserved, so token spans still point to the right place in `content`.
 pub fn sanitize_for_tokenizer(content: &str) -> String {
     let regex = raw_ident_regex();
-    let mut lines = content.lines().map(|line| {
-        // Ignore commented or doc lines
-        if !line.trim_start().starts_with("//") {
-            regex.replace(line, "$1")
-        } else {
-            Cow::Borrowed(line)
-        }
-    });
-    lines.next().map_or_else(String::new, |line| {
-        let mut sanitized = String::with_capacity(content.len());
-        sanitized.push_str(&line);
-        for line in lines {
-            sanitized.push('\n');
-            sanitized.push_str(&line);
-        }
-        sanitized
-    })
+    content
+        .split_inclusive('\n')
+        .map(|line| {
+            // Ignore commented or doc lines
+            if !line.trim_start().starts_with("//") {
+                regex.replace(line, " $1 ")
+            } else {
+                Cow::Borrowed(line)
+            }
+        })
+        .collect()
 }
 
 fn raw_ident_regex() -> regex::Regex {
@@ -78,6 +94,19 @@ pub mod kw {
         pub keyword Macro = "macro";
         pub keyword Vector = "vector";
         pub keyword Enum = "enum";
+        pub keyword Let = "let";
+        pub keyword If = "if";
+        pub keyword Else = "else";
+        pub keyword While = "while";
+        pub keyword Loop = "loop";
+        pub keyword Match = "match";
+        pub keyword Return = "return";
+        pub keyword Abort = "abort";
+        pub keyword Break = "break";
+        pub keyword Continue = "continue";
+        pub keyword Move = "move";
+        pub keyword True = "true";
+        pub keyword False = "false";
     }
 }
 
@@ -112,11 +141,13 @@ unsynn! {
         contents: BraceGroupContaining<Vec<Item>>,
     }
 
-    /// A Move language item.
-    pub struct Item {
-        pub attrs: Vec<Attributes>,
-        vis: Option<Vis>,
-        pub kind: ItemKind,
+    /// Everything in a module declaration before its contents.
+    struct ModuleHeader {
+        attrs: Vec<Attributes>,
+        keyword: kw::Module,
+        named_address: Ident,
+        path_sep: PathSep,
+        ident: Ident,
     }
 
     // === Attributes ===
@@ -169,17 +200,16 @@ unsynn! {
     /// <https://github.com/MystenLabs/sui/blob/129788902da4afc54a10af4ae45971a57ef080be/external-crates/move/crates/move-compiler/src/parser/syntax.rs#L1135-L1138>
     #[derive(Clone)]
     enum AttributeValue {
-        Lit(Literal),
+        Lit(Cons<Literal, Except<PathSep>>),
         //      NameAccessChain =
         //          <LeadingNameAccess> <OptionalTypeArgs>
         //              ( "::" <Identifier> <OptionalTypeArgs> )^n
         NameAccessChain {
-            // TODO: support NumericalAddress
             // LeadingNameAccess = <NumericalAddress> | <Identifier> | <SyntaxIdentifier>
-            leading_name_access: Either<SyntaxIdent, Ident>,
+            leading_name_access: Either<SyntaxIdent, Address>,
             // NOTE: ignoring <OptionalTypeArgs> for now
             // https://github.com/MystenLabs/sui/blob/129788902da4afc54a10af4ae45971a57ef080be/external-crates/move/crates/move-compiler/src/parser/syntax.rs#L3168
-            path: DelimitedVec<PathSep, Ident, TrailingDelimiter::Forbidden>,
+            path: Vec<Cons<PathSep, Ident>>,
         },
     }
 
@@ -245,12 +275,48 @@ unsynn! {
         ty: Type,
         /// `=`
         assign: Assign,
-        /// Hack to parse anything until (but excluding) a `;`
-        expr: Vec<Cons<Except<Semicolon>, TokenTree>>,
+        /// Value
+        expr: ConstExpr,
         /// `;`
         semicolon: Semicolon,
     }
 
+    /// Right-hand side of a constant declaration.
+    enum ConstExpr {
+        Value(Cons<ConstValue, Expect<Semicolon>>),
+        /// Hack to parse anything else until (but excluding) a `;`
+        Other(Vec<Cons<Except<Semicolon>, TokenTree>>),
+    }
+
+    /// Literal value of a constant.
+    pub enum ConstValue {
+        /// `true`
+        True(kw::True),
+        /// `false`
+        False(kw::False),
+        /// `@0x2`
+        Address {
+            at: At,
+            address: Literal,
+        },
+        /// `x"00ff"`
+        HexString {
+            prefix: HexPrefix,
+            hex: LiteralString,
+        },
+        /// `vector[...]` or `vector<T>[...]`
+        Vector {
+            kw: kw::Vector,
+            type_args: Option<TypeArgs>,
+            elements: BracketGroupContaining<CommaDelimitedVec<ConstValue>>,
+        },
+        /// Numbers like `1_000`, `0xff` or `10u8`, and byte strings like `b"abc"`
+        Lit(Literal),
+    }
+
+    /// Prefix of hex strings, like `x"00ff"`.
+    pub keyword HexPrefix = "x";
+
     // === Imports ===
 
     pub struct Import {
@@ -341,14 +407,6 @@ unsynn! {
         content: BraceGroupContaining<CommaDelimitedVec<EnumVariant>>,
     }
 
-    #[derive(Clone)]
-    pub struct EnumVariant {
-        pub attrs: Vec<Attributes>,
-        pub ident: Ident,
-        /// The fields of the enum variants. If none, it's a "unit" or "empty" variant.
-        pub fields: Option<FieldsKind>
-    }
-
     /// Kinds of fields for a Move enum.
     #[derive(Clone)]
     pub enum FieldsKind {
@@ -366,22 +424,6 @@ unsynn! {
     #[derive(Clone)]
     pub struct NamedFields(BraceGroupContaining<DelimitedVec<NamedField, Comma>>);
 
-    /// Named datatype field.
-    #[derive(Clone)]
-    pub struct NamedField {
-        pub attrs: Vec<Attributes>,
-        pub ident: Ident,
-        colon: Colon,
-        pub ty: Type,
-    }
-
-    /// Unnamed datatype field.
-    #[derive(Clone)]
-    pub struct UnnamedField {
-        pub attrs: Vec<Attributes>,
-        pub ty: Type,
-    }
-
     // === Generics ===
 
     /// The generics of a datatype or function.
@@ -447,7 +489,7 @@ unsynn! {
         generics: Option<MacroGenerics>,
         args: ParenthesisGroup,
         ret: Option<Cons<Colon, Either<MacroReturn, ParenthesisGroup>>>,
-        body: BraceGroup,
+        body: functions::Body,
     }
 
     struct MacroGenerics {
@@ -489,9 +531,9 @@ unsynn! {
     /// Name based on
     /// https://github.com/MystenLabs/sui/blob/129788902da4afc54a10af4ae45971a57ef080be/external-crates/move/crates/move-compiler/src/parser/syntax.rs#L675-L678
     #[derive(Clone)]
-    struct SyntaxIdent {
+    pub struct SyntaxIdent {
         dollar: Dollar,
-        ident: Ident,
+        pub ident: Ident,
     }
 
     // === Types ===
@@ -508,19 +550,12 @@ unsynn! {
         r#mut: Option<kw::Mut>,
     }
 
-    /// Non-reference type, used in datatype fields.
-    #[derive(Clone)]
-    pub struct Type {
-        pub path: ItemPath,
-        pub type_args: Option<TypeArgs>
-    }
-
     /// Path to an item.
     #[derive(Clone)]
     pub enum ItemPath {
         /// Fully qualified,
         Full {
-            named_address: Ident,
+            address: Address,
             sep0: PathSep,
             module: Ident,
             sep1: PathSep,
@@ -536,6 +571,15 @@ unsynn! {
         Ident(Ident),
     }
 
+    /// Leading address of a fully qualified path.
+    #[derive(Clone)]
+    pub enum Address {
+        /// Named address, e.g., `sui` in `sui::coin::Coin`.
+        Named(Ident),
+        /// Numerical address, e.g., `0x2` in `0x2::coin::Coin`.
+        Numeric(Literal),
+    }
+
     /// Angle bracket group (`<...>`) containing comma-delimited types.
     #[derive(Clone)]
     pub struct TypeArgs {
@@ -545,6 +589,48 @@ unsynn! {
     }
 }
 
+// === Nodes with source locations ===
+
+located! {
+    /// A Move language item.
+    pub struct Item {
+        pub attrs: Vec<Attributes>,
+        vis: Option<Vis>,
+        pub kind: ItemKind,
+    }
+
+    #[derive(Clone)]
+    pub struct EnumVariant {
+        pub attrs: Vec<Attributes>,
+        pub ident: Ident,
+        /// The fields of the enum variants. If none, it's a "unit" or "empty" variant.
+        pub fields: Option<FieldsKind>,
+    }
+
+    /// Named datatype field.
+    #[derive(Clone)]
+    pub struct NamedField {
+        pub attrs: Vec<Attributes>,
+        pub ident: Ident,
+        colon: Colon,
+        pub ty: Type,
+    }
+
+    /// Unnamed datatype field.
+    #[derive(Clone)]
+    pub struct UnnamedField {
+        pub attrs: Vec<Attributes>,
+        pub ty: Type,
+    }
+
+    /// Non-reference type, used in datatype fields.
+    #[derive(Clone)]
+    pub struct Type {
+        pub path: ItemPath,
+        pub type_args: Option<TypeArgs>,
+    }
+}
+
 impl File {
     pub fn into_modules(self) -> impl Iterator<Item = Module> {
         match self {
@@ -634,15 +720,7 @@ impl Module {
 
     /// Resolve all datatype field types to their fully-qualified paths.
     pub fn fully_qualify_datatype_field_types(&mut self) -> &mut Self {
-        // Collect all imported types and their paths
-        let imports: HashMap<_, _> = self
-            .items()
-            .filter_map(|item| match &item.kind {
-                ItemKind::Import(import) => Some(import),
-                _ => None,
-            })
-            .flat_map(|import| import.flatten())
-            .collect();
+        let imports = self.flat_imports();
 
         // Resolve datatype fields' types
         for item in &mut self.contents.content {
@@ -662,15 +740,44 @@ impl Module {
         self
     }
 
+    /// Resolve `ty`, as written in this module outside of any datatype or function, to its
+    /// fully-qualified path, like [`Self::fully_qualify_datatype_field_types`] does for field
+    /// types.
+    pub fn fully_qualify_type(&self, ty: &mut Type) {
+        ty.resolve(&self.flat_imports(), &[]);
+    }
+
     pub fn items(&self) -> impl Iterator<Item = &Item> {
         self.contents.content.iter()
     }
 
+    /// Whether this module is left out of regular builds; see [`Attributes::is_test_gated`].
+    pub fn is_test_gated(&self) -> bool {
+        self.attrs.iter().any(Attributes::is_test_gated)
+    }
+
+    /// Keep only the items for which `f` returns `true`.
+    pub fn retain_items(&mut self, f: impl FnMut(&Item) -> bool) -> &mut Self {
+        self.contents.content.retain(f);
+        self
+    }
+
     #[cfg(test)]
     pub fn into_items(self) -> impl Iterator<Item = Item> {
         self.contents.content.into_iter()
     }
 
+    /// All imported types and modules and their paths.
+    fn flat_imports(&self) -> HashMap<Ident, FlatImport> {
+        self.items()
+            .filter_map(|item| match &item.kind {
+                ItemKind::Import(import) => Some(import),
+                _ => None,
+            })
+            .flat_map(|import| import.flatten())
+            .collect()
+    }
+
     fn add_implicit_imports(&mut self, mut implicit_imports: HashMap<Ident, Import>) -> &mut Self {
         // Filter out any that were shadowed by existing imports
         for item in self.items() {
@@ -688,6 +795,7 @@ impl Module {
                 attrs: vec![],
                 vis: None,
                 kind: ItemKind::Import(import),
+                range: 0..0,
             })
         }
         self
@@ -843,6 +951,82 @@ impl Attributes {
         )
     }
 
+    /// Text of a doc comment (`/// ...`) or `#[doc = "..."]`, unescaped.
+    pub fn doc(&self) -> Option<String> {
+        let [
+            Delimited {
+                value: Attribute::Doc(doc),
+                ..
+            },
+        ] = &self.contents.content[..]
+        else {
+            return None;
+        };
+        Some(unescape(doc.third.as_str()))
+    }
+
+    /// Whether this contains an `#[error]` attribute, marking a Move 2024 "clever error" constant.
+    ///
+    /// Also true for `#[error(code = <N>)]`.
+    pub fn is_error(&self) -> bool {
+        self.error_attribute().is_some()
+    }
+
+    /// The `<N>` in `#[error(code = <N>)]`.
+    pub fn error_code(&self) -> Option<&Literal> {
+        let Some(SubAttribute::List(args)) = self.error_attribute()? else {
+            return None;
+        };
+        args.content.iter().find_map(|d| match &*d.value {
+            Attribute::Other {
+                ident,
+                sub:
+                    Some(SubAttribute::Eq(Cons {
+                        second: AttributeValue::Lit(lit),
+                        ..
+                    })),
+            } if ident == "code" => Some(&lit.first),
+            _ => None,
+        })
+    }
+
+    /// Whether this contains a `#[test_only]` attribute.
+    pub fn is_test_only(&self) -> bool {
+        self.has_attribute("test_only")
+    }
+
+    /// Whether this contains a `#[test]` or `#[random_test]` attribute, marking a unit test.
+    pub fn is_test(&self) -> bool {
+        self.has_attribute("test") || self.has_attribute("random_test")
+    }
+
+    /// Whether this contains a `#[mode(...)]` attribute, restricting the annotated item to the
+    /// given compilation modes.
+    pub fn is_mode(&self) -> bool {
+        self.has_attribute("mode")
+    }
+
+    /// Whether the annotated item is left out of regular builds, i.e., if this is
+    /// [`#[test_only]`](Self::is_test_only), [`#[test]`](Self::is_test) or
+    /// [`#[mode(...)]`](Self::is_mode).
+    pub fn is_test_gated(&self) -> bool {
+        self.is_test_only() || self.is_test() || self.is_mode()
+    }
+
+    fn has_attribute(&self, name: &str) -> bool {
+        self.contents
+            .content
+            .iter()
+            .any(|d| matches!(&d.value, Attribute::Other { ident, .. } if ident == name))
+    }
+
+    fn error_attribute(&self) -> Option<Option<&SubAttribute>> {
+        self.contents.content.iter().find_map(|d| match &d.value {
+            Attribute::Other { ident, sub } if ident == "error" => Some(sub.as_ref()),
+            _ => None,
+        })
+    }
+
     /// Everything inside the bracket group, `#[...]`.
     pub const fn contents(&self) -> &impl ToTokens {
         &self.contents.content
@@ -870,6 +1054,18 @@ impl Attributes {
     }
 }
 
+impl Item {
+    /// Whether this item is left out of regular builds; see [`Attributes::is_test_gated`].
+    pub fn is_test_gated(&self) -> bool {
+        self.attrs.iter().any(Attributes::is_test_gated)
+    }
+
+    /// Byte range of this item in the source, including its attributes.
+    pub fn byte_range(&self) -> Range<usize> {
+        self.range.clone()
+    }
+}
+
 impl ItemKind {
     /// Whether this item is a datatype (enum/struct) declaration.
     pub const fn is_datatype(&self) -> bool {
@@ -877,6 +1073,59 @@ impl ItemKind {
     }
 }
 
+impl Const {
+    pub const fn ident(&self) -> &Ident {
+        &self.ident
+    }
+
+    pub const fn type_(&self) -> &Type {
+        &self.ty
+    }
+
+    /// The literal value of the constant.
+    ///
+    /// `None` for other expressions, like `1 << 8` or `MAX - 1`.
+    pub const fn value(&self) -> Option<&ConstValue> {
+        match &self.expr {
+            ConstExpr::Value(value) => Some(&value.first),
+            ConstExpr::Other(_) => None,
+        }
+    }
+}
+
+impl ConstValue {
+    /// The elements of a `vector[...]` literal.
+    pub fn elements(&self) -> Option<impl Iterator<Item = &Self> + '_> {
+        match self {
+            Self::Vector { elements, .. } => Some(elements.content.iter().map(|d| &d.value)),
+            _ => None,
+        }
+    }
+
+    /// The bytes of a byte string (`b"..."`) or hex string (`x"..."`) literal.
+    ///
+    /// `None` for other values or if the literal is malformed.
+    pub fn byte_string(&self) -> Option<Vec<u8>> {
+        match self {
+            Self::Lit(lit) => {
+                let lit = lit.to_string();
+                unescape_bytes(lit.strip_prefix("b\"")?.strip_suffix('"')?)
+            }
+            Self::HexString { hex, .. } => {
+                let hex = hex.as_str();
+                if hex.len() % 2 != 0 {
+                    return None;
+                }
+                (0..hex.len())
+                    .step_by(2)
+                    .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+                    .collect()
+            }
+            _ => None,
+        }
+    }
+}
+
 impl Struct {
     pub fn abilities(&self) -> impl Iterator<Item = &Ability> {
         use StructKind as K;
@@ -945,6 +1194,27 @@ impl NamedFields {
     }
 }
 
+impl EnumVariant {
+    /// Byte range of this variant in the source, including its attributes.
+    pub fn byte_range(&self) -> Range<usize> {
+        self.range.clone()
+    }
+}
+
+impl NamedField {
+    /// Byte range of this field in the source, including its attributes.
+    pub fn byte_range(&self) -> Range<usize> {
+        self.range.clone()
+    }
+}
+
+impl UnnamedField {
+    /// Byte range of this field in the source, including its attributes.
+    pub fn byte_range(&self) -> Range<usize> {
+        self.range.clone()
+    }
+}
+
 impl PositionalFields {
     pub fn new() -> Self {
         Self(ParenthesisGroupContaining {
@@ -970,6 +1240,14 @@ impl Default for PositionalFields {
 }
 
 impl Type {
+    /// Byte range of this type in the source.
+    ///
+    /// Resolved paths (see [`Module::fully_qualify_datatype_field_types`]) keep the range of the
+    /// path as written.
+    pub fn byte_range(&self) -> Range<usize> {
+        self.range.clone()
+    }
+
     /// Resolve the types' path to a fully-qualified declaration, recursively.
     fn resolve(&mut self, imports: &HashMap<Ident, FlatImport>, generics: &[Ident]) {
         use ItemPath as P;
@@ -992,10 +1270,11 @@ impl Type {
                 else {
                     return;
                 };
+                let span = self.path_span();
                 P::Full {
-                    named_address: named_address.clone(),
+                    address: Address::Named(respan(named_address, span)),
                     sep0: PathSep::default(),
-                    module: module.clone(),
+                    module: respan(module, span),
                     sep1: PathSep::default(),
                     item: r#type.clone(),
                 }
@@ -1009,12 +1288,13 @@ impl Type {
                 else {
                     return;
                 };
+                let span = self.path_span();
                 P::Full {
-                    named_address: named_address.clone(),
+                    address: Address::Named(respan(named_address, span)),
                     sep0: PathSep::default(),
-                    module: module.clone(),
+                    module: respan(module, span),
                     sep1: PathSep::default(),
-                    item: r#type.clone(),
+                    item: respan(r#type, ident.span()),
                 }
             }
             // Already fully-qualified types or idents shadowed by generics should be left alone
@@ -1022,6 +1302,131 @@ impl Type {
         };
         self.path = resolved;
     }
+
+    /// Span of the first token in the path, as written in the source.
+    fn path_span(&self) -> Span {
+        match &self.path {
+            ItemPath::Full { address, .. } => address.span(),
+            ItemPath::Module { module, .. } => module.span(),
+            ItemPath::Ident(ident) => ident.span(),
+        }
+    }
+}
+
+impl Address {
+    /// The named address, if this isn't a numerical one.
+    pub const fn as_named(&self) -> Option<&Ident> {
+        match self {
+            Self::Named(ident) => Some(ident),
+            Self::Numeric(_) => None,
+        }
+    }
+
+    /// The 32 bytes of a numerical address, written either in hex (`0x2`) or decimal (`2`).
+    ///
+    /// `None` for named addresses or numbers that don't fit in an address.
+    pub fn to_bytes(&self) -> Option<[u8; 32]> {
+        match self {
+            Self::Named(_) => None,
+            Self::Numeric(lit) => address_bytes(&lit.to_string()),
+        }
+    }
+
+    fn span(&self) -> Span {
+        match self {
+            Self::Named(ident) => ident.span(),
+            Self::Numeric(lit) => lit.span(),
+        }
+    }
+}
+
+/// Parse the 32 big-endian bytes of a numerical address or `u256`, written either in hex (`0x2`)
+/// or decimal (`2`).
+///
+/// Underscores are allowed as separators, like in Move number literals.
+pub fn address_bytes(number: &str) -> Option<[u8; 32]> {
+    let number = number.replace('_', "");
+    let mut bytes = [0; 32];
+    if let Some(hex) = number.strip_prefix("0x") {
+        if hex.is_empty() || hex.len() > 64 {
+            return None;
+        }
+        let padded = format!("{hex:0>64}");
+        for (byte, pair) in bytes.iter_mut().zip(padded.as_bytes().chunks(2)) {
+            *byte = u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok()?;
+        }
+    } else {
+        if number.is_empty() {
+            return None;
+        }
+        for digit in number.chars() {
+            // bytes = bytes * 10 + digit
+            let mut carry = digit.to_digit(10)?;
+            for byte in bytes.iter_mut().rev() {
+                let value = u32::from(*byte) * 10 + carry;
+                *byte = (value & 0xff) as u8;
+                carry = value >> 8;
+            }
+            if carry != 0 {
+                return None;
+            }
+        }
+    }
+    Some(bytes)
+}
+
+/// Undo the escaping of a string literal's contents.
+fn unescape(escaped: &str) -> String {
+    let mut unescaped = String::with_capacity(escaped.len());
+    let mut chars = escaped.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => unescaped.push('\n'),
+            Some('t') => unescaped.push('\t'),
+            Some('r') => unescaped.push('\r'),
+            Some('0') => unescaped.push('\0'),
+            Some(other) => unescaped.push(other),
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+/// Bytes of the contents of a Move byte string, e.g., `\x41\n` -> `[0x41, 0x0a]`.
+fn unescape_bytes(escaped: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(escaped.len());
+    let mut chars = escaped.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            let mut buf = [0; 4];
+            bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            continue;
+        }
+        match chars.next()? {
+            'n' => bytes.push(b'\n'),
+            't' => bytes.push(b'\t'),
+            'r' => bytes.push(b'\r'),
+            '0' => bytes.push(b'\0'),
+            'x' => {
+                let hex: String = chars.by_ref().take(2).collect();
+                bytes.push(u8::from_str_radix(&hex, 16).ok()?);
+            }
+            other @ ('\\' | '"') => bytes.push(other as u8),
+            _ => return None,
+        }
+    }
+    Some(bytes)
+}
+
+/// Clone of `ident` pointing to `span` in the source.
+fn respan(ident: &Ident, span: Span) -> Ident {
+    let mut ident = ident.clone();
+    ident.set_span(span);
+    ident
 }
 
 impl TypeArgs {
@@ -1043,6 +1448,11 @@ impl MaybeRefType {
         self.r#ref.as_ref().is_some_and(|r| r.r#mut.is_none())
     }
 
+    /// Whether this is a mutable reference to a type.
+    pub fn is_mut_ref(&self) -> bool {
+        self.r#ref.as_ref().is_some_and(|r| r.r#mut.is_some())
+    }
+
     /// Reference to the Move type
     pub const fn type_(&self) -> &Type {
         &self.r#type
@@ -1065,6 +1475,92 @@ pub enum FlatImport {
 
 // === Misc helpers ===
 
+/// Re-parse a file item by item, so that the error points inside the offending item instead of at
+/// the start of its module.
+fn first_item_error(tokens: &TokenStream) -> Option<Error> {
+    fn items_error(tokens: &mut TokenIter) -> Option<Error> {
+        while EndOfStream::parse(tokens).is_err() {
+            if let Err(error) = Item::parse(tokens) {
+                return Some(error);
+            }
+        }
+        None
+    }
+
+    let mut tokens = tokens.to_token_iter();
+    loop {
+        if let Err(error) = ModuleHeader::parse(&mut tokens) {
+            return Some(error);
+        }
+        if Semicolon::parse(&mut tokens).is_ok() {
+            return items_error(&mut tokens);
+        }
+        let contents = BraceGroupContaining::<TokenStream>::parse(&mut tokens).ok()?;
+        if let Some(error) = items_error(&mut contents.content.to_token_iter()) {
+            return Some(error);
+        }
+        if EndOfStream::parse(&mut tokens).is_ok() {
+            return None;
+        }
+    }
+}
+
+/// Declare structs that remember the byte range of their tokens in the source.
+///
+/// Like the ones generated by [`unsynn!`], but with an extra private `range` field that is set
+/// while parsing. This can't be derived from the tokens afterwards because [`unsynn`] doesn't keep
+/// the spans of punctuation and group delimiters.
+macro_rules! located {
+    ($(
+        $(#[$attr:meta])*
+        $vis:vis struct $name:ident {
+            $(
+                $(#[$field_attr:meta])*
+                $field_vis:vis $field:ident: $ty:ty
+            ),* $(,)?
+        }
+    )*) => {$(
+        $(#[$attr])*
+        #[derive(Debug)]
+        $vis struct $name {
+            $(
+                $(#[$field_attr])*
+                $field_vis $field: $ty,
+            )*
+            range: Range<usize>,
+        }
+
+        #[expect(exported_private_dependencies, reason = "Used by unsynn's `TokenIter`")]
+        impl Parser for $name {
+            fn parser(tokens: &mut TokenIter) -> Result<Self> {
+                let mut cursor = tokens.clone();
+                let mut this = Self {
+                    $($field: <$ty>::parser(tokens)?,)*
+                    range: 0..0,
+                };
+                // Re-emitting the tokens preserves their top-level structure, so we can tell how
+                // many were consumed
+                let consumed = this.to_token_stream().into_iter().count();
+                if let Some(first) = cursor.next() {
+                    let last = consumed
+                        .checked_sub(2)
+                        .and_then(|n| cursor.nth(n))
+                        .unwrap_or_else(|| first.clone());
+                    this.range = first.span().byte_range().start..last.span().byte_range().end;
+                }
+                Ok(this)
+            }
+        }
+
+        impl ToTokens for $name {
+            fn to_tokens(&self, tokens: &mut TokenStream) {
+                $(self.$field.to_tokens(tokens);)*
+            }
+        }
+    )*};
+}
+use located;
+
 /// Box an iterator, necessary when returning different types that implement [`Iterator`].
 trait IteratorBoxed<'a>: Iterator + 'a {
     fn boxed(self) -> Box<dyn Iterator<Item = Self::Item> + 'a>
@@ -16,6 +16,130 @@ fn const_val() {
     assert_eq!(ast.tokens_to_string(), decl.tokens_to_string());
 }
 
+#[test]
+fn const_literal_values() {
+    for decl in [
+        "const MAX: u64 = 18_446_744_073_709_551_615u64;",
+        "const MASK: u8 = 0xff;",
+        "const ENABLED: bool = true;",
+        "const ADMIN: address = @0x2;",
+        r#"const DST: vector<u8> = b"transfer";"#,
+        r#"const BYTES: vector<u8> = x"00ff";"#,
+        r#"const HEX: vector<vector<u8>> = vector[b"0", b"1"];"#,
+        "const EMPTY: vector<u64> = vector<u64>[];",
+    ] {
+        let ast: Const = decl.to_token_iter().parse_all().unwrap();
+        assert!(ast.value().is_some(), "{decl}");
+        assert_eq!(
+            ast.tokens_to_string().replace(' ', ""),
+            decl.tokens_to_string().replace(' ', "")
+        );
+    }
+
+    let ast: Const = "const HEX: vector<vector<u8>> = vector[b\"0\", b\"1\"];"
+        .to_token_iter()
+        .parse_all()
+        .unwrap();
+    assert_eq!(ast.value().unwrap().elements().unwrap().count(), 2);
+
+    for decl in [
+        "const MAX_SLICE_SIZE: u64 = 256 * 1024;",
+        "const MAX: u128 = ((1u256 << 128) - 1) as u128;",
+        "const SCALE: u64 = FLOAT_SCALING;",
+    ] {
+        let ast: Const = decl.to_token_iter().parse_all().unwrap();
+        assert!(ast.value().is_none(), "{decl}");
+        assert_eq!(
+            ast.tokens_to_string().replace(' ', ""),
+            decl.tokens_to_string().replace(' ', "")
+        );
+    }
+}
+
+#[test]
+fn clever_error_constants() {
+    let module = parse_file(indoc::indoc! {r#"
+        module pkg::errors;
+
+        #[error]
+        const ENotFound: vector<u8> = b"Key \"k\" not found\x21";
+        #[error(code = 7)]
+        const EBytes: vector<u8> = x"4142";
+        /// Not an error.
+        const ENotClever: u64 = 0;
+    "#})
+    .unwrap()
+    .into_modules()
+    .next()
+    .unwrap();
+    let consts: Vec<_> = module
+        .items()
+        .filter_map(|item| match &item.kind {
+            ItemKind::Const(c) => Some((&item.attrs, c)),
+            _ => None,
+        })
+        .collect();
+
+    let [
+        (attrs, not_found),
+        (attrs_code, bytes),
+        (attrs_not_clever, _),
+    ] = &consts[..]
+    else {
+        panic!("Expected 3 constants");
+    };
+    assert!(attrs.iter().any(Attributes::is_error));
+    assert!(attrs.iter().all(|attr| attr.error_code().is_none()));
+    assert_eq!(
+        not_found
+            .value()
+            .and_then(ConstValue::byte_string)
+            .as_deref(),
+        Some(&b"Key \"k\" not found!"[..])
+    );
+
+    assert!(attrs_code.iter().any(Attributes::is_error));
+    let code = attrs_code.iter().find_map(Attributes::error_code).unwrap();
+    assert_eq!(code.to_string(), "7");
+    assert_eq!(
+        bytes.value().and_then(ConstValue::byte_string).as_deref(),
+        Some(&b"AB"[..])
+    );
+
+    assert!(!attrs_not_clever.iter().any(Attributes::is_error));
+}
+
+#[test]
+fn test_gated_items() {
+    let mut modules = parse_file(indoc::indoc! {r#"
+        module pkg::lib {
+            public struct Prod {}
+            #[test_only]
+            public struct Fixture {}
+            #[test, expected_failure]
+            fun fails() { abort 0 }
+            #[random_test]
+            fun random(_: u64) {}
+            #[mode(bench)]
+            fun bench() {}
+            #[allow(unused)]
+            const UNUSED: u64 = 0;
+        }
+
+        #[test_only]
+        module pkg::lib_tests {}
+    "#})
+    .unwrap()
+    .into_modules();
+    let lib = modules.next().unwrap();
+    let lib_tests = modules.next().unwrap();
+
+    let gated: Vec<_> = lib.items().map(Item::is_test_gated).collect();
+    assert_eq!(gated, [false, true, true, true, true, false]);
+    assert!(!lib.is_test_gated());
+    assert!(lib_tests.is_test_gated());
+}
+
 #[test]
 fn empty_struct() {
     ensure_roundtrip_move_struct("struct OTW {}");
@@ -125,6 +249,59 @@ fn struct_with_annotated_fields() {
     );
 }
 
+#[test]
+fn struct_with_numeric_address_paths() {
+    ensure_roundtrip_move_struct(
+        "
+        #[ext(moverox(type_(T = 0x2::sui::SUI)))]
+        public struct Vault<phantom T> has key {
+            id: 0x2::object::UID,
+            balance: 0x0000000000000000000000000000000000000000000000000000000000000002::balance::Balance<T>
+        }",
+    );
+
+    let ty: Type = "0x2::balance::Balance<T>"
+        .to_token_iter()
+        .parse_all()
+        .unwrap();
+    let ItemPath::Full { address, .. } = &ty.path else {
+        panic!("Expected fully qualified path");
+    };
+    assert!(address.as_named().is_none());
+    let mut sui = [0; 32];
+    sui[31] = 2;
+    assert_eq!(address.to_bytes(), Some(sui));
+}
+
+#[test]
+fn numeric_address_bytes() {
+    let mut two = [0; 32];
+    two[31] = 2;
+    assert_eq!(address_bytes("0x2"), Some(two));
+    assert_eq!(address_bytes("0x0002"), Some(two));
+    assert_eq!(address_bytes("2"), Some(two));
+    assert_eq!(
+        address_bytes(&format!("0x{}", "f".repeat(64))),
+        Some([0xff; 32])
+    );
+    assert_eq!(address_bytes(&format!("0x{}", "f".repeat(65))), None);
+    assert_eq!(
+        address_bytes(
+            "115792089237316195423570985008687907853269984665640564039457584007913129639935"
+        ),
+        Some([0xff; 32])
+    );
+    assert_eq!(
+        address_bytes(
+            "115792089237316195423570985008687907853269984665640564039457584007913129639936"
+        ),
+        None
+    );
+    assert_eq!(address_bytes("0x"), None);
+    assert_eq!(address_bytes(""), None);
+    assert_eq!(address_bytes("0xg"), None);
+}
+
 #[test]
 fn empty_tuple_struct() {
     ensure_roundtrip_move_struct("public struct Wut()");
@@ -202,3 +379,100 @@ fn ensure_roundtrip_move_module(decl: &str) {
     let ast: Module = decl.to_token_iter().parse_all().unwrap();
     assert_eq!(ast.tokens_to_string(), decl.tokens_to_string());
 }
+
+#[test]
+fn parse_error_points_inside_item() {
+    let code = indoc::indoc! {"
+    module pkg::mod;
+
+    public struct Good has drop {}
+
+    public struct Bad has {
+        value: u64,
+    }
+    "};
+    let err = parse_file(code).unwrap_err().with_path("sources/mod.move");
+    assert_eq!((err.line(), err.column()), (5, 19));
+    insta::assert_snapshot!(err, @r"
+    expected `BraceGroupContaining<DelimitedVec<NamedField, ','>>`, found `has`
+     --> sources/mod.move:5:19
+      |
+    5 | public struct Bad has {
+      |                   ^^^
+    ");
+}
+
+#[test]
+fn parse_error_in_legacy_module() {
+    let code = indoc::indoc! {"
+    module pkg::a {
+        struct A {}
+    }
+
+    module pkg::b {
+        struct B { value u64 }
+    }
+    "};
+    let err = parse_file(code).unwrap_err();
+    assert_eq!(err.line(), 6);
+    assert_eq!(err.path(), None);
+}
+
+#[test]
+fn lex_error_location() {
+    let err = parse_file("module pkg::mod;\n\nconst X: u64 = 1;\n)").unwrap_err();
+    assert_eq!(err.line(), 4);
+}
+
+#[test]
+fn byte_ranges() {
+    let code = indoc::indoc! {"
+    module pkg::mod;
+
+    use sui::balance::Balance;
+
+    /// Docs.
+    public struct Pool<phantom T> has key {
+        id: UID,
+        balance: Balance<T>,
+    }
+    "};
+    let mut module = parse_file(code).unwrap().into_modules().next().unwrap();
+    module.fully_qualify_datatype_field_types();
+    let item = module.items().last().unwrap();
+    assert!(code[item.byte_range()].starts_with("/// Docs."));
+    assert!(code[item.byte_range()].ends_with('}'));
+
+    let ItemKind::Struct(Struct {
+        kind: StructKind::Braced(braced),
+        ..
+    }) = &item.kind
+    else {
+        panic!("Expected braced struct");
+    };
+    let field = braced.fields().last().unwrap();
+    assert_eq!(&code[field.byte_range()], "balance: Balance<T>");
+    // Resolved paths still point to the type as written
+    assert_eq!(&code[field.ty.byte_range()], "Balance<T>");
+}
+
+#[test]
+fn fully_qualify_type() {
+    let code = indoc::indoc! {"
+    module pkg::mod;
+
+    use sui::sui::{Self, SUI};
+    "};
+    let module = parse_file(code).unwrap().into_modules().next().unwrap();
+    for written in ["SUI", "sui::SUI", "sui::sui::SUI", "0x2::sui::SUI"] {
+        let mut ty: Type = written.to_token_iter().parse_all().unwrap();
+        module.fully_qualify_type(&mut ty);
+        let ItemPath::Full { module, item, .. } = &ty.path else {
+            panic!("Expected fully qualified path for {written}");
+        };
+        assert_eq!(
+            (module.to_string(), item.to_string()),
+            ("sui".into(), "SUI".into())
+        );
+    }
+}
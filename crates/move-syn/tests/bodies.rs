@@ -0,0 +1,138 @@
+use std::path::{Path, PathBuf};
+
+use itertools::Itertools as _;
+use move_syn::body::{Block, Postfix, Primary, Term};
+use move_syn::{File, ItemKind, Module};
+use testresult::TestResult;
+use unsynn::{IParse as _, ToTokens};
+
+/// Every function and macro body in the repository's Move packages must parse.
+#[test]
+fn all_packages() -> TestResult {
+    let mut files = vec![];
+    visit(&move_dir()?, &mut files)?;
+    assert!(!files.is_empty());
+    for path in files {
+        let content = std::fs::read_to_string(&path)?;
+        let _: File = move_syn::sanitize_for_tokenizer(&content)
+            .as_str()
+            .to_token_iter()
+            .parse_all()
+            .map_err(|e| format!("{}: {e}", path.display()))?;
+    }
+    Ok(())
+}
+
+mod sui_framework {
+    use super::*;
+
+    test_files!("sui-framework" {
+        balance,
+        coin,
+        event,
+        vec_map,
+    });
+}
+
+mod move_stdlib {
+    use super::*;
+
+    test_files!("move-stdlib" {
+        option,
+        vector,
+    });
+}
+
+mod deepbook {
+    use super::*;
+
+    test_files!("deepbook" {
+        pool,
+    });
+}
+
+macro_rules! test_files {
+    ($package:literal { $($file: ident),* $(,)? }) => {$(
+        #[test]
+        fn $file() -> TestResult {
+            let path = move_dir()?
+                .join($package)
+                .join("sources")
+                .join(concat!(stringify!($file), ".move"));
+            let content = std::fs::read_to_string(path)?;
+            let ast: File = move_syn::sanitize_for_tokenizer(&content)
+                .as_str()
+                .to_token_iter()
+                .parse_all()?;
+            insta::assert_snapshot!(bodies_summary(ast));
+            Ok(())
+        }
+    )*};
+}
+
+pub(crate) use test_files;
+
+/// List the calls and aborts in each function or macro body.
+fn bodies_summary(ast: File) -> String {
+    let modules = ast.into_modules().collect_vec();
+    modules
+        .iter()
+        .flat_map(Module::items)
+        .filter_map(|item| match &item.kind {
+            ItemKind::Function(fun) => Some((fun.ident().to_string(), summary(fun.body()))),
+            ItemKind::MacroFun(mac) => Some((format!("{}!", mac.ident()), summary(mac.body()))),
+            _ => None,
+        })
+        .map(|(name, lines)| {
+            let lines = lines.iter().map(|line| format!("    {line}")).join("\n");
+            format!("{name}\n{lines}")
+        })
+        .join("\n")
+}
+
+fn summary(body: &Block) -> Vec<String> {
+    let mut lines = vec![];
+    body.visit_terms(|term: &Term| {
+        match &term.primary {
+            Primary::Call(call) => lines.push(format!("call {}", to_string(&call.path))),
+            Primary::MacroCall(call) => lines.push(format!("macro {}", to_string(&call.path))),
+            Primary::Abort(abort) => lines.push(format!(
+                "abort {}",
+                abort.code().map(to_string).unwrap_or_default()
+            )),
+            _ => (),
+        }
+        for postfix in &term.postfix {
+            if let Postfix::MethodCall(call) = postfix {
+                let bang = if call.is_macro() { "!" } else { "" };
+                lines.push(format!("method .{}{bang}", call.name.as_str()));
+            }
+        }
+    });
+    lines
+}
+
+fn to_string(t: &impl ToTokens) -> String {
+    t.tokens_to_string().replace(" :: ", "::")
+}
+
+fn visit(dir: &Path, files: &mut Vec<PathBuf>) -> TestResult {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            visit(&path, files)?;
+        } else if path.extension().is_some_and(|ext| ext == "move") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn move_dir() -> TestResult<PathBuf> {
+    Ok(Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .ok_or("../")?
+        .parent()
+        .ok_or("../")?
+        .join("move"))
+}
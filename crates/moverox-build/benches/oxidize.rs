@@ -0,0 +1,23 @@
+//! Oxidizing the vendored Sui framework on one thread and on several.
+
+use std::path::{Path, PathBuf};
+
+fn main() {
+    divan::main();
+}
+
+fn sui_framework() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("../../move/sui-framework")
+}
+
+#[divan::bench(args = [1, 2, 4, 8], sample_count = 10)]
+fn sui_framework_with_jobs(jobs: usize) {
+    moverox_build::move_package(sui_framework(), "sui")
+        .with_implicit_sui_imports()
+        .with_call_descriptors()
+        .with_events()
+        .emit_rerun_if_changed(false)
+        .jobs(jobs)
+        .generate()
+        .expect("Oxidizing the Sui framework");
+}
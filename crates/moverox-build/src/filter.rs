@@ -0,0 +1,350 @@
+//! Selecting which modules and datatypes of a package to oxidize.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use move_syn::unsynn::Ident;
+use move_syn::{Address, FieldsKind, Generics, Item, ItemKind, ItemPath, Module, StructKind, Type};
+use regex::Regex;
+
+/// Module and datatype name patterns set on the [`Builder`](crate::Builder).
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Filters {
+    pub(crate) allow_modules: Vec<Pattern>,
+    pub(crate) deny_modules: Vec<Pattern>,
+    pub(crate) allow_types: Vec<Pattern>,
+    pub(crate) deny_types: Vec<Pattern>,
+}
+
+/// A glob, or a regex if prefixed with `regex:`, matching a whole name.
+#[derive(Clone, Debug)]
+pub(crate) struct Pattern(Regex);
+
+/// A datatype of the package, as `(module, name)`.
+type DatatypeKey = (String, String);
+
+impl Pattern {
+    /// # Panics
+    ///
+    /// If `pattern` is an invalid regex
+    pub(crate) fn new(pattern: &str) -> Self {
+        let regex = pattern
+            .strip_prefix("regex:")
+            .map_or_else(|| glob_to_regex(pattern), |regex| format!("^(?:{regex})$"));
+        Self(Regex::new(&regex).unwrap_or_else(|err| panic!("Invalid pattern {pattern}: {err}")))
+    }
+
+    fn matches(&self, name: &str) -> bool {
+        self.0.is_match(name)
+    }
+}
+
+/// `*` matches any sequence of characters and `?` any single one; anything else is literal.
+fn glob_to_regex(glob: &str) -> String {
+    let mut regex = String::from("^");
+    let mut buf = [0; 4];
+    for c in glob.chars() {
+        match c {
+            '*' => regex.push_str(".*"),
+            '?' => regex.push('.'),
+            c => regex.push_str(&regex::escape(c.encode_utf8(&mut buf))),
+        }
+    }
+    regex.push('$');
+    regex
+}
+
+fn any_matches(patterns: &[Pattern], name: &str) -> bool {
+    patterns.iter().any(|p| p.matches(name))
+}
+
+impl Filters {
+    pub(crate) const fn is_empty(&self) -> bool {
+        self.allow_modules.is_empty()
+            && self.deny_modules.is_empty()
+            && self.allow_types.is_empty()
+            && self.deny_types.is_empty()
+    }
+
+    /// Whether the module is selected as a whole, i.e., its constants and functions as well.
+    fn selects_module(&self, module: &Module) -> bool {
+        let name = module.ident.to_string();
+        if any_matches(&self.deny_modules, &name) {
+            return false;
+        }
+        let no_allowlist = self.allow_modules.is_empty() && self.allow_types.is_empty();
+        no_allowlist || any_matches(&self.allow_modules, &name)
+    }
+
+    /// Whether the datatype `ident` of the module is selected.
+    fn selects_datatype(&self, module: &Module, ident: &Ident) -> bool {
+        let qualified = format!("{}::{}::{ident}", module.named_address, module.ident);
+        if any_matches(&self.deny_types, &qualified) {
+            return false;
+        }
+        self.selects_module(module) || any_matches(&self.allow_types, &qualified)
+    }
+
+    /// The datatypes that `module` declares and those they might depend on, for
+    /// [`select`](Self::select).
+    pub(crate) fn graph(&self, module: &Module, functions: bool) -> ModuleGraph {
+        let module_name = module.ident.to_string();
+        let whole = self.selects_module(module);
+        let mut graph = ModuleGraph {
+            named_address: module.named_address.to_string(),
+            name: module_name.clone(),
+            datatypes: vec![],
+            signatures: vec![],
+        };
+        for item in module.items() {
+            let generics = item_generics(item);
+            if let Some(ident) = datatype_ident(item) {
+                let mut references = vec![];
+                for ty in field_types(item) {
+                    referenced_datatypes(ty, &module_name, &generics, &mut references);
+                }
+                graph.datatypes.push(Datatype {
+                    name: ident.to_string(),
+                    selected: self.selects_datatype(module, ident),
+                    references,
+                });
+            } else if whole && functions && crate::is_callable(item) {
+                for ty in signature_types(item) {
+                    referenced_datatypes(ty, &module_name, &generics, &mut graph.signatures);
+                }
+            }
+        }
+        graph
+    }
+
+    /// Select the datatypes to keep out of the [`graph`](Self::graph)s of all the modules of the
+    /// package: the selected ones, the datatypes in the signatures of the functions of the
+    /// selected modules and everything those depend on.
+    ///
+    /// `package` is the package's address, if known, for paths like `0x2::coin::Coin` into the
+    /// package itself.
+    pub(crate) fn select(&self, graphs: &[ModuleGraph], package: Option<[u8; 32]>) -> Selection {
+        let named_addresses: HashSet<_> = graphs.iter().map(|g| g.named_address.as_str()).collect();
+        let mut datatypes = HashMap::new();
+        for graph in graphs {
+            for datatype in &graph.datatypes {
+                datatypes.insert(
+                    (graph.name.clone(), datatype.name.clone()),
+                    (graph, datatype),
+                );
+            }
+        }
+        let in_package = |reference: &Reference| match &reference.address {
+            None => true,
+            Some(RefAddress::Named(named)) => named_addresses.contains(named.as_str()),
+            Some(RefAddress::Numeric(bytes)) => package.is_some() && *bytes == package,
+        };
+
+        // Start from the selected datatypes and the datatypes in the signatures of the functions
+        // of the selected modules
+        let mut selected = HashSet::new();
+        let mut pending = vec![];
+        for graph in graphs {
+            for datatype in &graph.datatypes {
+                if datatype.selected {
+                    selected.insert((graph.name.clone(), datatype.name.clone()));
+                }
+            }
+            pending.extend(
+                graph
+                    .signatures
+                    .iter()
+                    .filter(|r| in_package(r))
+                    .map(|r| r.key.clone()),
+            );
+        }
+        pending.extend(selected.iter().cloned());
+
+        // Then pull in everything they depend on
+        let mut kept = HashSet::new();
+        while let Some(key) = pending.pop() {
+            let Some((_, datatype)) = datatypes.get(&key) else {
+                continue;
+            };
+            if !kept.insert(key) {
+                continue;
+            }
+            pending.extend(
+                datatype
+                    .references
+                    .iter()
+                    .filter(|r| in_package(r))
+                    .map(|r| r.key.clone()),
+            );
+        }
+
+        let dependencies: BTreeSet<_> = kept
+            .difference(&selected)
+            .map(|key| {
+                let (graph, _) = datatypes[key];
+                format!("{}::{}::{}", graph.named_address, key.0, key.1)
+            })
+            .collect();
+        Selection {
+            kept,
+            dependencies: dependencies.into_iter().collect(),
+        }
+    }
+
+    /// Leave the datatypes that weren't [selected](Self::select), and the constants and functions
+    /// if the module wasn't selected as a whole, out of `module`.
+    pub(crate) fn retain(&self, module: &mut Module, selection: &Selection) {
+        let module_name = module.ident.to_string();
+        let whole = self.selects_module(module);
+        module.retain_items(|item| match &item.kind {
+            ItemKind::Struct(_) | ItemKind::Enum(_) => datatype_ident(item).is_some_and(|ident| {
+                selection
+                    .kept
+                    .contains(&(module_name.clone(), ident.to_string()))
+            }),
+            ItemKind::Const(_) | ItemKind::Function(_) | ItemKind::NativeFun(_) => whole,
+            _ => true,
+        });
+    }
+}
+
+/// The datatypes a module declares and those they might refer to; see [`Filters::graph`].
+///
+/// Unlike the module, it can be sent to other threads.
+pub(crate) struct ModuleGraph {
+    named_address: String,
+    name: String,
+    datatypes: Vec<Datatype>,
+    /// Datatypes that the signatures of the module's functions might refer to, if the module is
+    /// selected as a whole.
+    signatures: Vec<Reference>,
+}
+
+struct Datatype {
+    name: String,
+    /// Whether it's selected itself, rather than as a dependency.
+    selected: bool,
+    /// Datatypes that its fields might refer to.
+    references: Vec<Reference>,
+}
+
+/// A datatype that a type might refer to, which is only in the package if its address is.
+struct Reference {
+    /// `None` if the path isn't fully qualified, i.e., it's in the package.
+    address: Option<RefAddress>,
+    key: DatatypeKey,
+}
+
+enum RefAddress {
+    Named(String),
+    Numeric(Option<[u8; 32]>),
+}
+
+/// The datatypes kept after filtering; see [`Filters::select`].
+pub(crate) struct Selection {
+    kept: HashSet<DatatypeKey>,
+    /// Fully qualified names of the datatypes kept only as dependencies.
+    pub(crate) dependencies: Vec<String>,
+}
+
+const fn datatype_ident(item: &Item) -> Option<&Ident> {
+    match &item.kind {
+        ItemKind::Struct(s) => Some(&s.ident),
+        ItemKind::Enum(e) => Some(&e.ident),
+        _ => None,
+    }
+}
+
+fn item_generics(item: &Item) -> Vec<&Ident> {
+    let generics = match &item.kind {
+        ItemKind::Struct(s) => s.generics.as_ref(),
+        ItemKind::Enum(e) => e.generics.as_ref(),
+        ItemKind::Function(f) => f.generics(),
+        ItemKind::NativeFun(f) => f.generics(),
+        _ => None,
+    };
+    generics
+        .into_iter()
+        .flat_map(Generics::generics)
+        .map(|g| &g.ident)
+        .collect()
+}
+
+/// Types of the fields of a datatype.
+fn field_types(item: &Item) -> Vec<&Type> {
+    fn fields_kind_types(fields: &FieldsKind) -> Vec<&Type> {
+        match fields {
+            FieldsKind::Named(named) => named.fields().map(|f| &f.ty).collect(),
+            FieldsKind::Positional(positional) => positional.fields().map(|f| &f.ty).collect(),
+        }
+    }
+    match &item.kind {
+        ItemKind::Struct(s) => match &s.kind {
+            StructKind::Braced(braced) => braced.fields().map(|f| &f.ty).collect(),
+            StructKind::Tuple(tuple) => tuple.fields().map(|f| &f.ty).collect(),
+        },
+        ItemKind::Enum(e) => e
+            .variants()
+            .filter_map(|v| v.fields.as_ref())
+            .flat_map(fields_kind_types)
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// Types of the arguments and returns of a function.
+fn signature_types(item: &Item) -> Vec<&Type> {
+    match &item.kind {
+        ItemKind::Function(f) => f
+            .arguments()
+            .map(|arg| arg.type_().type_())
+            .chain(f.returns().map(|ret| ret.type_()))
+            .collect(),
+        ItemKind::NativeFun(f) => f
+            .arguments()
+            .map(|arg| arg.type_().type_())
+            .chain(f.returns().map(|ret| ret.type_()))
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// Push the datatypes that `ty` might refer to, itself or in its type arguments, as written in
+/// `module`.
+///
+/// Identifiers that aren't datatypes of the module, like `u64` or `vector`, are pushed as well and
+/// ignored later on.
+fn referenced_datatypes(ty: &Type, module: &str, generics: &[&Ident], out: &mut Vec<Reference>) {
+    match &ty.path {
+        ItemPath::Ident(ident) if !generics.contains(&ident) => {
+            out.push(Reference {
+                address: None,
+                key: (module.to_owned(), ident.to_string()),
+            });
+        }
+        ItemPath::Module { module, item, .. } => {
+            out.push(Reference {
+                address: None,
+                key: (module.to_string(), item.to_string()),
+            });
+        }
+        ItemPath::Full {
+            address,
+            module,
+            item,
+            ..
+        } => {
+            let address = match address {
+                Address::Named(named) => RefAddress::Named(named.to_string()),
+                Address::Numeric(_) => RefAddress::Numeric(address.to_bytes()),
+            };
+            out.push(Reference {
+                address: Some(address),
+                key: (module.to_string(), item.to_string()),
+            });
+        }
+        _ => (),
+    }
+    for ty in ty.type_args.iter().flat_map(|args| args.types()) {
+        referenced_datatypes(ty, module, generics, out);
+    }
+}
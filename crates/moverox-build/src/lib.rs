@@ -2,49 +2,140 @@
 
 //! Build script utilities to oxidize an entire Move package.
 
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
 use std::error::Error as StdError;
 use std::fs;
-use std::io::Write as _;
 use std::path::{Path, PathBuf};
 
-use move_syn::sanitize_for_tokenizer;
-use move_syn::unsynn::{IParse as _, Ident, Span, ToTokens as _, TokenStream};
-use moverox_codegen::ModuleGen as _;
+use move_syn::unsynn::{IParse as _, Ident, LiteralString, Span, ToTokens as _, TokenStream};
+use moverox_codegen::{AddressMap, ModuleGen as _};
 
+use self::manifest::DependencySources;
+pub use self::manifest::{Dependency, Manifest};
+use self::published::Environment;
+pub use self::published::Publication;
+use self::stamp::Stamp;
+pub use self::workspace::{Workspace, move_workspace};
+
+mod filter;
+mod manifest;
+mod parallel;
+mod published;
+mod stamp;
 #[cfg(test)]
 mod tests;
+mod workspace;
 
 const MOVE_FILE_EXT: &str = "move";
+const GENERATED_HEADER: &str = "// This file is @generated by moverox-build.\n";
 
 type Result<T, E = Box<dyn StdError + Send + Sync + 'static>> = ::std::result::Result<T, E>;
 
 /// Initialize a builder for oxidizing the Move package at `pkg_path`, being available to import
 /// later via the `name`.
+///
+/// If the package has a `Move.toml`, it is used to fill in defaults when building; see
+/// [`Builder::build`].
 pub fn move_package(pkg_path: impl AsRef<Path>, name: &str) -> Builder<'_> {
     Builder {
         pkg_path: pkg_path.as_ref().to_owned(),
-        name,
+        name: Cow::Borrowed(name),
+        manifest: None,
         with_implicit_iota_imports: false,
         with_implicit_sui_imports: false,
+        call_descriptors: false,
+        return_decoders: false,
+        events: false,
+        file_per_module: false,
+        test_cfg: None,
         emit_rerun_if_changed: std::env::var_os("CARGO").is_some(),
         out_dir: None,
-        moverox_path: "::moverox".to_token_stream(),
+        jobs: None,
+        moverox_path: "::moverox".to_owned(),
         address_map: Default::default(),
+        numeric_address_map: Default::default(),
+        manifest_address_map: Default::default(),
+        address_values: Default::default(),
         published_at: None,
+        environment: None,
+        publications: Default::default(),
+        published_constants: false,
+        type_origins: Default::default(),
+        type_origins_file: None,
+        dependency_sources: Default::default(),
+        dependencies: vec![],
+        filters: Default::default(),
+        datatype_attrs: vec![],
+        type_map: Default::default(),
+        renames: Default::default(),
+        type_defaults: Default::default(),
     }
 }
 
+/// Initialize a builder for oxidizing the Move package at `pkg_path`, being available to import
+/// later via the package name in its `Move.toml`.
+pub fn move_package_from_manifest(pkg_path: impl AsRef<Path>) -> Result<Builder<'static>> {
+    let manifest = Manifest::read(&pkg_path)?;
+    let mut builder = move_package(&pkg_path, "");
+    builder.name = Cow::Owned(manifest.name.clone());
+    builder.manifest = Some(manifest);
+    Ok(builder)
+}
+
+// Paths and identifiers are kept as strings rather than tokens, which can't be shared with the
+// threads oxidizing the modules.
 pub struct Builder<'a> {
     pkg_path: PathBuf,
-    name: &'a str,
+    name: Cow<'a, str>,
+    manifest: Option<Manifest>,
     with_implicit_iota_imports: bool,
     with_implicit_sui_imports: bool,
+    call_descriptors: bool,
+    return_decoders: bool,
+    events: bool,
+    file_per_module: bool,
+    /// Configuration predicate for test-only Move items, if generating them.
+    test_cfg: Option<String>,
     emit_rerun_if_changed: bool,
     out_dir: Option<PathBuf>,
-    moverox_path: TokenStream,
-    address_map: HashMap<Ident, TokenStream>,
-    published_at: Option<&'a str>,
+    /// Number of threads to oxidize the modules on, if set.
+    jobs: Option<usize>,
+    moverox_path: String,
+    address_map: HashMap<String, String>,
+    numeric_address_map: HashMap<[u8; 32], String>,
+    /// Mappings of named addresses learned from `Move.toml`s, which explicit ones override.
+    manifest_address_map: HashMap<String, String>,
+    /// Values of named addresses, learned from `Move.toml`s.
+    address_values: HashMap<String, [u8; 32]>,
+    published_at: Option<Cow<'a, str>>,
+    environment: Option<Environment>,
+    /// Publications of the package keyed by environment, from `Published.toml` or `Move.lock`.
+    publications: BTreeMap<String, Publication>,
+    published_constants: bool,
+    /// Explicit ones, then the ones from the file once read.
+    type_origins: HashMap<String, String>,
+    type_origins_file: Option<PathBuf>,
+    dependency_sources: DependencySources,
+    /// Directories and manifests of the dependencies found, learned from `Move.toml`s.
+    dependencies: Vec<(PathBuf, Manifest)>,
+    filters: filter::Filters,
+    /// Scope and change of each rule for the derives and attributes of datatypes, in order.
+    datatype_attrs: Vec<(Option<String>, DatatypeChange)>,
+    /// Rust type and serde adapter of each mapped Move type.
+    type_map: BTreeMap<String, (String, Option<String>)>,
+    /// Rust names of Move datatypes, fields and enum variants, keyed by their paths.
+    renames: BTreeMap<String, String>,
+    /// Move types defaulting type parameters, keyed by their paths.
+    type_defaults: BTreeMap<String, String>,
+}
+
+/// A change to the derives or attributes of some datatypes; see [`Builder::add_derives`].
+#[derive(Clone, Debug)]
+enum DatatypeChange {
+    AddDerives(Vec<String>),
+    RemoveDerives(Vec<String>),
+    AddAttribute(String),
 }
 
 impl<'a> Builder<'a> {
@@ -68,16 +159,294 @@ impl<'a> Builder<'a> {
         self
     }
 
+    /// Generate a function returning a typed `MoveCall` for each `public` or `entry` function.
+    ///
+    /// The generated functions have the same names as the Move ones and take the type arguments
+    /// as type tags, pure values as their Rust equivalents and objects as `ObjectArg`s, whether
+    /// passed by reference or by value, so that calls are checked against the Move signatures at
+    /// compile time. If the package address isn't known (see [`published_at`](Self::published_at)), it's
+    /// the first argument.
+    pub const fn with_call_descriptors(mut self) -> Self {
+        self.call_descriptors = true;
+        self
+    }
+
+    /// Generate a `<name>_returns` function decoding the return values of each `public` or `entry`
+    /// function that returns any, e.g., from a dev-inspect transaction.
+    ///
+    /// The generated functions take the `(TypeTag, bytes)` pairs of the return values, check each
+    /// type tag against the Move return type and BCS-decode them into a tuple. References are
+    /// decoded as the values they point to. Functions returning an `Option` that isn't imported get
+    /// none, since it's generated as Rust's `Option`. It's an error for a decoder to have the name
+    /// of a [call descriptor](Self::with_call_descriptors). The generated code needs the `bcs`
+    /// feature of `moverox`.
+    pub const fn with_return_decoders(mut self) -> Self {
+        self.return_decoders = true;
+        self
+    }
+
+    /// Generate an `Event` enum for the package, with a variant for each non-generic struct that
+    /// has `copy` and `drop` or is annotated with `#[ext(moverox(event))]`.
+    ///
+    /// `Event::from_raw` decodes an event from its struct tag and BCS bytes, looking up the
+    /// variant by the struct tag's module and name instead of trying each event type in turn. The
+    /// generated code needs the `bcs` feature of `moverox`.
+    pub const fn with_events(mut self) -> Self {
+        self.events = true;
+        self
+    }
+
+    /// Generate a `published_at` module with a constant for the address the package is published
+    /// at on each environment, e.g., `published_at::MAINNET`, from its `Published.toml` or
+    /// `Move.lock`.
+    ///
+    /// Useful for passing to call descriptors when the package address isn't set, or for
+    /// comparing with the addresses of objects from different networks. Building fails if the
+    /// package has no publications.
+    pub const fn with_published_constants(mut self) -> Self {
+        self.published_constants = true;
+        self
+    }
+
+    /// Write each module to a file of its own, at `{out_dir}/{name}/{module}.rs`, which the file
+    /// at `{out_dir}/{name}.rs` includes.
+    ///
+    /// [`include_oxidized!`] works the same either way, but compiler errors in the generated
+    /// code point to lines in much smaller files.
+    ///
+    /// [`include_oxidized!`]: https://docs.rs/moverox/latest/moverox/macro.include_oxidized.html
+    pub const fn with_file_per_module(mut self) -> Self {
+        self.file_per_module = true;
+        self
+    }
+
+    /// Generate the Move items and modules annotated with `#[test_only]`, `#[test]` or
+    /// `#[mode(...)]` behind `#[cfg(test)]`.
+    ///
+    /// By default, those are left out, since they aren't part of the published package. Either
+    /// way, they're left out of the package-level `AbortError`, `clever_error` and `Event`.
+    pub fn with_test_items(mut self) -> Self {
+        self.test_cfg = Some("test".to_owned());
+        self
+    }
+
+    /// Like [`with_test_items`](Self::with_test_items), but behind `#[cfg(feature = "<feature>")]`
+    /// so that other crates can use them by enabling the cargo `feature`.
+    pub fn with_test_items_feature(mut self, feature: &str) -> Self {
+        self.test_cfg = Some(format!("feature = {feature:?}"));
+        self
+    }
+
+    /// Like [`with_test_items`](Self::with_test_items), but behind `#[cfg(<predicate>)]`, e.g.,
+    /// `any(test, feature = "testing")`.
+    pub fn with_test_items_cfg(mut self, predicate: &str) -> Self {
+        self.test_cfg = Some(predicate.to_owned());
+        self
+    }
+
+    /// Only oxidize the modules whose names match `pattern`, e.g., `pool` or `*_events`.
+    ///
+    /// Patterns are globs, where `*` matches any sequence of characters and `?` any single one,
+    /// or regexes if prefixed with `regex:`, e.g., `regex:(pool|vault)`. Either way, they must
+    /// match the whole name. This and [`allow_types`](Self::allow_types) may be called several
+    /// times, and if neither is, all modules are allowed.
+    ///
+    /// Datatypes that the selected ones depend on are oxidized as well, even if they were
+    /// [denied](Self::deny_types), so that the generated code always compiles. The same goes for
+    /// the datatypes in the signatures of the selected modules' functions, when generating
+    /// [call descriptors](Self::with_call_descriptors) or
+    /// [return decoders](Self::with_return_decoders). Those included only as dependencies are
+    /// listed in a comment at the top of the generated file.
+    ///
+    /// # Panics
+    ///
+    /// If `pattern` is an invalid regex
+    pub fn allow_modules(mut self, pattern: &str) -> Self {
+        self.filters
+            .allow_modules
+            .push(filter::Pattern::new(pattern));
+        self
+    }
+
+    /// Don't oxidize the modules whose names match `pattern`, unless they declare datatypes that
+    /// the oxidized ones depend on. Takes precedence over the allowlists.
+    ///
+    /// See [`allow_modules`](Self::allow_modules) for the pattern syntax.
+    ///
+    /// # Panics
+    ///
+    /// If `pattern` is an invalid regex
+    pub fn deny_modules(mut self, pattern: &str) -> Self {
+        self.filters
+            .deny_modules
+            .push(filter::Pattern::new(pattern));
+        self
+    }
+
+    /// Oxidize the datatypes whose fully qualified names match `pattern`, e.g.,
+    /// `deepbook::pool::Pool` or `deepbook::*::*Event`, without the rest of their modules.
+    ///
+    /// See [`allow_modules`](Self::allow_modules) for the pattern syntax and how dependencies are
+    /// handled.
+    ///
+    /// # Panics
+    ///
+    /// If `pattern` is an invalid regex
+    pub fn allow_types(mut self, pattern: &str) -> Self {
+        self.filters.allow_types.push(filter::Pattern::new(pattern));
+        self
+    }
+
+    /// Don't oxidize the datatypes whose fully qualified names match `pattern`, unless the
+    /// oxidized ones depend on them. Takes precedence over the allowlists.
+    ///
+    /// See [`allow_modules`](Self::allow_modules) for the pattern syntax.
+    ///
+    /// # Panics
+    ///
+    /// If `pattern` is an invalid regex
+    pub fn deny_types(mut self, pattern: &str) -> Self {
+        self.filters.deny_types.push(filter::Pattern::new(pattern));
+        self
+    }
+
+    /// Add derives to the datatypes in `scope`, e.g., `PartialOrd, Ord` or
+    /// `schemars::JsonSchema`, after the standard ones.
+    ///
+    /// `scope` is `*` for all datatypes, the name of a module, e.g., `pool`, or a datatype as
+    /// `module::Datatype`. Rules for all datatypes apply first, then the ones for modules and
+    /// finally the ones for single datatypes, each in the order they were added. The derives in
+    /// `#[ext(moverox(derive(...)))]` annotations of the Move datatypes are added last.
+    ///
+    /// # Panics
+    ///
+    /// If `scope` or `derives` are invalid
+    pub fn add_derives(self, scope: &str, derives: &str) -> Self {
+        let derives = derive_paths(derives);
+        self.datatype_rule(scope, DatatypeChange::AddDerives(derives))
+    }
+
+    /// Remove derives from the datatypes in `scope`, given by path or by name, e.g., `Hash`.
+    ///
+    /// See [`add_derives`](Self::add_derives) for the scope syntax and the order rules apply in.
+    /// Removing the `serde` or `MoveDatatype` derives breaks the rest of the generated code, as
+    /// does removing any of the standard ones from an [event](Self::with_events).
+    ///
+    /// # Panics
+    ///
+    /// If `scope` or `derives` are invalid
+    pub fn remove_derives(self, scope: &str, derives: &str) -> Self {
+        let derives = derive_paths(derives);
+        self.datatype_rule(scope, DatatypeChange::RemoveDerives(derives))
+    }
+
+    /// Add an outer attribute to the datatypes in `scope`, without the `#[...]`, e.g.,
+    /// `serde(deny_unknown_fields)`.
+    ///
+    /// See [`add_derives`](Self::add_derives) for the scope syntax and the order rules apply in.
+    ///
+    /// # Panics
+    ///
+    /// If `scope` or `attribute` are invalid
+    pub fn add_attribute(self, scope: &str, attribute: &str) -> Self {
+        syn::parse_str::<syn::Meta>(attribute)
+            .unwrap_or_else(|err| panic!("Invalid attribute {attribute}: {err}"));
+        self.datatype_rule(scope, DatatypeChange::AddAttribute(attribute.to_owned()))
+    }
+
+    fn datatype_rule(mut self, scope: &str, change: DatatypeChange) -> Self {
+        let scope = (scope != "*").then(|| {
+            let valid = scope
+                .split("::")
+                .all(|part| syn::parse_str::<syn::Ident>(part).is_ok());
+            assert!(
+                valid && scope.matches("::").count() <= 1,
+                "Invalid scope {scope}, expected *, module or module::Datatype"
+            );
+            scope.to_owned()
+        });
+        self.datatype_attrs.push((scope, change));
+        self
+    }
+
+    /// Rename the Rust datatype, field or enum variant generated for the Move one at `path`, e.g.,
+    /// `DynField` for `dynamic_field::Field`, taking precedence over any
+    /// `#[ext(moverox(rename = "..."))]` annotation of it.
+    ///
+    /// `path` is `module::Datatype`, `module::Datatype::field` or `module::Enum::Variant` for a
+    /// field or variant, or `module::Enum::Variant::field` for a field of an enum variant.
+    /// Renamed datatypes keep their Move name in type tags. References to them from this package
+    /// are renamed too, but other packages need to [map](Self::map_type) them to the Rust name.
+    ///
+    /// # Panics
+    ///
+    /// If `path` is invalid or `rust_name` isn't an identifier
+    pub fn rename(mut self, path: &str, rust_name: &str) -> Self {
+        let parts = path.split("::").count();
+        // Move names may be Rust keywords, e.g., a `type` field
+        let valid = path.split("::").all(|part| {
+            syn::parse::Parser::parse_str(<syn::Ident as syn::ext::IdentExt>::parse_any, part)
+                .is_ok()
+        });
+        assert!(
+            valid && (2..=4).contains(&parts),
+            "Invalid path {path}, expected module::Datatype, module::Datatype::member or \
+            module::Enum::Variant::field"
+        );
+        syn::parse_str::<syn::Ident>(rust_name)
+            .unwrap_or_else(|err| panic!("Invalid Rust name {rust_name}: {err}"));
+        self.renames.insert(path.to_owned(), rust_name.to_owned());
+        self
+    }
+
+    /// Default the type parameter at `path`, e.g., `pool::Pool::T`, to `move_type`, e.g.,
+    /// `sui::sui::SUI` or `OTW` for the [`Otw`] type, taking precedence over any
+    /// `#[ext(moverox(type_(T = ...)))]` annotation of it.
+    ///
+    /// `move_type` is written as in the datatype's module, since it's resolved through its
+    /// imports and the address map like field types.
+    ///
+    /// # Panics
+    ///
+    /// If `path` isn't of the form `module::Datatype::T` or `move_type` isn't a Move type
+    ///
+    /// [`Otw`]: https://docs.rs/moverox/latest/moverox/struct.Otw.html
+    pub fn type_default(mut self, path: &str, move_type: &str) -> Self {
+        let parts: Vec<_> = path.split("::").collect();
+        assert!(
+            parts.len() == 3
+                && parts
+                    .iter()
+                    .all(|part| syn::parse_str::<syn::Ident>(part).is_ok()),
+            "Invalid path {path}, expected module::Datatype::T"
+        );
+        move_type
+            .to_token_iter()
+            .parse_all::<move_syn::Type>()
+            .unwrap_or_else(|err| panic!("Invalid Move type {move_type}: {err}"));
+        self.type_defaults
+            .insert(path.to_owned(), move_type.to_owned());
+        self
+    }
+
     /// Path to the module containing the necessary exports that the generated code needs.
     ///
     /// Defaults to `::moverox`.
     ///
     /// `rust_path` must point to a crate/module which exports:
-    /// - a `types` module with `Address` and `U256` types from `moverox-types`
+    /// - a `types` module with `Address` and `U256` types and the `const_address` function from
+    ///   `moverox-types`
     /// - a `traits` module with `HasKey`, `MoveDatatype` and `MoveType` traits from `moverox-traits`
     /// - the `serde` crate
+    /// - the `CleverAbort`, `CleverError` and `ErrorConstant` types from `moverox`
+    /// - the `MoveCall`, `ObjectArg`, `Parameter` and `ParameterKind` types from `moverox`, if
+    ///   generating [call descriptors](Self::with_call_descriptors)
+    /// - the `FromReturnValuesError` type and the `check_return_count` and `parse_return_value`
+    ///   functions from `moverox`, if generating [return decoders](Self::with_return_decoders)
+    /// - the `parse_move_datatype` function and `FromRawDatatypeError` type from `moverox`, if
+    ///   generating [events](Self::with_events)
     pub fn moverox_path(mut self, rust_path: &str) -> Self {
-        self.moverox_path = rust_path.to_token_stream();
+        self.moverox_path = rust_path.to_owned();
         self
     }
 
@@ -87,19 +456,188 @@ impl<'a> Builder<'a> {
     /// those dependency packages first and them substitute their paths for the Move paths in the
     /// source code this builder is processing.
     ///
+    /// `named_address` may also be a numerical address like `0x2`, for Move paths like
+    /// `0x2::coin::Coin`. Numerical addresses whose named counterpart is known from a `Move.toml`
+    /// are mapped along with it.
+    ///
+    /// Named addresses of the dependencies declared in the package's `Move.toml` are mapped by
+    /// default, if their sources can be found; see [`Builder::build`].
+    ///
     /// # Panics
     ///
-    /// If `named_address` is not a valid identifier
+    /// If `named_address` is neither a valid identifier nor a numerical address
     pub fn map_address(mut self, named_address: &str, rust_path: &str) -> Self {
-        self.address_map.insert(
-            Ident::new(named_address, Span::call_site()),
-            rust_path.to_token_stream(),
+        if named_address.starts_with(|c: char| c.is_ascii_digit()) {
+            let bytes = move_syn::address_bytes(named_address)
+                .unwrap_or_else(|| panic!("Invalid numerical address {named_address}"));
+            self.numeric_address_map.insert(bytes, rust_path.to_owned());
+            return self;
+        }
+        // Panics if it isn't an identifier
+        let ident = Ident::new(named_address, Span::call_site());
+        self.address_map
+            .insert(ident.to_string(), rust_path.to_owned());
+        self
+    }
+
+    /// Use a custom Rust type for a Move datatype in the fields of the oxidized datatypes, e.g.,
+    /// `::std::string::String` for `0x1::string::String`.
+    ///
+    /// `move_type` is fully qualified, with a named or numerical address. It matches both if the
+    /// value of the named address is known from a `Move.toml`. `rust_type` is a path, which the
+    /// type arguments of the Move type follow, e.g., `::std::option::Option` for
+    /// `0x1::option::Option<T>` becomes `::std::option::Option<T>`.
+    ///
+    /// The Rust type has to be BCS-compatible with the Move type, or else use
+    /// [`map_type_with`](Self::map_type_with). Function signatures keep the oxidized Move types,
+    /// since their type tags are needed to call the functions and decode their return values.
+    ///
+    /// # Panics
+    ///
+    /// If `move_type` isn't of the form `address::module::Datatype` or `rust_type` isn't a path
+    pub fn map_type(self, move_type: &str, rust_type: &str) -> Self {
+        self.insert_type_mapping(move_type, rust_type, None)
+    }
+
+    /// Like [`map_type`](Self::map_type), but (de)serializing the fields whose type is the Move
+    /// type with the `serialize` and `deserialize` functions of the `serde_with` module, as in
+    /// `#[serde(with = ...)]`, e.g., for `0x2::vec_map::VecMap<K, V>` to be a `BTreeMap<K, V>`.
+    ///
+    /// The adapter doesn't apply where the Move type is a type argument, e.g., in `vector<T>`.
+    ///
+    /// # Panics
+    ///
+    /// If `move_type` isn't of the form `address::module::Datatype` or `rust_type` or `serde_with`
+    /// aren't paths
+    pub fn map_type_with(self, move_type: &str, rust_type: &str, serde_with: &str) -> Self {
+        syn::parse_str::<syn::Path>(serde_with)
+            .unwrap_or_else(|err| panic!("Invalid serde adapter {serde_with}: {err}"));
+        self.insert_type_mapping(move_type, rust_type, Some(serde_with))
+    }
+
+    fn insert_type_mapping(
+        mut self,
+        move_type: &str,
+        rust_type: &str,
+        serde_with: Option<&str>,
+    ) -> Self {
+        let valid = match move_type.split("::").collect::<Vec<_>>()[..] {
+            [address, module, name] => {
+                (move_syn::address_bytes(address).is_some()
+                    || syn::parse_str::<syn::Ident>(address).is_ok())
+                    && syn::parse_str::<syn::Ident>(module).is_ok()
+                    && syn::parse_str::<syn::Ident>(name).is_ok()
+            }
+            _ => false,
+        };
+        assert!(
+            valid,
+            "Invalid Move type {move_type}, expected address::module::Datatype"
+        );
+        syn::parse_str::<syn::Path>(rust_type)
+            .unwrap_or_else(|err| panic!("Invalid Rust type {rust_type}: {err}"));
+        self.type_map.insert(
+            move_type.to_owned(),
+            (rust_type.to_owned(), serde_with.map(str::to_owned)),
         );
         self
     }
 
-    pub const fn published_at(mut self, hex_address: &'a str) -> Self {
-        self.published_at = Some(hex_address);
+    /// Address of the package on chain, used for the type tags of its datatypes.
+    ///
+    /// Defaults to the address on the selected [`environment`](Self::environment), if any, or else
+    /// to `published-at` in the package's `Move.toml`, if any.
+    pub fn published_at(mut self, hex_address: &'a str) -> Self {
+        self.published_at = Some(Cow::Borrowed(hex_address));
+        self
+    }
+
+    /// Address of the package version that first defined `datatype`, given as
+    /// `module::Datatype`, for its type tags.
+    ///
+    /// After a package upgrade, datatypes keep the address of the version that first defined them,
+    /// while the ones added by the upgrade get the new version's address. Datatypes without an
+    /// origin get the [`published_at`](Self::published_at) address.
+    ///
+    /// # Panics
+    ///
+    /// If `datatype` isn't of the form `module::Datatype` or `hex_address` isn't an address
+    pub fn type_origin(mut self, datatype: &str, hex_address: &str) -> Self {
+        assert!(
+            datatype
+                .split_once("::")
+                .is_some_and(|(module, name)| !module.is_empty() && !name.contains("::")),
+            "Invalid datatype {datatype}, expected module::Datatype"
+        );
+        assert!(
+            move_syn::address_bytes(hex_address).is_some(),
+            "Invalid address {hex_address}"
+        );
+        self.type_origins
+            .insert(datatype.to_owned(), hex_address.to_owned());
+        self
+    }
+
+    /// Read the [`type_origin`](Self::type_origin)s from a JSON file with the type origin table
+    /// of the package object on chain: a list of objects with `module_name`, `datatype_name` (or
+    /// `struct_name`) and `package` fields, or an object with the list under `typeOriginTable`.
+    ///
+    /// Explicit type origins take precedence over the file's.
+    pub fn type_origins_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.type_origins_file = Some(path.into());
+        self
+    }
+
+    /// Take the [`published_at`](Self::published_at) address from the publication of the package
+    /// on the environment `name`, e.g., `mainnet`, in its `Published.toml` or `Move.lock`.
+    ///
+    /// Building fails if the package has no publication on it.
+    pub fn environment(mut self, name: &str) -> Self {
+        self.environment = Some(Environment::Named(name.to_owned()));
+        self
+    }
+
+    /// Like [`environment`](Self::environment), for the environment named by the environment
+    /// variable `var` when building, e.g., `MOVE_ENV=testnet cargo build`.
+    ///
+    /// Building fails if `var` isn't set.
+    pub fn environment_from_var(mut self, var: &str) -> Self {
+        self.environment = Some(Environment::Var(var.to_owned()));
+        self
+    }
+
+    /// Like [`environment`](Self::environment), for the environment named like the enabled
+    /// Cargo feature of the crate being built, e.g., `mainnet` for `cargo build --features
+    /// mainnet`.
+    ///
+    /// Only the features named after the environments the package is published on count, with
+    /// `-` in place of `_`. Building fails unless exactly one of them is enabled.
+    pub fn environment_from_features(mut self) -> Self {
+        self.environment = Some(Environment::Features);
+        self
+    }
+
+    /// Look for the sources of `git` dependencies in a package cache laid out like the Move CLI's
+    /// `~/.move` directory, so that they're found without fetching anything.
+    ///
+    /// A dependency on `url` at revision `rev` is expected at `{path}/{url}_{rev}/{subdir}`, where
+    /// `/`, `:`, `.` and `@` in `url` are replaced by `_`, and `/` in `rev` by `__`. E.g., the
+    /// `Sui` framework at `rev = "framework/mainnet"` is at
+    /// `{path}/https___github_com_MystenLabs_sui_git_framework__mainnet/crates/sui-framework/packages/sui-framework`.
+    ///
+    /// If this or [`vendor_dir`](Self::vendor_dir) is set, every `git` dependency must be found;
+    /// otherwise they're ignored. Those without a `rev` can only be found in the `vendor_dir`.
+    pub fn package_cache(mut self, path: impl Into<PathBuf>) -> Self {
+        self.dependency_sources.package_cache = Some(path.into());
+        self
+    }
+
+    /// Look for the sources of `git` dependencies in `{path}/{name}`, where `name` is the key of
+    /// the dependency in `Move.toml`.
+    ///
+    /// Takes precedence over the [`package_cache`](Self::package_cache).
+    pub fn vendor_dir(mut self, path: impl Into<PathBuf>) -> Self {
+        self.dependency_sources.vendor_dir = Some(path.into());
         self
     }
 
@@ -130,25 +668,308 @@ impl<'a> Builder<'a> {
         self
     }
 
-    pub fn build(self) -> Result<()> {
+    /// Number of threads to parse and oxidize the Move modules on.
+    ///
+    /// Defaults to the [available parallelism](std::thread::available_parallelism). The generated
+    /// code is the same regardless.
+    pub const fn jobs(mut self, threads: usize) -> Self {
+        self.jobs = Some(threads);
+        self
+    }
+
+    /// Oxidize the package, writing the Rust code to `{out_dir}/{name}.rs`.
+    ///
+    /// If the package has a `Move.toml`, any settings not configured explicitly are taken from it:
+    /// - `published-at` sets the [`published_at`](Self::published_at) address;
+    /// - named addresses in `[addresses]` map to the package itself, as do the ones only in
+    ///   `[dev-addresses]` when [generating test items](Self::with_test_items);
+    /// - named addresses of dependencies (direct or transitive) map to `crate::<package>`, where
+    ///   `<package>` is the dependency's package name in snake case, e.g., `std` maps to
+    ///   `crate::move_stdlib` for a dependency on `MoveStdlib`. That's `local` dependencies and
+    ///   `git` ones found in the [`package_cache`](Self::package_cache) or
+    ///   [`vendor_dir`](Self::vendor_dir).
+    ///
+    /// The hash of the settings and Move sources is kept in `{out_dir}/{name}.moverox.toml`, so that
+    /// nothing is generated if they haven't changed since the last build. Files are only written
+    /// if their contents changed, so as to not trigger recompilations.
+    pub fn build(mut self) -> Result<()> {
+        let out_dir = self.out_dir.clone().map_or_else(default_out_dir, Ok)?;
+
+        self.apply_manifest()?;
+
+        let move_files = self.collect_move_files()?;
+
+        let settings = self.settings();
+        write_outputs(&out_dir, &self.name, &settings, &move_files, || {
+            self.generate_from(&move_files)
+        })
+    }
+
+    /// Oxidize the package like [`build`](Self::build) does, but return the Rust code instead of
+    /// writing it to a file.
+    pub fn generate(mut self) -> Result<Generated> {
+        self.apply_manifest()?;
+
         let move_files = self.collect_move_files()?;
 
-        let modules = self.parse_files(&move_files)?;
+        self.generate_from(&move_files)
+    }
+
+    fn generate_from(&self, move_files: &[PathBuf]) -> Result<Generated> {
+        let (mut file, dependencies) = self.generate_rust_in_parallel(move_files)?;
+        let module_files = if self.file_per_module {
+            split_modules(&mut file, &self.name)?
+        } else {
+            vec![]
+        };
 
-        let rust_code = self.generate_rust_str(&modules)?;
+        Ok(Generated {
+            rust_code: prettyplease::unparse(&file),
+            module_files,
+            dependencies,
+        })
+    }
+
+    /// Description of every setting that affects the generated code, including the version of
+    /// this crate, for the [`Stamp`].
+    fn settings(&self) -> String {
+        let sorted = |mut entries: Vec<String>| {
+            entries.sort();
+            entries
+        };
+        let address_map = sorted(
+            self.address_map
+                .iter()
+                .map(|(named, path)| format!("{named} => {path}"))
+                .collect(),
+        );
+        let manifest_address_map = sorted(
+            self.manifest_address_map
+                .iter()
+                .map(|(named, path)| format!("{named} => {path}"))
+                .collect(),
+        );
+        let numeric_address_map = sorted(
+            self.numeric_address_map
+                .iter()
+                .map(|(bytes, path)| format!("{bytes:?} => {path}"))
+                .collect(),
+        );
+        let address_values = sorted(
+            self.address_values
+                .iter()
+                .map(|(named, bytes)| format!("{named} = {bytes:?}"))
+                .collect(),
+        );
+        format!(
+            "moverox-build {}\n{:?}\n{:?}\n{:?}",
+            env!("CARGO_PKG_VERSION"),
+            (
+                &self.name,
+                self.with_implicit_iota_imports,
+                self.with_implicit_sui_imports,
+                self.call_descriptors,
+                self.return_decoders,
+                self.events,
+                self.file_per_module,
+                &self.test_cfg,
+            ),
+            (
+                &self.moverox_path,
+                address_map,
+                numeric_address_map,
+                manifest_address_map,
+                address_values,
+                &self.published_at,
+                &self.environment,
+                self.published_constants.then_some(&self.publications),
+                sorted(
+                    self.type_origins
+                        .iter()
+                        .map(|(datatype, address)| format!("{datatype} = {address}"))
+                        .collect(),
+                ),
+                &self.dependency_sources,
+                &self.filters,
+            ),
+            (
+                &self.datatype_attrs,
+                &self.type_map,
+                &self.renames,
+                &self.type_defaults,
+            ),
+        )
+    }
+
+    /// Fill in the settings that weren't set explicitly from the package's `Move.toml`.
+    fn apply_manifest(&mut self) -> Result<()> {
+        self.apply_publications()?;
+        if let Some(path) = &self.type_origins_file {
+            if self.emit_rerun_if_changed {
+                println!("cargo:rerun-if-changed={}", path.display());
+            }
+            for (datatype, address) in published::read_type_origins(path)? {
+                self.type_origins.entry(datatype).or_insert(address);
+            }
+        }
 
-        let target = self
-            .out_dir
-            .map_or_else(default_out_dir, Ok)?
-            .join(format!("{}.rs", self.name));
+        let manifest = match self.manifest.take() {
+            Some(manifest) => manifest,
+            None => match Manifest::read_if_exists(&self.pkg_path)? {
+                Some(manifest) => manifest,
+                None => return Ok(()),
+            },
+        };
+        if self.emit_rerun_if_changed {
+            println!(
+                "cargo:rerun-if-changed={}",
+                self.pkg_path.join("Move.toml").display()
+            );
+        }
 
-        let mut file = fs::File::create(&target)?;
-        file.write_all(b"// This file is @generated by moverox-build.\n")?;
-        file.write_all(rust_code.as_bytes())?;
+        if self.published_at.is_none() {
+            self.published_at = manifest.published_at.clone().map(Cow::Owned);
+        }
 
+        for (named, value) in &manifest.addresses {
+            self.manifest_address_map
+                .entry(named.clone())
+                .or_insert_with(|| "super".to_owned());
+            self.record_address_value(named, value.as_deref());
+        }
+        // Test-only items may use the named addresses only assigned for tests
+        if self.test_cfg.is_some() {
+            for (named, value) in &manifest.dev_addresses {
+                if manifest.addresses.contains_key(named) {
+                    continue;
+                }
+                self.manifest_address_map
+                    .entry(named.clone())
+                    .or_insert_with(|| "super".to_owned());
+                self.record_address_value(named, Some(value));
+            }
+        }
+        let dependencies =
+            manifest.dependency_manifests(&self.pkg_path, &self.dependency_sources)?;
+        for (path, dep) in &dependencies {
+            if self.emit_rerun_if_changed {
+                println!(
+                    "cargo:rerun-if-changed={}",
+                    path.join("Move.toml").display()
+                );
+            }
+            let rust_path = format!("crate::{}", manifest::module_name(&dep.name));
+            for (named, value) in &dep.addresses {
+                self.manifest_address_map
+                    .entry(named.clone())
+                    .or_insert_with(|| rust_path.clone());
+                self.record_address_value(named, value.as_deref());
+            }
+        }
+        self.dependencies = dependencies;
+        self.manifest = Some(manifest);
         Ok(())
     }
 
+    /// Read the package's publications and take its address from the selected environment's, if
+    /// not set explicitly.
+    fn apply_publications(&mut self) -> Result<()> {
+        if self.emit_rerun_if_changed {
+            for file in [published::LOCK_FILE, published::PUBLISHED_FILE] {
+                println!(
+                    "cargo:rerun-if-changed={}",
+                    self.pkg_path.join(file).display()
+                );
+            }
+        }
+        self.publications = Publication::read_all(&self.pkg_path)?;
+
+        let Some(environment) = &self.environment else {
+            return Ok(());
+        };
+        let name = environment.resolve(&self.publications, self.emit_rerun_if_changed)?;
+        let publication = self.publications.get(&name).ok_or_else(|| {
+            format!(
+                "package `{}` has no publication on environment `{name}` in Published.toml or \
+                 Move.lock; it has: {}",
+                self.name,
+                self.environments()
+            )
+        })?;
+        if self.published_at.is_none() {
+            self.published_at = Some(Cow::Owned(publication.published_at.clone()));
+        }
+        Ok(())
+    }
+
+    /// Names of the environments the package is published on, for error messages.
+    fn environments(&self) -> String {
+        if self.publications.is_empty() {
+            return "none".to_owned();
+        }
+        self.publications
+            .keys()
+            .map(|name| format!("`{name}`"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Remember the value of a named address so that numerical paths with it map to the same Rust
+    /// path. Placeholder (`_`) and zero addresses are ignored.
+    fn record_address_value(&mut self, named: &str, value: Option<&str>) {
+        let Some(bytes) = value.and_then(move_syn::address_bytes) else {
+            return;
+        };
+        if bytes != [0; 32] {
+            self.address_values.entry(named.to_owned()).or_insert(bytes);
+        }
+    }
+
+    /// Whether any Rust functions are generated for the Move ones.
+    const fn functions(&self) -> bool {
+        self.call_descriptors || self.return_decoders
+    }
+
+    /// The package address, if known.
+    fn package_bytes(&self) -> Option<[u8; 32]> {
+        self.published_at
+            .as_deref()
+            .and_then(move_syn::address_bytes)
+    }
+
+    /// The [type mappings](Self::map_type), by named and numerical address alike where the value
+    /// of the named address is known.
+    fn type_map(&self) -> moverox_codegen::TypeMap {
+        let mut type_map = moverox_codegen::TypeMap::new();
+        for (move_type, (rust_type, serde_with)) in &self.type_map {
+            let Some((address, datatype)) = move_type.split_once("::") else {
+                continue;
+            };
+            let mapping =
+                moverox_codegen::TypeMapping::new(rust_type.to_token_stream(), serde_with.clone());
+            let (named, bytes) = move_syn::address_bytes(address).map_or_else(
+                || (vec![address], self.address_values.get(address).copied()),
+                |bytes| {
+                    let named = self
+                        .address_values
+                        .iter()
+                        .filter(|(_, value)| **value == bytes)
+                        .map(|(named, _)| named.as_str())
+                        .collect();
+                    (named, Some(bytes))
+                },
+            );
+            for named in named {
+                let named = Ident::new(named, Span::call_site());
+                type_map.insert_named(named, datatype.to_owned(), mapping.clone());
+            }
+            if let Some(bytes) = bytes {
+                type_map.insert_numeric(bytes, datatype.to_owned(), mapping);
+            }
+        }
+        type_map
+    }
+
     fn collect_move_files(&self) -> Result<Vec<PathBuf>> {
         let move_sources = self.pkg_path.join("sources").canonicalize()?;
         let mut move_files = vec![];
@@ -162,62 +983,349 @@ impl<'a> Builder<'a> {
         Ok(move_files)
     }
 
-    /// Parse the Move files and apply any modifications pre-Rust code generation.
-    fn parse_files(&self, move_files: &[PathBuf]) -> Result<Vec<move_syn::Module>> {
-        let mut move_modules = Vec::with_capacity(move_files.len());
-        for path in move_files {
-            // Read the .move file
-            let contents = sanitize_for_tokenizer(&fs::read_to_string(path)?);
+    /// Parse a Move file and apply any modifications pre-Rust code generation.
+    fn parse_file(&self, path: &Path) -> Result<Vec<move_syn::Module>> {
+        // Read the .move file
+        let contents = fs::read_to_string(path)?;
 
-            // Parse to IR
-            let parsed_file: move_syn::File = contents
-                .into_token_iter()
-                .parse_all()
-                .map_err(|e| anyhow::anyhow!(e.to_string()))?;
+        // Parse to IR
+        let parsed_file = move_syn::parse_file(&contents).map_err(|e| e.with_path(path))?;
 
-            for mut module in parsed_file.into_modules() {
-                if self.with_implicit_iota_imports {
-                    module.with_implicit_iota_imports();
-                }
-                if self.with_implicit_sui_imports {
-                    module.with_implicit_sui_imports();
-                }
-                module.fully_qualify_datatype_field_types();
-                move_modules.push(module);
+        let mut move_modules = vec![];
+        for mut module in parsed_file.into_modules() {
+            if self.with_implicit_iota_imports {
+                module.with_implicit_iota_imports();
             }
+            if self.with_implicit_sui_imports {
+                module.with_implicit_sui_imports();
+            }
+            module.fully_qualify_datatype_field_types();
+            if self.functions() {
+                module.fully_qualify_fun_signature_types();
+            }
+            move_modules.push(module);
         }
         Ok(move_modules)
     }
 
-    fn generate_rust_str(&self, move_modules: &[move_syn::Module]) -> Result<String> {
-        let mut address_map = self.address_map.clone();
+    /// What [`module_to_rust`](Self::module_to_rust) needs, given the named addresses of the
+    /// package's modules, the paths to the other packages oxidized along with it and the
+    /// datatypes its modules rename with annotations, keyed by `module::Datatype`.
+    fn module_context(
+        &self,
+        named_addresses: impl IntoIterator<Item = String>,
+        links: &Links,
+        renamed_datatypes: impl IntoIterator<Item = (String, String)>,
+    ) -> ModuleContext {
+        // Explicit mappings take precedence over the other packages', which take precedence over
+        // the ones from `Move.toml`s
+        let mut address_map: AddressMap = self
+            .manifest_address_map
+            .iter()
+            .chain(links.named.iter().map(|(named, path)| (named, path)))
+            .chain(&self.address_map)
+            .map(|(named, rust_path)| {
+                (
+                    Ident::new(named, Span::call_site()),
+                    rust_path.to_token_stream(),
+                )
+            })
+            .collect();
         // If any type path starts with one of the named addresses of the package's modules,
         // substitute that named address prefix with `super`, since oxidized modules will all
         // be right under the same 'super' module.
-        for module in move_modules {
-            address_map.insert(module.named_address.clone(), "super".to_token_stream());
+        for named in named_addresses {
+            address_map.insert_named(
+                Ident::new(&named, Span::call_site()),
+                "super".to_token_stream(),
+            );
         }
 
-        let package_address = self
-            .published_at
-            .map(move_syn::unsynn::LiteralString::from_str);
+        // Numerical addresses map to the same paths as the named ones with the same value
+        let super_ = "super".to_token_stream();
+        let known_values = self
+            .address_values
+            .iter()
+            .filter_map(|(named, bytes)| {
+                let named = Ident::new(named, Span::call_site());
+                Some((*bytes, address_map.get_named(&named)?.clone()))
+            })
+            .chain(self.package_bytes().map(|bytes| (bytes, super_)))
+            .chain(
+                links
+                    .numeric
+                    .iter()
+                    .map(|(bytes, path)| (*bytes, path.to_token_stream())),
+            )
+            .collect::<Vec<_>>();
+        for (bytes, rust_path) in known_values {
+            if address_map.get_numeric(&bytes).is_none() {
+                address_map.insert_numeric(bytes, rust_path);
+            }
+        }
+        for (bytes, rust_path) in &self.numeric_address_map {
+            address_map.insert_numeric(*bytes, rust_path.to_token_stream());
+        }
 
-        // Collect generated Rust code
-        let mut generated_code = String::new();
-        for module in move_modules {
-            // Skip module generation if no datatypes are found
-            if !module.items().any(|item| item.kind.is_datatype()) {
-                continue;
+        let mut options = moverox_codegen::Options::default();
+        options.call_descriptors = self.call_descriptors;
+        options.return_decoders = self.return_decoders;
+        options.test_cfg = self.test_cfg.as_deref().map(|cfg| cfg.to_token_stream());
+        options.type_origins.clone_from(&self.type_origins);
+        options.datatype_attrs = self
+            .datatype_attrs
+            .iter()
+            .map(|(scope, change)| {
+                let mut rule = moverox_codegen::DatatypeAttrs::default();
+                rule.scope.clone_from(scope);
+                let tokens = |paths: &[String]| paths.iter().map(|p| p.to_token_stream()).collect();
+                match change {
+                    DatatypeChange::AddDerives(derives) => rule.derives = tokens(derives),
+                    DatatypeChange::RemoveDerives(derives) => rule.remove_derives = tokens(derives),
+                    DatatypeChange::AddAttribute(attr) => rule.attrs = vec![attr.to_token_stream()],
+                }
+                rule
+            })
+            .collect();
+        options.type_map = self.type_map();
+        // Explicit renames take precedence over the annotated ones
+        options.renames = renamed_datatypes
+            .into_iter()
+            .chain(self.renames.clone())
+            .map(|(path, rust_name)| (path, rust_ident(&rust_name)))
+            .collect();
+        options.type_defaults = self
+            .type_defaults
+            .iter()
+            .map(|(path, move_type)| (path.clone(), move_type.to_token_stream()))
+            .collect();
+
+        ModuleContext {
+            thecrate: self.moverox_path.to_token_stream(),
+            package: self.package_address(),
+            address_map,
+            options,
+        }
+    }
+
+    /// Rust code for a module and its summary for the package-level items, unless there's nothing
+    /// to generate for it.
+    fn module_to_rust(
+        &self,
+        module: &move_syn::Module,
+        ctx: &ModuleContext,
+    ) -> Result<Option<(TokenStream, moverox_codegen::ModuleSummary)>> {
+        let test_items = self.test_cfg.is_some();
+        if module.is_test_gated() && !test_items {
+            return Ok(None);
+        }
+        // Skip module generation if no datatypes, constants or callable functions are found
+        let mut items = module
+            .items()
+            .filter(|item| test_items || !item.is_test_gated());
+        if !items.any(|item| match &item.kind {
+            move_syn::ItemKind::Const(c) => c.value().is_some(),
+            move_syn::ItemKind::Function(_) | move_syn::ItemKind::NativeFun(_) => {
+                self.functions() && is_callable(item)
             }
-            let rust_code = module
-                .to_rust(&self.moverox_path, package_address.as_ref(), &address_map)
-                .map_err(|err| format!("module {}: {err}", module.ident))?
-                .to_string();
-            generated_code.push_str(&rust_code);
-            generated_code.push('\n');
+            kind => kind.is_datatype(),
+        }) {
+            return Ok(None);
+        }
+        let error = |err| format!("module {}: {err}", module.ident);
+        let rust_code = module
+            .to_rust(
+                &ctx.thecrate,
+                ctx.package.as_ref(),
+                &ctx.address_map,
+                &ctx.options,
+            )
+            .map_err(error)?;
+        let summary = moverox_codegen::ModuleSummary::new(module).map_err(error)?;
+        Ok(Some((rust_code, summary)))
+    }
+
+    /// The package-level items, given the summaries of the oxidized modules.
+    fn package_to_rust(&self, summaries: &[moverox_codegen::ModuleSummary]) -> Result<TokenStream> {
+        let thecrate = self.moverox_path.to_token_stream();
+        let package = self.package_address();
+
+        let mut generated_code = TokenStream::new();
+        let abort_errors = moverox_codegen::abort_errors(summaries, &thecrate, package.as_ref())
+            .map_err(|err| format!("abort errors: {err}"))?;
+        generated_code.extend(abort_errors);
+        let clever_errors = moverox_codegen::clever_errors(summaries, &thecrate, package.as_ref())
+            .map_err(|err| format!("clever errors: {err}"))?;
+        generated_code.extend(clever_errors);
+        if self.events {
+            let renames = self
+                .renames
+                .iter()
+                .map(|(path, rust_name)| (path.clone(), rust_ident(rust_name)))
+                .collect();
+            let events = moverox_codegen::events(
+                summaries,
+                &thecrate,
+                package.as_ref(),
+                &self.type_origins,
+                &renames,
+            )
+            .map_err(|err| format!("events: {err}"))?;
+            generated_code.extend(events);
+        }
+        if self.published_constants {
+            generated_code.extend(self.published_constants()?);
         }
         Ok(generated_code)
     }
+
+    /// The `published_at` module; see
+    /// [`with_published_constants`](Self::with_published_constants).
+    fn published_constants(&self) -> Result<TokenStream> {
+        if self.publications.is_empty() {
+            return Err(format!(
+                "package `{}` has no publications in Published.toml or Move.lock for its \
+                 `published_at` constants",
+                self.name
+            )
+            .into());
+        }
+        let thecrate = &self.moverox_path;
+        let mut constants = String::new();
+        for (name, publication) in &self.publications {
+            let ident = published::constant_name(name);
+            let doc = format!(" Address of the package on `{name}`.");
+            constants.push_str(&format!(
+                "#[doc = {doc:?}] \
+                 pub const {ident}: {thecrate}::types::Address = \
+                 {thecrate}::types::const_address(b{:?});",
+                publication.published_at
+            ));
+        }
+        let module = format!(
+            "#[doc = \" Addresses the package is published at on each environment.\"] \
+             pub mod published_at {{ {constants} }}"
+        );
+        Ok(module.as_str().to_token_stream())
+    }
+
+    fn package_address(&self) -> Option<LiteralString> {
+        self.published_at.as_deref().map(LiteralString::from_str)
+    }
+}
+
+/// Paths to the items of the other packages oxidized along with a package; see [`Workspace`].
+#[derive(Clone, Debug, Default)]
+struct Links {
+    named: Vec<(String, String)>,
+    numeric: Vec<([u8; 32], String)>,
+}
+
+/// Settings for oxidizing modules, made from the [`Builder`]'s for each thread.
+struct ModuleContext {
+    thecrate: TokenStream,
+    package: Option<LiteralString>,
+    address_map: AddressMap,
+    options: moverox_codegen::Options,
+}
+
+/// Whether Rust functions are generated for this item: a `public` or `entry` function.
+const fn is_callable(item: &move_syn::Item) -> bool {
+    match &item.kind {
+        move_syn::ItemKind::Function(f) => item.visibility().is_public() || f.is_entry(),
+        move_syn::ItemKind::NativeFun(_) => item.visibility().is_public(),
+        _ => false,
+    }
+}
+
+/// Rust code for an oxidized Move package; see [`Builder::generate`].
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct Generated {
+    /// The pretty-printed Rust code.
+    pub rust_code: String,
+    /// Paths and contents of the module files, if
+    /// [generating one per module](Builder::with_file_per_module).
+    ///
+    /// The paths are relative to the directory of the file with [`rust_code`](Self::rust_code),
+    /// which includes them. The contents start with a `@generated` comment already.
+    pub module_files: Vec<(PathBuf, String)>,
+    /// Fully qualified names of the datatypes included only as dependencies of the selected ones;
+    /// see [`Builder::allow_modules`].
+    pub dependencies: Vec<String>,
+}
+
+impl Generated {
+    /// Comment lines to put at the top of the generated file, marking it as such and listing the
+    /// [`dependencies`](Self::dependencies).
+    pub fn header(&self) -> String {
+        let mut header = String::from(GENERATED_HEADER);
+        if !self.dependencies.is_empty() {
+            header.push_str("//\n// Included only as dependencies of the selected datatypes:\n");
+            for name in &self.dependencies {
+                header.push_str(&format!("// - {name}\n"));
+            }
+        }
+        header
+    }
+}
+
+/// Write the generated files to `out_dir`, unless the settings and Move sources are the same as
+/// when they were last written there; see [`Builder::build`].
+fn write_outputs(
+    out_dir: &Path,
+    name: &str,
+    settings: &str,
+    move_files: &[PathBuf],
+    generate: impl FnOnce() -> Result<Generated>,
+) -> Result<()> {
+    let stamp_path = Stamp::path(out_dir, name);
+    let inputs = stamp::hash_inputs(settings, move_files)?;
+    if Stamp::read(&stamp_path).is_some_and(|stamp| stamp.is_fresh(&inputs, out_dir)) {
+        return Ok(());
+    }
+
+    let generated = generate()?;
+
+    let target = PathBuf::from(format!("{name}.rs"));
+    let contents = format!("{}{}", generated.header(), generated.rust_code);
+    stamp::write_if_changed(&out_dir.join(&target), &contents)?;
+    let mut outputs = vec![target];
+    for (path, contents) in generated.module_files {
+        stamp::write_if_changed(&out_dir.join(&path), &contents)?;
+        outputs.push(path);
+    }
+
+    Stamp { inputs, outputs }.write(&stamp_path)
+}
+
+/// Move the contents of each module of `file` to a pretty-printed file of its own, at
+/// `{dir}/{module}.rs`, leaving an `include!` of it in its place.
+///
+/// Returns the paths of the module files, relative to the directory of `file`, and their
+/// contents.
+fn split_modules(file: &mut syn::File, dir: &str) -> Result<Vec<(PathBuf, String)>> {
+    use syn::ext::IdentExt as _;
+
+    let mut module_files = vec![];
+    for item in &mut file.items {
+        let syn::Item::Mod(item_mod) = item else {
+            continue;
+        };
+        let Some((brace, items)) = item_mod.content.take() else {
+            continue;
+        };
+        let path = format!("{dir}/{}.rs", item_mod.ident.unraw());
+        let contents = prettyplease::unparse(&syn::File {
+            shebang: None,
+            attrs: vec![],
+            items,
+        });
+        let include = syn::parse_str(&format!("include!({path:?});"))?;
+        item_mod.content = Some((brace, vec![include]));
+        module_files.push((PathBuf::from(path), format!("{GENERATED_HEADER}{contents}")));
+    }
+    Ok(module_files)
 }
 
 fn visit_move_files(path: &Path, f: &mut impl FnMut(&Path)) -> std::io::Result<()> {
@@ -241,3 +1349,26 @@ fn default_out_dir() -> Result<PathBuf> {
         .ok_or("OUT_DIR environment variable is not set")?
         .into())
 }
+
+/// An identifier that was validated already, possibly raw.
+fn rust_ident(name: &str) -> Ident {
+    syn::parse_str(name).expect("Valid identifier")
+}
+
+/// Paths of the comma-separated `derives`.
+///
+/// # Panics
+///
+/// If any isn't a path
+fn derive_paths(derives: &str) -> Vec<String> {
+    derives
+        .split(',')
+        .map(str::trim)
+        .filter(|derive| !derive.is_empty())
+        .map(|derive| {
+            syn::parse_str::<syn::Path>(derive)
+                .unwrap_or_else(|err| panic!("Invalid derive {derive}: {err}"));
+            derive.to_owned()
+        })
+        .collect()
+}
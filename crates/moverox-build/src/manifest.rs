@@ -0,0 +1,271 @@
+//! Reading `Move.toml` package manifests.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::Result;
+
+const MANIFEST_FILE: &str = "Move.toml";
+
+/// The parts of a Move package manifest (`Move.toml`) that matter for oxidizing the package.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Manifest {
+    /// Package name, from `[package].name`.
+    pub name: String,
+    /// Address of the package on chain, from `[package].published-at`.
+    pub published_at: Option<String>,
+    /// Named addresses declared by the package, from `[addresses]`.
+    ///
+    /// Placeholder addresses (`_`) are `None`.
+    pub addresses: BTreeMap<String, Option<String>>,
+    /// Named address assignments for tests, from `[dev-addresses]`.
+    pub dev_addresses: BTreeMap<String, String>,
+    /// Package dependencies, from `[dependencies]`, keyed by name.
+    pub dependencies: BTreeMap<String, Dependency>,
+}
+
+/// Where a dependency of a Move package can be found.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Dependency {
+    /// `{ local = "..." }`, with the path relative to the depending package.
+    Local(PathBuf),
+    /// `{ git = "...", subdir = "...", rev = "..." }`
+    Git {
+        url: String,
+        subdir: Option<PathBuf>,
+        rev: Option<String>,
+    },
+    /// Any other kind of dependency, e.g., on-chain or MVR ones.
+    Other,
+}
+
+impl Manifest {
+    /// Read the `Move.toml` in the package directory `pkg_path`.
+    pub fn read(pkg_path: impl AsRef<Path>) -> Result<Self> {
+        let path = pkg_path.as_ref().join(MANIFEST_FILE);
+        let contents = std::fs::read_to_string(&path)
+            .map_err(|err| format!("reading {}: {err}", path.display()))?;
+        Self::parse(&contents).map_err(|err| format!("{}: {err}", path.display()).into())
+    }
+
+    /// Read `pkg_path/Move.toml`, if it exists.
+    pub(crate) fn read_if_exists(pkg_path: &Path) -> Result<Option<Self>> {
+        if pkg_path.join(MANIFEST_FILE).is_file() {
+            Self::read(pkg_path).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Parse the contents of a `Move.toml` file.
+    pub fn parse(toml: &str) -> Result<Self> {
+        let RawManifest {
+            package,
+            addresses,
+            dev_addresses,
+            dependencies,
+        } = toml::from_str(toml)?;
+        Ok(Self {
+            name: package.name,
+            published_at: package.published_at,
+            addresses: addresses
+                .into_iter()
+                .map(|(name, value)| (name, (value != "_").then_some(value)))
+                .collect(),
+            dev_addresses,
+            dependencies: dependencies
+                .into_iter()
+                .map(|(name, dep)| (name, dep.into()))
+                .collect(),
+        })
+    }
+
+    /// Directories and manifests of the dependencies of the package at `pkg_path` that can be
+    /// found locally, direct or transitive; see [`DependencySources`].
+    ///
+    /// Dependencies that are closer to the package come first.
+    pub(crate) fn dependency_manifests(
+        &self,
+        pkg_path: &Path,
+        sources: &DependencySources,
+    ) -> Result<Vec<(PathBuf, Self)>> {
+        let mut manifests: Vec<(PathBuf, Self)> = vec![];
+        let mut queue = sources.resolve_all(self, pkg_path)?;
+        while !queue.is_empty() {
+            let mut next = vec![];
+            for path in queue {
+                let canonical = path
+                    .canonicalize()
+                    .map_err(|err| format!("dependency {}: {err}", path.display()))?;
+                if manifests.iter().any(|(visited, _)| *visited == canonical) {
+                    continue;
+                }
+                let dep = Self::read(&canonical)?;
+                next.extend(sources.resolve_all(&dep, &canonical)?);
+                manifests.push((canonical, dep));
+            }
+            queue = next;
+        }
+        Ok(manifests)
+    }
+}
+
+/// Where to find the sources of `git` dependencies without fetching them.
+///
+/// `local` dependencies are always found relative to the depending package.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct DependencySources {
+    /// Package cache laid out like the Move CLI's `~/.move` directory.
+    pub(crate) package_cache: Option<PathBuf>,
+    /// Directory with a package for each dependency name.
+    pub(crate) vendor_dir: Option<PathBuf>,
+}
+
+impl DependencySources {
+    /// Directories of the dependencies of the package at `pkg_path` that can be found.
+    ///
+    /// `git` dependencies are skipped if there's nowhere to look for them, but it's an error not
+    /// to find them otherwise, e.g., if there's only a package cache and they have no `rev`.
+    fn resolve_all(&self, manifest: &Manifest, pkg_path: &Path) -> Result<Vec<PathBuf>> {
+        let mut paths = vec![];
+        for (name, dep) in &manifest.dependencies {
+            match dep {
+                Dependency::Local(path) => paths.push(pkg_path.join(path)),
+                Dependency::Git { .. }
+                    if self.package_cache.is_none() && self.vendor_dir.is_none() => {}
+                Dependency::Git { url, subdir, rev } => {
+                    let candidates =
+                        self.git_candidates(name, url, subdir.as_deref(), rev.as_deref());
+                    if candidates.is_empty() {
+                        return Err(format!(
+                            "dependency `{name}` of `{}` on {url} has no `rev` to find it in the \
+                             package cache",
+                            manifest.name
+                        )
+                        .into());
+                    }
+                    let found = candidates
+                        .iter()
+                        .find(|path| path.join(MANIFEST_FILE).is_file())
+                        .ok_or_else(|| {
+                            let searched = candidates
+                                .iter()
+                                .map(|path| path.display().to_string())
+                                .collect::<Vec<_>>()
+                                .join(", ");
+                            format!(
+                                "dependency `{name}` of `{}` on {url} not found in {searched}",
+                                manifest.name
+                            )
+                        })?;
+                    paths.push(found.clone());
+                }
+                Dependency::Other => (),
+            }
+        }
+        Ok(paths)
+    }
+
+    /// Where a `git` dependency may be, in order of preference.
+    fn git_candidates(
+        &self,
+        name: &str,
+        url: &str,
+        subdir: Option<&Path>,
+        rev: Option<&str>,
+    ) -> Vec<PathBuf> {
+        let mut candidates = vec![];
+        if let Some(vendor_dir) = &self.vendor_dir {
+            candidates.push(vendor_dir.join(name));
+        }
+        if let (Some(package_cache), Some(rev)) = (&self.package_cache, rev) {
+            let mut path = package_cache.join(git_cache_dir(url, rev));
+            if let Some(subdir) = subdir {
+                path.push(subdir);
+            }
+            candidates.push(path);
+        }
+        candidates
+    }
+}
+
+/// Name of the directory that the Move CLI clones a git repository at a revision into, e.g.,
+/// `https___github_com_MystenLabs_sui_git_main`.
+fn git_cache_dir(url: &str, rev: &str) -> String {
+    let url: String = url
+        .chars()
+        .map(|c| {
+            if matches!(c, '/' | ':' | '.' | '@') {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect();
+    format!("{url}_{}", rev.replace('/', "__"))
+}
+
+/// Convert a Move package name to the name of a Rust module, e.g., `MoveStdlib` -> `move_stdlib`.
+pub(crate) fn module_name(package: &str) -> String {
+    let mut name = String::with_capacity(package.len() + 4);
+    let mut prev_lower = false;
+    for c in package.chars() {
+        if c.is_uppercase() && prev_lower {
+            name.push('_');
+        }
+        prev_lower = c.is_lowercase() || c.is_ascii_digit();
+        if c == '-' {
+            name.push('_');
+        } else {
+            name.extend(c.to_lowercase());
+        }
+    }
+    name
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct RawManifest {
+    package: RawPackage,
+    #[serde(default)]
+    addresses: BTreeMap<String, String>,
+    #[serde(default)]
+    dev_addresses: BTreeMap<String, String>,
+    #[serde(default)]
+    dependencies: BTreeMap<String, RawDependency>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct RawPackage {
+    name: String,
+    published_at: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RawDependency {
+    local: Option<PathBuf>,
+    git: Option<String>,
+    subdir: Option<PathBuf>,
+    rev: Option<String>,
+}
+
+impl From<RawDependency> for Dependency {
+    fn from(value: RawDependency) -> Self {
+        let RawDependency {
+            local,
+            git,
+            subdir,
+            rev,
+        } = value;
+        match (local, git) {
+            (Some(path), _) => Self::Local(path),
+            (None, Some(url)) => Self::Git { url, subdir, rev },
+            (None, None) => Self::Other,
+        }
+    }
+}
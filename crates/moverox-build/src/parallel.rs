@@ -0,0 +1,277 @@
+//! Parsing and oxidizing the Move files on several threads.
+//!
+//! `move_syn` modules can't be sent to other threads, since they're made of `proc_macro2` tokens,
+//! so each worker thread keeps the modules it parsed until they're oxidized. The steps that need
+//! all of a package's modules, i.e., filtering, linking packages and the package-level items, work
+//! on summaries that the workers send to the main thread instead.
+
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, mpsc};
+use std::thread;
+
+use move_syn::{FlatImport, ItemKind, Module};
+use moverox_codegen::ModuleSummary;
+
+use crate::filter::{ModuleGraph, Selection};
+use crate::{Builder, Links, ModuleContext, Result};
+
+/// A package to oxidize: its builder and Move files.
+pub(crate) type Package<'s, 'a> = (&'s Builder<'a>, &'s [PathBuf]);
+
+/// What's learned about a package once its modules are parsed, for linking it to the other
+/// packages oxidized along with it.
+#[derive(Debug, Default)]
+pub(crate) struct PackageInfo {
+    /// Named addresses of its modules.
+    pub(crate) named_addresses: BTreeSet<String>,
+    /// Named addresses that its modules import from.
+    pub(crate) imported_addresses: BTreeSet<String>,
+}
+
+/// Position of a module: the index of its package, of its file in the package and of the module
+/// in the file.
+///
+/// Results are sorted by it so that the generated code doesn't depend on which thread parsed
+/// what.
+type Key = (usize, usize, usize);
+
+/// What the main thread learns about a module after it's parsed.
+struct Parsed {
+    named_address: String,
+    imported_addresses: Vec<String>,
+    /// Datatypes renamed with annotations, for the references from other modules.
+    renamed_datatypes: Vec<(String, String)>,
+    /// Only if filtering.
+    graph: Option<ModuleGraph>,
+}
+
+/// What the main thread tells the workers about a package once all modules are parsed.
+struct Plan {
+    named_addresses: Vec<String>,
+    links: Links,
+    renamed_datatypes: Vec<(String, String)>,
+    selection: Option<Selection>,
+}
+
+/// The oxidized module, as a string since tokens can't be sent to other threads, and its summary.
+type Oxidized = Option<(String, ModuleSummary)>;
+
+impl Builder<'_> {
+    /// Rust code for the package: a module for each Move module and the package-level items.
+    ///
+    /// Also returns the fully qualified names of the datatypes included only as dependencies; see
+    /// [`allow_modules`](Self::allow_modules).
+    pub(crate) fn generate_rust_in_parallel(
+        &self,
+        move_files: &[PathBuf],
+    ) -> Result<(syn::File, Vec<String>)> {
+        generate_rust(&[(self, move_files)], self.jobs, |infos| {
+            Ok(vec![Links::default(); infos.len()])
+        })?
+        .pop()
+        .ok_or_else(|| "no package generated".into())
+    }
+}
+
+/// Rust code for each package, like [`Builder::generate_rust_in_parallel`], on `jobs` threads at
+/// most.
+///
+/// Once all packages are parsed, `link` tells each one's paths to the others' items.
+pub(crate) fn generate_rust(
+    packages: &[Package<'_, '_>],
+    jobs: Option<usize>,
+    link: impl FnOnce(&[PackageInfo]) -> Result<Vec<Links>>,
+) -> Result<Vec<(syn::File, Vec<String>)>> {
+    let move_files: Vec<_> = packages
+        .iter()
+        .enumerate()
+        .flat_map(|(package, (_, files))| {
+            files
+                .iter()
+                .enumerate()
+                .map(move |(file, path)| (package, file, path.as_path()))
+        })
+        .collect();
+    let threads = jobs
+        .unwrap_or_else(|| thread::available_parallelism().map_or(1, Into::into))
+        .clamp(1, move_files.len().max(1));
+    let next_file = AtomicUsize::new(0);
+
+    thread::scope(|scope| {
+        let (parsed_tx, parsed_rx) = mpsc::channel();
+        let (oxidized_tx, oxidized_rx) = mpsc::channel();
+        let mut plan_txs = Vec::with_capacity(threads);
+        for _ in 0..threads {
+            let (plan_tx, plan_rx) = mpsc::channel();
+            plan_txs.push(plan_tx);
+            let worker = Worker {
+                packages,
+                move_files: &move_files,
+                next_file: &next_file,
+                parsed_tx: parsed_tx.clone(),
+                plan_rx,
+                oxidized_tx: oxidized_tx.clone(),
+            };
+            scope.spawn(move || worker.run());
+        }
+        // So that the receivers are done once the workers are
+        drop((parsed_tx, oxidized_tx));
+
+        // If any file fails to parse, report the first one's error, like a serial build would.
+        // Dropping the plan senders on return lets the workers finish.
+        let mut parsed: Vec<(Key, Result<Parsed>)> = parsed_rx.iter().collect();
+        parsed.sort_by_key(|(key, _)| *key);
+        let mut infos: Vec<_> = packages.iter().map(|_| PackageInfo::default()).collect();
+        let mut graphs: Vec<Vec<_>> = packages.iter().map(|_| vec![]).collect();
+        let mut renamed_datatypes: Vec<Vec<_>> = packages.iter().map(|_| vec![]).collect();
+        for ((package, _, _), result) in parsed {
+            let parsed = result?;
+            let info = &mut infos[package];
+            info.imported_addresses.extend(parsed.imported_addresses);
+            info.named_addresses.insert(parsed.named_address);
+            graphs[package].extend(parsed.graph);
+            renamed_datatypes[package].extend(parsed.renamed_datatypes);
+        }
+
+        let links = link(&infos)?;
+        let mut plans = vec![];
+        let mut dependencies = vec![];
+        for ((((builder, _), info), (links, graphs)), renamed_datatypes) in packages
+            .iter()
+            .zip(infos)
+            .zip(links.into_iter().zip(graphs))
+            .zip(renamed_datatypes)
+        {
+            let selection = (!builder.filters.is_empty()).then(|| {
+                let mut selection = builder.filters.select(&graphs, builder.package_bytes());
+                dependencies.push(std::mem::take(&mut selection.dependencies));
+                selection
+            });
+            if selection.is_none() {
+                dependencies.push(vec![]);
+            }
+            plans.push(Plan {
+                named_addresses: info.named_addresses.into_iter().collect(),
+                links,
+                renamed_datatypes,
+                selection,
+            });
+        }
+        let plans = Arc::new(plans);
+        for plan_tx in plan_txs {
+            // A worker only hangs up early if it panicked, which the scope propagates
+            let _ = plan_tx.send(Arc::clone(&plans));
+        }
+
+        let mut oxidized: Vec<(Key, Result<Oxidized>)> = oxidized_rx.iter().collect();
+        oxidized.sort_by_key(|(key, _)| *key);
+        let mut generated_code: Vec<_> = packages.iter().map(|_| String::new()).collect();
+        let mut summaries: Vec<Vec<_>> = packages.iter().map(|_| vec![]).collect();
+        for ((package, _, _), result) in oxidized {
+            if let Some((rust_code, summary)) = result? {
+                generated_code[package].push_str(&rust_code);
+                summaries[package].push(summary);
+            }
+        }
+
+        packages
+            .iter()
+            .zip(generated_code)
+            .zip(summaries.iter().zip(dependencies))
+            .map(
+                |(((builder, _), mut generated_code), (summaries, dependencies))| {
+                    generated_code.push_str(&builder.package_to_rust(summaries)?.to_string());
+                    Ok((syn::parse_file(&generated_code)?, dependencies))
+                },
+            )
+            .collect()
+    })
+}
+
+/// Named addresses that the module imports from.
+fn imported_addresses(module: &Module) -> impl Iterator<Item = String> + '_ {
+    module
+        .items()
+        .filter_map(|item| match &item.kind {
+            ItemKind::Import(import) => Some(import),
+            _ => None,
+        })
+        .flat_map(|import| import.flatten())
+        .map(|(_, flat)| match flat {
+            FlatImport::Module { named_address, .. } | FlatImport::Item { named_address, .. } => {
+                named_address.to_string()
+            }
+        })
+}
+
+/// A thread parsing Move files until there are none left, then oxidizing the modules in them.
+struct Worker<'scope, 'a> {
+    packages: &'scope [Package<'scope, 'a>],
+    /// Package index, file index and path of each file.
+    move_files: &'scope [(usize, usize, &'scope Path)],
+    /// Index of the next file to parse, shared by all workers.
+    next_file: &'scope AtomicUsize,
+    parsed_tx: mpsc::Sender<(Key, Result<Parsed>)>,
+    plan_rx: mpsc::Receiver<Arc<Vec<Plan>>>,
+    oxidized_tx: mpsc::Sender<(Key, Result<Oxidized>)>,
+}
+
+impl Worker<'_, '_> {
+    fn run(self) {
+        let mut modules: Vec<(Key, Module)> = vec![];
+        loop {
+            let index = self.next_file.fetch_add(1, Ordering::Relaxed);
+            let Some(&(package, file, path)) = self.move_files.get(index) else {
+                break;
+            };
+            let (builder, _) = self.packages[package];
+            let parsed = match builder.parse_file(path) {
+                Ok(parsed) => parsed,
+                Err(err) => {
+                    let _ = self.parsed_tx.send(((package, file, 0), Err(err)));
+                    continue;
+                }
+            };
+            for (i, module) in parsed.into_iter().enumerate() {
+                let graph = (!builder.filters.is_empty())
+                    .then(|| builder.filters.graph(&module, builder.functions()));
+                let parsed = moverox_codegen::renamed_datatypes(&module)
+                    .map_err(|err| format!("module {}: {err}", module.ident).into())
+                    .map(|renamed_datatypes| Parsed {
+                        named_address: module.named_address.to_string(),
+                        imported_addresses: imported_addresses(&module).collect(),
+                        renamed_datatypes,
+                        graph,
+                    });
+                let _ = self.parsed_tx.send(((package, file, i), parsed));
+                modules.push(((package, file, i), module));
+            }
+        }
+        drop(self.parsed_tx);
+
+        let Ok(plans) = self.plan_rx.recv() else {
+            return;
+        };
+        let mut contexts: HashMap<usize, ModuleContext> = HashMap::new();
+        for (key, mut module) in modules {
+            let (builder, _) = self.packages[key.0];
+            let plan = &plans[key.0];
+            if let Some(selection) = &plan.selection {
+                builder.filters.retain(&mut module, selection);
+            }
+            let ctx = contexts.entry(key.0).or_insert_with(|| {
+                builder.module_context(
+                    plan.named_addresses.iter().cloned(),
+                    &plan.links,
+                    plan.renamed_datatypes.iter().cloned(),
+                )
+            });
+            let oxidized = builder.module_to_rust(&module, ctx).map(|oxidized| {
+                oxidized.map(|(rust_code, summary)| (rust_code.to_string(), summary))
+            });
+            let _ = self.oxidized_tx.send((key, oxidized));
+        }
+    }
+}
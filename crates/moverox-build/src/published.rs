@@ -0,0 +1,230 @@
+//! Reading the addresses a Move package is published at on each network.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::Result;
+
+pub(crate) const LOCK_FILE: &str = "Move.lock";
+pub(crate) const PUBLISHED_FILE: &str = "Published.toml";
+
+/// Where a Move package is published on a network, from its `Move.lock` or `Published.toml`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Publication {
+    /// Chain ID of the network, if recorded.
+    pub chain_id: Option<String>,
+    /// Address of the latest version of the package.
+    pub published_at: String,
+    /// Address of the first version of the package, if recorded.
+    pub original_id: Option<String>,
+}
+
+impl Publication {
+    /// Publications of the package at `pkg_path`, keyed by environment, e.g., `mainnet`.
+    ///
+    /// They're read from the `[published.<env>]` tables of `Published.toml` and the `[env.<env>]`
+    /// tables of `Move.lock`, with the former taking precedence. Either file may be missing.
+    pub fn read_all(pkg_path: impl AsRef<Path>) -> Result<BTreeMap<String, Self>> {
+        let pkg_path = pkg_path.as_ref();
+        let read = |file: &str, parse: fn(&str) -> Result<BTreeMap<String, Self>>| {
+            let path = pkg_path.join(file);
+            if !path.is_file() {
+                return Ok(BTreeMap::new());
+            }
+            let contents = std::fs::read_to_string(&path)
+                .map_err(|err| format!("reading {}: {err}", path.display()))?;
+            parse(&contents).map_err(|err| format!("{}: {err}", path.display()))
+        };
+        let mut publications = read(LOCK_FILE, Self::parse_lock)?;
+        publications.extend(read(PUBLISHED_FILE, Self::parse_published)?);
+        Ok(publications)
+    }
+
+    /// Parse the `[env.<env>]` tables of the contents of a `Move.lock` file.
+    pub fn parse_lock(toml: &str) -> Result<BTreeMap<String, Self>> {
+        let RawLock { env } = toml::from_str(toml)?;
+        Ok(env
+            .into_iter()
+            .map(|(name, env)| {
+                let publication = Self {
+                    chain_id: env.chain_id,
+                    published_at: env.latest_published_id,
+                    original_id: env.original_published_id,
+                };
+                (name, publication)
+            })
+            .collect())
+    }
+
+    /// Parse the `[published.<env>]` tables of the contents of a `Published.toml` file.
+    pub fn parse_published(toml: &str) -> Result<BTreeMap<String, Self>> {
+        let RawPublished { published } = toml::from_str(toml)?;
+        Ok(published
+            .into_iter()
+            .map(|(name, env)| {
+                let publication = Self {
+                    chain_id: env.chain_id,
+                    published_at: env.published_at,
+                    original_id: env.original_id,
+                };
+                (name, publication)
+            })
+            .collect())
+    }
+}
+
+/// Addresses of the package versions that first defined each datatype, keyed by
+/// `module::Datatype`, from a JSON file; see
+/// [`Builder::type_origins_file`](crate::Builder::type_origins_file).
+pub(crate) fn read_type_origins(path: &Path) -> Result<HashMap<String, String>> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|err| format!("reading {}: {err}", path.display()))?;
+    let table = match serde_json::from_str(&contents)
+        .map_err(|err| format!("{}: {err}", path.display()))?
+    {
+        RawTypeOrigins::Table(table) | RawTypeOrigins::Object { table } => table,
+    };
+    Ok(table
+        .into_iter()
+        .map(|origin| {
+            let datatype = format!("{}::{}", origin.module_name, origin.datatype_name);
+            (datatype, origin.package)
+        })
+        .collect())
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawTypeOrigins {
+    Table(Vec<RawTypeOrigin>),
+    Object {
+        #[serde(rename = "typeOriginTable")]
+        table: Vec<RawTypeOrigin>,
+    },
+}
+
+#[derive(Deserialize)]
+struct RawTypeOrigin {
+    module_name: String,
+    #[serde(alias = "struct_name")]
+    datatype_name: String,
+    package: String,
+}
+
+#[derive(Deserialize)]
+struct RawLock {
+    #[serde(default)]
+    env: BTreeMap<String, RawLockEnv>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct RawLockEnv {
+    chain_id: Option<String>,
+    original_published_id: Option<String>,
+    latest_published_id: String,
+}
+
+#[derive(Deserialize)]
+struct RawPublished {
+    #[serde(default)]
+    published: BTreeMap<String, RawPublishedEnv>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct RawPublishedEnv {
+    chain_id: Option<String>,
+    published_at: String,
+    original_id: Option<String>,
+}
+
+/// How the environment to take the package address from is selected; see
+/// [`Builder::environment`](crate::Builder::environment).
+#[derive(Clone, Debug)]
+pub(crate) enum Environment {
+    Named(String),
+    /// Named by an environment variable.
+    Var(String),
+    /// Named like the enabled Cargo feature.
+    Features,
+}
+
+impl Environment {
+    /// Name of the selected environment, among the ones in `publications` if selected by a
+    /// feature.
+    pub(crate) fn resolve(
+        &self,
+        publications: &BTreeMap<String, Publication>,
+        emit_rerun_if_changed: bool,
+    ) -> Result<String> {
+        match self {
+            Self::Named(name) => Ok(name.clone()),
+            Self::Var(var) => {
+                if emit_rerun_if_changed {
+                    println!("cargo:rerun-if-env-changed={var}");
+                }
+                std::env::var(var)
+                    .map_err(|err| format!("selecting the environment from `{var}`: {err}").into())
+            }
+            Self::Features => {
+                let enabled: Vec<_> = publications
+                    .keys()
+                    .filter(|name| std::env::var_os(feature_var(name)).is_some())
+                    .collect();
+                match enabled[..] {
+                    [name] => Ok(name.clone()),
+                    [] => Err(format!(
+                        "no Cargo feature enabled for any environment: {}",
+                        publications
+                            .keys()
+                            .map(|name| format!("`{name}`"))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )
+                    .into()),
+                    _ => Err(format!(
+                        "Cargo features enabled for several environments: {}",
+                        enabled
+                            .iter()
+                            .map(|name| format!("`{name}`"))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )
+                    .into()),
+                }
+            }
+        }
+    }
+}
+
+/// Environment variable that Cargo sets for build scripts if the feature named like the
+/// environment is enabled.
+fn feature_var(environment: &str) -> String {
+    format!(
+        "CARGO_FEATURE_{}",
+        environment.to_uppercase().replace('-', "_")
+    )
+}
+
+/// Name of the Rust constant for the address on an environment, e.g., `MAINNET`.
+pub(crate) fn constant_name(environment: &str) -> String {
+    let name: String = environment
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{name}")
+    } else {
+        name
+    }
+}
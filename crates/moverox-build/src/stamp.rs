@@ -0,0 +1,80 @@
+//! Skipping code generation when the inputs of a build haven't changed.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest as _, Sha256};
+
+use crate::Result;
+
+/// Record of the inputs and outputs of a build, kept next to the outputs.
+#[derive(Deserialize, Serialize)]
+pub(crate) struct Stamp {
+    /// Hash of the builder settings and the Move sources; see [`hash_inputs`].
+    pub(crate) inputs: String,
+    /// Generated files, relative to the output directory.
+    pub(crate) outputs: Vec<PathBuf>,
+}
+
+impl Stamp {
+    /// Path of the stamp for the package `name` in `out_dir`.
+    pub(crate) fn path(out_dir: &Path, name: &str) -> PathBuf {
+        out_dir.join(format!("{name}.moverox.toml"))
+    }
+
+    /// The stamp at `path`, if there's a valid one.
+    pub(crate) fn read(path: &Path) -> Option<Self> {
+        let contents = std::fs::read_to_string(path).ok()?;
+        toml::from_str(&contents).ok()
+    }
+
+    pub(crate) fn write(&self, path: &Path) -> Result<()> {
+        let contents = format!(
+            "# This file is @generated by moverox-build.\n{}",
+            toml::to_string(self)?
+        );
+        write_if_changed(path, &contents)
+    }
+
+    /// Whether the outputs were generated from `inputs` and are all still in `out_dir`.
+    pub(crate) fn is_fresh(&self, inputs: &str, out_dir: &Path) -> bool {
+        self.inputs == inputs && self.outputs.iter().all(|path| out_dir.join(path).is_file())
+    }
+}
+
+/// Hex-encoded SHA-256 of the `settings` and the paths and contents of the `move_files`.
+pub(crate) fn hash_inputs(settings: &str, move_files: &[PathBuf]) -> Result<String> {
+    let mut move_files: Vec<_> = move_files.iter().collect();
+    move_files.sort();
+
+    let mut hasher = Sha256::new();
+    update_with(&mut hasher, settings.as_bytes());
+    for path in move_files {
+        update_with(&mut hasher, path.as_os_str().as_encoded_bytes());
+        update_with(&mut hasher, &std::fs::read(path)?);
+    }
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect())
+}
+
+/// Length-prefixed, so that the boundaries between inputs are part of the hash.
+fn update_with(hasher: &mut Sha256, bytes: &[u8]) {
+    hasher.update((bytes.len() as u64).to_le_bytes());
+    hasher.update(bytes);
+}
+
+/// Write `contents` to `path`, unless it has them already, so that its modification time is only
+/// bumped when it changes.
+pub(crate) fn write_if_changed(path: &Path, contents: &str) -> Result<()> {
+    if std::fs::read(path).is_ok_and(|current| current == contents.as_bytes()) {
+        return Ok(());
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, contents)
+        .map_err(|err| format!("writing {}: {err}", path.display()).into())
+}
@@ -1,9 +1,16 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use itertools::Itertools as _;
 use testresult::TestResult;
 
-use crate::move_package;
+use crate::{
+    Builder,
+    Dependency,
+    Manifest,
+    move_package,
+    move_package_from_manifest,
+    move_workspace,
+};
 
 #[test]
 fn generate_rust_for_move_stdlib() -> TestResult {
@@ -46,10 +53,7 @@ fn generate_rust_for_move_stdlib() -> TestResult {
     vector.move
     ");
 
-    let modules = builder.parse_files(&move_files)?;
-    let rust_code = builder.generate_rust_str(&modules)?;
-
-    let move_stdlib = prettyplease::unparse(&syn::parse_file(&rust_code).unwrap());
+    let move_stdlib = rust_code(builder, &move_files)?;
     insta::assert_snapshot!("MoveStdLib", move_stdlib);
     Ok(())
 }
@@ -139,10 +143,7 @@ fn generate_rust_for_sui_framework() -> TestResult {
     versioned.move
     ");
 
-    let modules = builder.parse_files(&move_files)?;
-    let rust_code = builder.generate_rust_str(&modules)?;
-
-    let sui_framework = prettyplease::unparse(&syn::parse_file(&rust_code).unwrap());
+    let sui_framework = rust_code(builder, &move_files)?;
     insta::assert_snapshot!("Sui", sui_framework);
     Ok(())
 }
@@ -167,14 +168,924 @@ fn generate_rust_for_enums() -> TestResult {
     other.move
     ");
 
-    let modules = builder.parse_files(&move_files)?;
-    let rust_code = builder.generate_rust_str(&modules)?;
-
-    let enums = prettyplease::unparse(&syn::parse_file(&rust_code).unwrap());
+    let enums = rust_code(builder, &move_files)?;
     insta::assert_snapshot!("Enums", enums);
     Ok(())
 }
 
+#[test]
+fn parse_error_shows_location() -> TestResult {
+    let tempdir = tempfile::tempdir()?;
+    let broken = indoc::indoc! {"
+        module pkg::broken;
+
+        public struct Broken has key {
+            id: UID,
+            value u64,
+        }
+    "};
+    fake_package(tempdir.path(), "Broken", "pkg", &[("broken.move", broken)])?;
+
+    let builder = move_package(tempdir.path(), "Broken");
+    let move_files = builder.collect_move_files()?;
+    let err = rust_code(builder, &move_files)
+        .expect_err("Missing colon")
+        .to_string()
+        .replace(&tempdir.path().display().to_string(), "<pkg>");
+    insta::assert_snapshot!(err, @r"
+    unexpected `value`
+     --> <pkg>/sources/broken.move:5:5
+      |
+    5 |     value u64,
+      |     ^^^^^
+    ");
+    Ok(())
+}
+
+#[test]
+fn manifests() -> TestResult {
+    let enums = Manifest::read(move_dir()?.join("enums"))?;
+    assert_eq!(enums.name, "Enums");
+    assert_eq!(enums.published_at, None);
+    assert_eq!(enums.addresses["enums"], None, "Placeholder address");
+    assert_eq!(enums.dev_addresses["enums"], "0x0");
+    assert_eq!(
+        enums.dependencies["MoveStdlib"],
+        Dependency::Local("../move-stdlib".into())
+    );
+
+    let sui = Manifest::read(move_dir()?.join("sui-framework"))?;
+    assert_eq!(sui.name, "Sui");
+    assert_eq!(sui.published_at.as_deref(), Some("0x2"));
+    assert_eq!(sui.addresses["sui"].as_deref(), Some("0x2"));
+
+    let margin = Manifest::read(move_dir()?.join("deepbook_margin"))?;
+    assert_eq!(
+        margin.dependencies["deepbook"],
+        Dependency::Local("../deepbook".into())
+    );
+    assert!(matches!(
+        &margin.dependencies["token"],
+        Dependency::Git {
+            subdir: Some(_),
+            ..
+        }
+    ));
+
+    // Every package in the repository has a valid manifest
+    for entry in std::fs::read_dir(move_dir()?)? {
+        let path = entry?.path();
+        if path.join("Move.toml").exists() {
+            Manifest::read(&path)?;
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn manifest_defaults() -> TestResult {
+    let mut builder = move_package_from_manifest(move_dir()?.join("enums"))?;
+    builder.apply_manifest()?;
+    assert_eq!(builder.name, "Enums");
+    assert_eq!(builder.published_at, None);
+    let addresses = address_mappings(&builder);
+    insta::assert_snapshot!(addresses, @r"
+    enums => super
+    std => crate::move_stdlib
+    sui => crate::sui
+    ");
+
+    let mut builder = move_package(move_dir()?.join("sui-framework"), "sui")
+        .map_address("std", "::moverox_sui::std");
+    builder.apply_manifest()?;
+    assert_eq!(builder.published_at.as_deref(), Some("0x2"));
+    let addresses = address_mappings(&builder);
+    insta::assert_snapshot!(addresses, @r"
+    std => ::moverox_sui::std
+    sui => super
+    ");
+
+    // Explicit settings override the manifest
+    let mut builder = move_package(move_dir()?.join("sui-framework"), "sui").published_at("0x3");
+    builder.apply_manifest()?;
+    assert_eq!(builder.published_at.as_deref(), Some("0x3"));
+    Ok(())
+}
+
+/// The named address mappings, with the explicit ones overriding the ones from `Move.toml`s.
+fn address_mappings(builder: &Builder<'_>) -> String {
+    builder
+        .manifest_address_map
+        .iter()
+        .chain(&builder.address_map)
+        .collect::<std::collections::HashMap<_, _>>()
+        .into_iter()
+        .map(|(named, path)| format!("{named} => {path}"))
+        .sorted()
+        .join("\n")
+}
+
+#[test]
+fn dev_addresses_with_test_items() -> TestResult {
+    let tempdir = tempfile::tempdir()?;
+    let pool = indoc::indoc! {"
+        module pkg::pool;
+
+        public struct Pool has store { fee: u64 }
+
+        #[test_only]
+        public struct MockPool has store { oracle: 0xbeef::oracle::Oracle }
+    "};
+    let oracle = indoc::indoc! {"
+        #[test_only]
+        module mocks::oracle;
+
+        public struct Oracle has store { price: u64 }
+    "};
+    fake_package(
+        tempdir.path(),
+        "pkg",
+        "pkg",
+        &[("pool.move", pool), ("oracle.move", oracle)],
+    )?;
+    extend_manifest(tempdir.path(), "\n[dev-addresses]\nmocks = \"0xbeef\"\n")?;
+    let package = || move_package(tempdir.path(), "pkg").emit_rerun_if_changed(false);
+
+    // Dev-only named addresses are only used with test items
+    let mut builder = package();
+    builder.apply_manifest()?;
+    assert_eq!(address_mappings(&builder), "pkg => super");
+    let mut builder = package().with_test_items();
+    builder.apply_manifest()?;
+    insta::assert_snapshot!(address_mappings(&builder), @r"
+    mocks => super
+    pkg => super
+    ");
+
+    // The numerical address maps like the named one
+    let generated = package().with_test_items().generate()?;
+    let oracle = generated
+        .rust_code
+        .lines()
+        .find(|line| line.contains("oracle: "))
+        .ok_or("MockPool field")?;
+    insta::assert_snapshot!(oracle.trim(), @"pub oracle: super::oracle::Oracle,");
+    Ok(())
+}
+
+#[test]
+fn without_manifest() -> TestResult {
+    let tempdir = tempfile::tempdir()?;
+    let mut builder = move_package(tempdir.path(), "Legacy");
+    builder.apply_manifest()?;
+    assert!(address_mappings(&builder).is_empty());
+    assert!(move_package_from_manifest(tempdir.path()).is_err());
+    Ok(())
+}
+
+#[test]
+fn numeric_addresses_map_like_named_ones() -> TestResult {
+    let tempdir = tempfile::tempdir()?;
+    let vault = indoc::indoc! {"
+        module vaults::vault;
+
+        public struct Vault<phantom T> has key {
+            id: 0x2::object::UID,
+            balance: 0x0000000000000000000000000000000000000000000000000000000000000002::balance::Balance<T>,
+            name: 0x1::string::String,
+        }
+    "};
+    fake_package(tempdir.path(), "Vaults", "vaults", &[("vault.move", vault)])?;
+    let sui_framework = move_dir()?.join("sui-framework");
+    extend_manifest(
+        tempdir.path(),
+        &format!(
+            "\n[dependencies]\nSui = {{ local = {:?} }}\n",
+            sui_framework.display()
+        ),
+    )?;
+
+    let mut builder = move_package(tempdir.path(), "Vaults").map_address("0x1", "::std_rs");
+    builder.apply_manifest()?;
+    let move_files = builder.collect_move_files()?;
+    let fields = rust_code(builder, &move_files)?
+        .lines()
+        .filter(|line| line.trim_start().starts_with("pub ") && line.contains(": "))
+        .map(str::trim)
+        .join("\n");
+    insta::assert_snapshot!(fields, @r"
+    pub id: crate::sui::object::UID,
+    pub balance: crate::sui::balance::Balance<T>,
+    pub name: ::std_rs::string::String,
+    ");
+    Ok(())
+}
+
+#[test]
+fn filters_keep_dependencies() -> TestResult {
+    let builder = move_package(move_dir()?.join("deepbook"), "Deepbook")
+        .with_implicit_sui_imports()
+        .allow_types("deepbook::pool::PoolInner")
+        .allow_modules("regex:deep_price|ewma")
+        .deny_types("deepbook::ewma::*")
+        .deny_types("deepbook::book::Book");
+    let move_files = builder.collect_move_files()?;
+    let (file, dependencies) = builder.jobs(1).generate_rust_in_parallel(&move_files)?;
+    insta::assert_snapshot!(dependencies.join("\n"), @r"
+    deepbook::account::Account
+    deepbook::balances::Balances
+    deepbook::big_vector::BigVector
+    deepbook::book::Book
+    deepbook::governance::Governance
+    deepbook::governance::Proposal
+    deepbook::history::History
+    deepbook::history::Volumes
+    deepbook::order::Order
+    deepbook::state::State
+    deepbook::trade_params::TradeParams
+    deepbook::vault::Vault
+    ");
+
+    // The generated code only has the kept modules
+    let kept = file
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            syn::Item::Mod(module) => Some(module),
+            _ => None,
+        })
+        .map(|module| {
+            let items = module.content.iter().flat_map(|(_, items)| items);
+            let datatypes = items
+                .clone()
+                .filter_map(|item| match item {
+                    syn::Item::Struct(s) => Some(s.ident.to_string()),
+                    syn::Item::Enum(e) => Some(e.ident.to_string()),
+                    _ => None,
+                })
+                .join(", ");
+            let consts = items
+                .filter(|item| matches!(item, syn::Item::Const(_)))
+                .count();
+            (module.ident.to_string(), datatypes, consts)
+        })
+        .filter(|(_, datatypes, consts)| !datatypes.is_empty() || *consts > 0)
+        .sorted()
+        .map(|(module, datatypes, consts)| format!("{module}: [{datatypes}], {consts} constants"))
+        .join("\n");
+    insta::assert_snapshot!(kept, @r"
+    account: [Account], 0 constants
+    balances: [Balances], 0 constants
+    big_vector: [BigVector], 0 constants
+    book: [Book], 0 constants
+    deep_price: [Price, PriceAdded, DeepPrice, OrderDeepPrice, Error], 3 constants
+    governance: [Proposal, Governance], 0 constants
+    history: [Volumes, History], 0 constants
+    order: [Order], 0 constants
+    pool: [PoolInner], 0 constants
+    state: [State], 0 constants
+    trade_params: [TradeParams], 0 constants
+    vault: [Vault], 0 constants
+    ");
+    Ok(())
+}
+
+#[test]
+fn file_per_module() -> TestResult {
+    let tempdir = tempfile::tempdir()?;
+    move_package(move_dir()?.join("enums"), "enums")
+        .with_implicit_sui_imports()
+        .with_file_per_module()
+        .emit_rerun_if_changed(false)
+        .out_dir(tempdir.path())
+        .build()?;
+
+    let root = std::fs::read_to_string(tempdir.path().join("enums.rs"))?;
+    let modules = root
+        .lines()
+        .filter(|line| line.contains("mod ") || line.contains("include!"))
+        .join("\n");
+    insta::assert_snapshot!(modules, @r#"
+    pub mod enums {
+        include!("enums/enums.rs");
+    pub mod other {
+        include!("enums/other.rs");
+    "#);
+
+    let other = std::fs::read_to_string(tempdir.path().join("enums/other.rs"))?;
+    assert!(other.starts_with("// This file is @generated by moverox-build.\n"));
+    syn::parse_file(&other)?;
+    Ok(())
+}
+
+#[test]
+fn unchanged_inputs_skip_generation() -> TestResult {
+    let tempdir = tempfile::tempdir()?;
+    let pkg_path = tempdir.path().join("pkg");
+    let counter = "module pkg::counter;\n\npublic struct Counter has copy, drop { value: u64 }\n";
+    fake_package(&pkg_path, "pkg", "pkg", &[("counter.move", counter)])?;
+    let move_file = pkg_path.join("sources/counter.move");
+    let out_dir = tempdir.path().join("out");
+    let build = || {
+        move_package(&pkg_path, "pkg")
+            .emit_rerun_if_changed(false)
+            .out_dir(&out_dir)
+            .build()
+    };
+    let output = out_dir.join("pkg.rs");
+
+    build()?;
+    let stamp = std::fs::read_to_string(out_dir.join("pkg.moverox.toml"))?;
+    assert!(stamp.contains("outputs = [\"pkg.rs\"]"));
+
+    // Regenerating the same code doesn't touch the file
+    let modified = std::fs::metadata(&output)?.modified()?;
+    std::fs::write(
+        &move_file,
+        "module pkg::counter;\n\n// A comment\npublic struct Counter has copy, drop { value: u64 }\n",
+    )?;
+    build()?;
+    assert_eq!(std::fs::metadata(&output)?.modified()?, modified);
+
+    // Nothing is generated if the inputs are the same
+    std::fs::write(&output, "// edited")?;
+    build()?;
+    assert_eq!(std::fs::read_to_string(&output)?, "// edited");
+
+    // Changing the settings regenerates the code
+    move_package(&pkg_path, "pkg")
+        .emit_rerun_if_changed(false)
+        .out_dir(&out_dir)
+        .published_at("0x42")
+        .build()?;
+    assert!(std::fs::read_to_string(&output)?.contains("pub struct Counter"));
+
+    // Missing outputs are regenerated
+    std::fs::remove_file(&output)?;
+    build()?;
+    assert!(output.exists());
+    Ok(())
+}
+
+#[test]
+fn generation_is_the_same_on_any_number_of_threads() -> TestResult {
+    let builders = [
+        move_package(move_dir()?.join("sui-framework"), "Sui")
+            .published_at("0x2")
+            .with_implicit_sui_imports()
+            .with_call_descriptors()
+            .with_events()
+            .map_address("std", "::moverox_sui::move_stdlib"),
+        move_package(move_dir()?.join("deepbook"), "Deepbook")
+            .with_implicit_sui_imports()
+            .allow_types("deepbook::pool::PoolInner")
+            .deny_types("deepbook::book::Book"),
+    ];
+    for mut builder in builders {
+        let move_files = builder.collect_move_files()?;
+        builder.jobs = Some(1);
+        let (file, dependencies) = builder.generate_rust_in_parallel(&move_files)?;
+        let single_threaded = prettyplease::unparse(&file);
+
+        builder.jobs = Some(4);
+        let (file, parallel_dependencies) = builder.generate_rust_in_parallel(&move_files)?;
+        assert_eq!(prettyplease::unparse(&file), single_threaded);
+        assert_eq!(parallel_dependencies, dependencies);
+    }
+    Ok(())
+}
+
+#[test]
+fn workspace_links_packages() -> TestResult {
+    let package = |name: &'static str| {
+        move_package(move_dir().unwrap().join(name), name)
+            .with_implicit_sui_imports()
+            .map_address("std", "::moverox_sui::std")
+            .map_address("sui", "::moverox_sui::sui")
+    };
+    // Added out of order on purpose
+    let generated = move_workspace("deepbookv3")
+        .package(package("margin_liquidation").with_call_descriptors())
+        .package(package("deepbook_margin"))
+        .package(package("deepbook").published_at("0xdee9"))
+        .emit_rerun_if_changed(false)
+        .jobs(2)
+        .generate()?;
+
+    let packages = generated
+        .rust_code
+        .lines()
+        .filter(|line| line.starts_with("pub mod"))
+        .join("\n");
+    insta::assert_snapshot!(packages, @r"
+    pub mod deepbook {
+    pub mod deepbook_margin {
+    pub mod margin_liquidation {
+    ");
+    assert!(
+        generated
+            .rust_code
+            .contains("super::super::deepbook::balance_manager::BalanceManager")
+    );
+    assert!(
+        generated
+            .rust_code
+            .contains("super::super::deepbook_margin::margin_pool::MarginPool")
+    );
+    Ok(())
+}
+
+#[test]
+fn workspace_rejects_cyclic_packages() -> TestResult {
+    let tempdir = tempfile::tempdir()?;
+    for (name, other) in [("a", "b"), ("b", "a")] {
+        let m =
+            format!("module {name}::m;\n\nuse {other}::m::S as T;\n\npublic struct S {{ t: T }}\n");
+        fake_package(&tempdir.path().join(name), name, name, &[("m.move", &m)])?;
+    }
+    let err = move_workspace("cyclic")
+        .package(move_package(tempdir.path().join("a"), "a"))
+        .package(move_package(tempdir.path().join("b"), "b"))
+        .emit_rerun_if_changed(false)
+        .generate()
+        .unwrap_err();
+    assert_eq!(err.to_string(), "packages depend on each other: `a`, `b`");
+    Ok(())
+}
+
+#[test]
+fn git_dependencies_from_package_cache() -> TestResult {
+    let tempdir = tempfile::tempdir()?;
+    let package_cache = tempdir.path().join("cache");
+    let deepbookv3 = package_cache.join("https___github_com_MystenLabs_deepbookv3_main/packages");
+    copy_dir(&move_dir()?.join("deepbook"), &deepbookv3.join("deepbook"))?;
+    copy_dir(
+        &move_dir()?.join("deepbook_margin"),
+        &deepbookv3.join("deepbook_margin"),
+    )?;
+    let token =
+        package_cache.join("https___github_com_MystenLabs_deepbookv3_git_main/packages/token");
+    fake_package(&token, "token", "token", &[])?;
+    let vendor_dir = tempdir.path().join("vendor");
+    fake_package(&vendor_dir.join("Pyth"), "Pyth", "pyth", &[])?;
+
+    let package = || {
+        move_package(
+            move_dir().unwrap().join("margin_liquidation"),
+            "margin_liquidation",
+        )
+        .package_cache(&package_cache)
+        .emit_rerun_if_changed(false)
+    };
+    let mut builder = package().vendor_dir(&vendor_dir);
+    builder.apply_manifest()?;
+    insta::assert_snapshot!(address_mappings(&builder), @r"
+    deepbook => crate::deepbook
+    deepbook_margin => crate::deepbook_margin
+    margin_liquidation => super
+    pyth => crate::pyth
+    token => crate::token
+    ");
+
+    // Every git dependency must be found once there's somewhere to look for them
+    let err = package().generate().unwrap_err().to_string();
+    assert!(
+        err.starts_with(
+            "dependency `Pyth` of `margin_liquidation` on \
+             https://github.com/pyth-network/pyth-crosschain.git not found in "
+        ),
+        "{err}"
+    );
+
+    let generated = move_workspace("liquidation")
+        .package(
+            package()
+                .vendor_dir(&vendor_dir)
+                .with_implicit_sui_imports()
+                .with_call_descriptors()
+                .map_address("std", "::moverox_sui::std")
+                .map_address("sui", "::moverox_sui::sui"),
+        )
+        .with_dependencies()
+        .emit_rerun_if_changed(false)
+        .generate()?;
+    let packages = generated
+        .rust_code
+        .lines()
+        .filter(|line| line.starts_with("pub mod"))
+        .join("\n");
+    insta::assert_snapshot!(packages, @r"
+    pub mod pyth {}
+    pub mod token {}
+    pub mod deepbook {
+    pub mod deepbook_margin {
+    pub mod margin_liquidation {
+    ");
+    assert!(
+        generated
+            .rust_code
+            .contains("super::super::pyth::price_info::PriceInfoObject")
+    );
+    Ok(())
+}
+
+#[test]
+fn git_dependencies_without_rev_need_a_vendor_dir() -> TestResult {
+    let tempdir = tempfile::tempdir()?;
+    let pkg_path = tempdir.path().join("pkg");
+    fake_package(&pkg_path, "pkg", "pkg", &[])?;
+    extend_manifest(
+        &pkg_path,
+        "\n[dependencies]\nToken = { git = \"https://github.com/MystenLabs/deepbookv3.git\" }\n",
+    )?;
+    let vendor_dir = tempdir.path().join("vendor");
+    fake_package(&vendor_dir.join("Token"), "Token", "token", &[])?;
+
+    let package = || {
+        move_package(&pkg_path, "pkg")
+            .package_cache(tempdir.path().join("cache"))
+            .emit_rerun_if_changed(false)
+    };
+    let err = package().apply_manifest().unwrap_err();
+    insta::assert_snapshot!(err, @"dependency `Token` of `pkg` on https://github.com/MystenLabs/deepbookv3.git has no `rev` to find it in the package cache");
+
+    let mut builder = package().vendor_dir(&vendor_dir);
+    builder.apply_manifest()?;
+    insta::assert_snapshot!(address_mappings(&builder), @r"
+    pkg => super
+    token => crate::token
+    ");
+    Ok(())
+}
+
+#[test]
+fn publications_per_environment() -> TestResult {
+    let tempdir = tempfile::tempdir()?;
+    let pkg_path = tempdir.path();
+    let counter = "module pkg::counter;\n\npublic struct Counter has copy, drop { value: u64 }\n";
+    fake_package(pkg_path, "pkg", "pkg", &[("counter.move", counter)])?;
+    std::fs::write(
+        pkg_path.join("Move.lock"),
+        indoc::indoc! {r#"
+            [move]
+            version = 3
+
+            [env.mainnet]
+            chain-id = "35834a8a"
+            original-published-id = "0xaaa"
+            latest-published-id = "0xaaa2"
+            published-version = "2"
+
+            [env.testnet]
+            chain-id = "4c78adac"
+            original-published-id = "0xbbb"
+            latest-published-id = "0xbbb"
+            published-version = "1"
+        "#},
+    )?;
+    std::fs::write(
+        pkg_path.join("Published.toml"),
+        indoc::indoc! {r#"
+            [published.testnet]
+            chain-id = "4c78adac"
+            published-at = "0xbbb3"
+            original-id = "0xbbb"
+            version = 3
+        "#},
+    )?;
+    let package = || move_package(pkg_path, "pkg").emit_rerun_if_changed(false);
+
+    let mut builder = package().environment("mainnet");
+    builder.apply_manifest()?;
+    assert_eq!(builder.published_at.as_deref(), Some("0xaaa2"));
+
+    // Published.toml takes precedence over Move.lock
+    let mut builder = package().environment("testnet");
+    builder.apply_manifest()?;
+    assert_eq!(builder.published_at.as_deref(), Some("0xbbb3"));
+
+    // Explicit addresses take precedence over the environment's
+    let mut builder = package().environment("testnet").published_at("0x42");
+    builder.apply_manifest()?;
+    assert_eq!(builder.published_at.as_deref(), Some("0x42"));
+
+    let err = package().environment("devnet").generate().unwrap_err();
+    insta::assert_snapshot!(err, @"package `pkg` has no publication on environment `devnet` in Published.toml or Move.lock; it has: `mainnet`, `testnet`");
+
+    let generated = package().with_published_constants().generate()?;
+    let start = generated
+        .rust_code
+        .find("pub mod published_at")
+        .ok_or("published_at module")?;
+    let constants = &generated.rust_code[start..];
+    insta::assert_snapshot!(constants, @r#"
+    pub mod published_at {
+        /// Address of the package on `mainnet`.
+        pub const MAINNET: ::moverox::types::Address = ::moverox::types::const_address(
+            b"0xaaa2",
+        );
+        /// Address of the package on `testnet`.
+        pub const TESTNET: ::moverox::types::Address = ::moverox::types::const_address(
+            b"0xbbb3",
+        );
+    }
+    "#);
+    Ok(())
+}
+
+#[test]
+fn type_origins_from_file() -> TestResult {
+    let tempdir = tempfile::tempdir()?;
+    let pkg_path = tempdir.path().join("pkg");
+    let pool = indoc::indoc! {"
+        module pkg::pool;
+
+        public struct Pool has key { id: UID }
+        public struct Config has copy, drop, store { fee: u64 }
+        public struct Upgraded has copy, drop { version: u64 }
+    "};
+    fake_package(&pkg_path, "pkg", "pkg", &[("pool.move", pool)])?;
+    let type_origins = tempdir.path().join("type_origins.json");
+    std::fs::write(
+        &type_origins,
+        r#"{
+            "typeOriginTable": [
+                { "module_name": "pool", "datatype_name": "Pool", "package": "0x1" },
+                { "module_name": "pool", "struct_name": "Config", "package": "0x1" }
+            ]
+        }"#,
+    )?;
+
+    let generated = move_package(&pkg_path, "pkg")
+        .with_implicit_sui_imports()
+        .published_at("0x3")
+        .type_origins_file(&type_origins)
+        .type_origin("pool::Config", "0x2")
+        .emit_rerun_if_changed(false)
+        .generate()?;
+    let addresses = generated
+        .rust_code
+        .lines()
+        .filter(|line| line.contains("move_(address"))
+        .join("\n");
+    // The explicit origin of `Config` overrides the file's
+    insta::assert_snapshot!(addresses, @r#"
+    #[move_(address = "0x1")]
+    #[move_(address = "0x2")]
+    #[move_(address = "0x3")]
+    "#);
+    Ok(())
+}
+
+#[test]
+fn derive_and_attribute_rules() -> TestResult {
+    let tempdir = tempfile::tempdir()?;
+    let pool = indoc::indoc! {"
+        module pkg::pool;
+
+        public struct Config has copy, drop, store { fee: u64 }
+        public struct Fee has copy, drop, store { value: u64 }
+    "};
+    fake_package(tempdir.path(), "pkg", "pkg", &[("pool.move", pool)])?;
+
+    let generated = move_package(tempdir.path(), "pkg")
+        .add_derives("*", "PartialOrd, Ord")
+        .remove_derives("pool", "Hash")
+        .add_derives("pool::Fee", "Copy")
+        .add_attribute("pool::Fee", "serde(transparent)")
+        .emit_rerun_if_changed(false)
+        .generate()?;
+    let derives = generated
+        .rust_code
+        .lines()
+        .filter(|line| {
+            line.contains("PartialOrd")
+                || line.contains("Copy")
+                || line.contains("Hash")
+                || line.contains("transparent")
+        })
+        .join("\n");
+    // `Hash` is removed from all of them, `Copy` only added to `Fee`
+    insta::assert_snapshot!(derives, @r"
+        PartialOrd,
+        PartialOrd,
+        Copy,
+    #[serde(transparent)]
+    ");
+    Ok(())
+}
+
+#[test]
+fn renamed_datatypes() -> TestResult {
+    let tempdir = tempfile::tempdir()?;
+    let dynamic = indoc::indoc! {r#"
+        module pkg::dynamic;
+
+        #[ext(moverox(rename = "DynField"))]
+        public struct Field<Name, Value> has store { name: Name, value: Value }
+    "#};
+    let pool = indoc::indoc! {"
+        module pkg::pool;
+
+        use pkg::dynamic::Field;
+
+        public struct Pool has store { fields: vector<Field<u64, bool>>, type: u8 }
+
+        public struct Deposited has copy, drop { amount: u64 }
+    "};
+    fake_package(
+        tempdir.path(),
+        "pkg",
+        "pkg",
+        &[("dynamic.move", dynamic), ("pool.move", pool)],
+    )?;
+
+    let generated = move_package(tempdir.path(), "pkg")
+        .with_events()
+        .rename("pool::Deposited", "DepositEvent")
+        .rename("pool::Pool::type", "kind")
+        .emit_rerun_if_changed(false)
+        .generate()?;
+    let lines = generated
+        .rust_code
+        .lines()
+        .filter(|line| {
+            ["DynField", "DepositEvent", "kind", "name ="]
+                .iter()
+                .any(|s| line.contains(s))
+        })
+        .join("\n");
+    insta::assert_snapshot!(lines, @r#"
+        pub fields: vector<super::dynamic::DynField<u64, bool>>,
+        #[serde(rename = "type")]
+        pub kind: u8,
+            fields: vector<super::dynamic::DynField<u64, bool>>,
+            kind: u8,
+            Self { fields, kind }
+    #[move_(name = "Deposited")]
+    pub struct DepositEvent {
+    impl DepositEvent {
+    #[move_(name = "Field")]
+    pub struct DynField<Name, Value> {
+    impl<Name, Value> DynField<Name, Value> {
+    pool_Deposited(pool::DepositEvent),
+    "#);
+    Ok(())
+}
+
+#[test]
+fn type_parameter_defaults() -> TestResult {
+    let tempdir = tempfile::tempdir()?;
+    let usd = indoc::indoc! {"
+        module pkg::usd;
+
+        public struct USD has drop {}
+    "};
+    let pool = indoc::indoc! {"
+        module pkg::pool;
+
+        use pkg::usd::{Self, USD};
+
+        #[ext(moverox(type_(T = USD)))]
+        public struct Pool<phantom T> has store { value: u64 }
+
+        #[ext(moverox(type_(T = OTW)))]
+        public struct Vault<phantom T> has store { value: u64 }
+    "};
+    fake_package(
+        tempdir.path(),
+        "pkg",
+        "pkg",
+        &[("usd.move", usd), ("pool.move", pool)],
+    )?;
+
+    let generated = move_package(tempdir.path(), "pkg")
+        .type_default("pool::Vault::T", "usd::USD")
+        .emit_rerun_if_changed(false)
+        .generate()?;
+    let lines = generated
+        .rust_code
+        .lines()
+        .filter(|line| line.contains("pub struct"))
+        .join("\n");
+    insta::assert_snapshot!(lines, @r#"
+    pub struct Pool<T = super::usd::USD> {
+    pub struct Vault<T = super::usd::USD> {
+    pub struct USD {
+    "#);
+    Ok(())
+}
+
+#[test]
+fn custom_rust_types() -> TestResult {
+    let tempdir = tempfile::tempdir()?;
+    let pool = indoc::indoc! {"
+        module pkg::pool;
+
+        use std::string::String;
+        use sui::vec_map::VecMap;
+
+        public struct Pool has key {
+            id: UID,
+            name: String,
+            fee: 0x1::option::Option<u64>,
+            fees: VecMap<address, u64>,
+        }
+
+        public fun rename(pool: &mut Pool, name: String) { pool.name = name; }
+    "};
+    fake_package(tempdir.path(), "pkg", "pkg", &[("pool.move", pool)])?;
+    // Appended to the `[addresses]` table
+    extend_manifest(tempdir.path(), "std = \"0x1\"\n")?;
+
+    let generated = move_package(tempdir.path(), "pkg")
+        .with_implicit_sui_imports()
+        .with_call_descriptors()
+        .map_address("std", "::moverox_sui::std")
+        .map_type("0x1::string::String", "::std::string::String")
+        .map_type("std::option::Option", "::std::option::Option")
+        .map_type_with(
+            "sui::vec_map::VecMap",
+            "::std::collections::BTreeMap",
+            "crate::vec_map",
+        )
+        .emit_rerun_if_changed(false)
+        .generate()?;
+    let lines = generated
+        .rust_code
+        .lines()
+        .filter(|line| {
+            ["String", "fee", "serde(with"]
+                .iter()
+                .any(|s| line.contains(s))
+        })
+        .join("\n");
+    // Function signatures keep the oxidized Move types
+    insta::assert_snapshot!(lines, @r#"
+    pub name: ::std::string::String,
+    pub fee: ::std::option::Option<u64>,
+    #[serde(with = "crate::vec_map")]
+    pub fees: ::std::collections::BTreeMap<address, u64>,
+        name: ::std::string::String,
+        fee: ::std::option::Option<u64>,
+        fees: ::std::collections::BTreeMap<address, u64>,
+        Self { id, name, fee, fees }
+    name: ::moverox_sui::std::string::String,
+    (::moverox_sui::std::string::String,),
+    "#);
+    Ok(())
+}
+
+/// The pretty-printed Rust code for the package on a single thread, without applying its
+/// `Move.toml`.
+fn rust_code(builder: Builder<'_>, move_files: &[PathBuf]) -> crate::Result<String> {
+    let (file, _) = builder.jobs(1).generate_rust_in_parallel(move_files)?;
+    Ok(prettyplease::unparse(&file))
+}
+
+/// A Move package with the given `(file name, source)` modules.
+fn fake_package(
+    pkg_path: &Path,
+    name: &str,
+    named_address: &str,
+    modules: &[(&str, &str)],
+) -> TestResult {
+    let sources = pkg_path.join("sources");
+    std::fs::create_dir_all(&sources)?;
+    std::fs::write(
+        pkg_path.join("Move.toml"),
+        format!("[package]\nname = \"{name}\"\n\n[addresses]\n{named_address} = \"0x0\"\n"),
+    )?;
+    for (file_name, source) in modules {
+        std::fs::write(sources.join(file_name), source)?;
+    }
+    Ok(())
+}
+
+/// Appends to the `Move.toml` of a [`fake_package`].
+fn extend_manifest(pkg_path: &Path, toml: &str) -> TestResult {
+    use std::io::Write as _;
+    let mut manifest = std::fs::OpenOptions::new()
+        .append(true)
+        .open(pkg_path.join("Move.toml"))?;
+    manifest.write_all(toml.as_bytes())?;
+    Ok(())
+}
+
+fn copy_dir(from: &Path, to: &Path) -> TestResult {
+    std::fs::create_dir_all(to)?;
+    for entry in std::fs::read_dir(from)? {
+        let path = entry?.path();
+        let target = to.join(path.file_name().ok_or("file name")?);
+        if path.is_dir() {
+            copy_dir(&path, &target)?;
+        } else {
+            std::fs::copy(&path, &target)?;
+        }
+    }
+    Ok(())
+}
+
 fn move_dir() -> TestResult<std::path::PathBuf> {
     Ok(Path::new(env!("CARGO_MANIFEST_DIR"))
         .parent()
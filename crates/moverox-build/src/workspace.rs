@@ -0,0 +1,354 @@
+//! Oxidizing several interdependent Move packages together.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::manifest::{self, Manifest};
+use crate::parallel::{self, PackageInfo};
+use crate::{Builder, Generated, Links, Result, default_out_dir, split_modules, write_outputs};
+
+/// Initialize a builder for oxidizing several interdependent Move packages into a single file,
+/// being available to import later via the `name`.
+///
+/// Add the packages with [`Workspace::package`].
+pub fn move_workspace(name: &str) -> Workspace<'_> {
+    Workspace {
+        name,
+        packages: vec![],
+        emit_rerun_if_changed: std::env::var_os("CARGO").is_some(),
+        out_dir: None,
+        jobs: None,
+        with_dependencies: false,
+    }
+}
+
+/// Builder for several Move packages oxidized together; see [`move_workspace`].
+///
+/// Each package becomes a Rust module, named after the package in snake case, e.g.,
+/// `deepbook_margin`. Paths to the items of the other packages are rewritten to point to their
+/// modules, so there's no need to [map their addresses](Builder::map_address).
+pub struct Workspace<'a> {
+    name: &'a str,
+    packages: Vec<Builder<'a>>,
+    emit_rerun_if_changed: bool,
+    out_dir: Option<PathBuf>,
+    jobs: Option<usize>,
+    with_dependencies: bool,
+}
+
+impl<'a> Workspace<'a> {
+    /// Add a Move package, oxidized with the settings of its `builder`.
+    ///
+    /// The `builder`'s [`emit_rerun_if_changed`](Builder::emit_rerun_if_changed),
+    /// [`out_dir`](Builder::out_dir) and [`jobs`](Builder::jobs) are ignored in favor of the
+    /// workspace's. The packages can be added in any order; they're oxidized in the order of
+    /// their dependencies, which are worked out from their `Move.toml`s and imports.
+    pub fn package(mut self, builder: Builder<'a>) -> Self {
+        self.packages.push(builder);
+        self
+    }
+
+    /// Also oxidize the dependencies of the packages that are found locally but aren't part of the
+    /// workspace, like `local` ones or `git` ones in a [package cache](Builder::package_cache).
+    ///
+    /// A dependency is left out if all of its named addresses are
+    /// [mapped explicitly](Builder::map_address) by the package depending on it. Otherwise, it's
+    /// oxidized with the same implicit imports, path to `moverox` and address and
+    /// [type mappings](Builder::map_type) as that package.
+    pub const fn with_dependencies(mut self) -> Self {
+        self.with_dependencies = true;
+        self
+    }
+
+    /// Enable or disable emitting
+    /// [`cargo:rerun-if-changed=PATH`](https://doc.rust-lang.org/cargo/reference/build-scripts.html#rerun-if-changed)
+    /// instructions for Cargo; see [`Builder::emit_rerun_if_changed`].
+    pub const fn emit_rerun_if_changed(mut self, enable: bool) -> Self {
+        self.emit_rerun_if_changed = enable;
+        self
+    }
+
+    /// Configures the output directory where generated Rust files will be written; see
+    /// [`Builder::out_dir`].
+    pub fn out_dir(mut self, path: impl Into<PathBuf>) -> Self {
+        self.out_dir = Some(path.into());
+        self
+    }
+
+    /// Number of threads to parse and oxidize the Move modules of all packages on; see
+    /// [`Builder::jobs`].
+    pub const fn jobs(mut self, threads: usize) -> Self {
+        self.jobs = Some(threads);
+        self
+    }
+
+    /// Oxidize the packages, writing the Rust code to `{out_dir}/{name}.rs`.
+    ///
+    /// The packages' `Move.toml`s fill in their settings like in [`Builder::build`]. Files are
+    /// only generated and written if anything changed, as for a single package.
+    pub fn build(mut self) -> Result<()> {
+        let out_dir = self.out_dir.clone().map_or_else(default_out_dir, Ok)?;
+
+        let move_files = self.prepare()?;
+
+        let settings = self.settings();
+        let all_files: Vec<_> = move_files.iter().flatten().cloned().collect();
+        write_outputs(&out_dir, self.name, &settings, &all_files, || {
+            self.generate_from(&move_files)
+        })
+    }
+
+    /// Oxidize the packages like [`build`](Self::build) does, but return the Rust code instead of
+    /// writing it to a file.
+    pub fn generate(mut self) -> Result<Generated> {
+        let move_files = self.prepare()?;
+
+        self.generate_from(&move_files)
+    }
+
+    /// Apply the packages' manifests, add their dependencies if asked to and collect their Move
+    /// files.
+    fn prepare(&mut self) -> Result<Vec<Vec<PathBuf>>> {
+        for builder in &mut self.packages {
+            builder.emit_rerun_if_changed = self.emit_rerun_if_changed;
+            builder.apply_manifest()?;
+        }
+        if self.with_dependencies {
+            self.add_dependencies()?;
+        }
+
+        let mut module_names: HashMap<String, &str> = HashMap::new();
+        for builder in &self.packages {
+            if let Some(other) = module_names.insert(builder.module_name(), &builder.name) {
+                return Err(format!(
+                    "packages `{other}` and `{}` would have the same Rust module",
+                    builder.name
+                )
+                .into());
+            }
+        }
+
+        self.packages
+            .iter()
+            .map(Builder::collect_move_files)
+            .collect()
+    }
+
+    /// Add the dependencies of the packages that aren't part of the workspace; see
+    /// [`with_dependencies`](Self::with_dependencies).
+    fn add_dependencies(&mut self) -> Result<()> {
+        let mut i = 0;
+        while i < self.packages.len() {
+            let builder = &self.packages[i];
+            let mut added = vec![];
+            for (path, manifest) in &builder.dependencies {
+                let is_member = self
+                    .packages
+                    .iter()
+                    .chain(&added)
+                    .any(|member: &Builder<'_>| {
+                        member.name == manifest.name
+                            || member
+                                .manifest
+                                .as_ref()
+                                .is_some_and(|m| m.name == manifest.name)
+                            || member.pkg_path.canonicalize().is_ok_and(|p| p == *path)
+                    });
+                let is_mapped = !manifest.addresses.is_empty()
+                    && manifest
+                        .addresses
+                        .keys()
+                        .all(|named| builder.address_map.contains_key(named));
+                if is_member || is_mapped {
+                    continue;
+                }
+                let mut dependency = builder.for_dependency(path, manifest);
+                dependency.apply_manifest()?;
+                added.push(dependency);
+            }
+            self.packages.extend(added);
+            i += 1;
+        }
+        Ok(())
+    }
+
+    fn generate_from(&self, move_files: &[Vec<PathBuf>]) -> Result<Generated> {
+        let packages: Vec<_> = self
+            .packages
+            .iter()
+            .zip(move_files)
+            .map(|(builder, files)| (builder, files.as_slice()))
+            .collect();
+        let mut order = vec![];
+        let generated = parallel::generate_rust(&packages, self.jobs, |infos| {
+            order = self.dependency_order(infos)?;
+            self.links(infos)
+        })?;
+
+        let mut generated: Vec<_> = generated.into_iter().map(Some).collect();
+        let mut file = syn::File {
+            shebang: None,
+            attrs: vec![],
+            items: vec![],
+        };
+        let mut module_files = vec![];
+        let mut dependencies = vec![];
+        for i in order {
+            let builder = &self.packages[i];
+            let (mut package_file, package_dependencies) =
+                generated[i].take().ok_or("package generated twice")?;
+            let module_name = builder.module_name();
+            if builder.file_per_module {
+                let dir = format!("{}/{module_name}", self.name);
+                module_files.extend(split_modules(&mut package_file, &dir)?);
+            }
+            let mut item_mod: syn::ItemMod = syn::parse_str(&format!(
+                "/// Oxidized `{}` Move package.\npub mod {module_name} {{}}",
+                builder.name
+            ))?;
+            if let Some((_, items)) = &mut item_mod.content {
+                *items = package_file.items;
+            }
+            file.items.push(item_mod.into());
+            dependencies.extend(package_dependencies);
+        }
+
+        Ok(Generated {
+            rust_code: prettyplease::unparse(&file),
+            module_files,
+            dependencies,
+        })
+    }
+
+    /// Description of every setting that affects the generated code, for the stamp.
+    fn settings(&self) -> String {
+        let mut settings = format!("workspace {}\n", self.name);
+        for builder in &self.packages {
+            settings.push_str(&builder.settings());
+            settings.push('\n');
+        }
+        settings
+    }
+
+    /// Indices of the packages such that every package comes after the ones it depends on.
+    ///
+    /// Packages that don't depend on each other keep the order they were added in.
+    fn dependency_order(&self, infos: &[PackageInfo]) -> Result<Vec<usize>> {
+        let pkg_paths: Vec<_> = self
+            .packages
+            .iter()
+            .map(|builder| builder.pkg_path.canonicalize().ok())
+            .collect();
+        let depends_on = |i: usize, j: usize| {
+            let builder = &self.packages[i];
+            let other = &self.packages[j];
+            let imports = infos[i]
+                .imported_addresses
+                .iter()
+                .any(|named| infos[j].named_addresses.contains(named));
+            // Either by name or, for the ones found locally, by directory
+            let declared = builder.manifest.as_ref().is_some_and(|manifest| {
+                manifest.dependencies.keys().any(|name| {
+                    *name == other.name || other.manifest.as_ref().is_some_and(|m| *name == m.name)
+                })
+            }) || builder
+                .dependencies
+                .iter()
+                .any(|(path, _)| pkg_paths[j].as_ref() == Some(path));
+            imports || declared
+        };
+
+        let n = self.packages.len();
+        let mut order = Vec::with_capacity(n);
+        let mut done = vec![false; n];
+        while order.len() < n {
+            let next = (0..n)
+                .find(|&i| !done[i] && (0..n).all(|j| i == j || done[j] || !depends_on(i, j)));
+            let Some(i) = next else {
+                let cycle: Vec<_> = (0..n)
+                    .filter(|&i| !done[i])
+                    .map(|i| format!("`{}`", self.packages[i].name))
+                    .collect();
+                return Err(format!("packages depend on each other: {}", cycle.join(", ")).into());
+            };
+            done[i] = true;
+            order.push(i);
+        }
+        Ok(order)
+    }
+
+    /// Paths from each package's modules to the items of the others.
+    fn links(&self, infos: &[PackageInfo]) -> Result<Vec<Links>> {
+        let mut owners: HashMap<&str, usize> = HashMap::new();
+        for (i, info) in infos.iter().enumerate() {
+            for named in &info.named_addresses {
+                if let Some(j) = owners.insert(named, i) {
+                    return Err(format!(
+                        "named address `{named}` is in both packages `{}` and `{}`",
+                        self.packages[j].name, self.packages[i].name
+                    )
+                    .into());
+                }
+            }
+        }
+
+        // Modules of a package are two levels down from the file: `{package}::{module}`
+        let paths: Vec<_> = self
+            .packages
+            .iter()
+            .map(|builder| format!("super::super::{}", builder.module_name()))
+            .collect();
+        Ok((0..self.packages.len())
+            .map(|i| {
+                let mut links = Links::default();
+                for (j, other) in self.packages.iter().enumerate() {
+                    if i == j {
+                        continue;
+                    }
+                    // Including the ones declared in `Move.toml` that no module has, in case
+                    // there are no sources for them
+                    let declared = other
+                        .manifest
+                        .iter()
+                        .flat_map(|manifest| manifest.addresses.keys());
+                    links.named.extend(
+                        declared
+                            .chain(&infos[j].named_addresses)
+                            .map(|named| (named.clone(), paths[j].clone())),
+                    );
+                    links
+                        .numeric
+                        .extend(other.package_bytes().map(|bytes| (bytes, paths[j].clone())));
+                }
+                links
+            })
+            .collect())
+    }
+}
+
+impl<'a> Builder<'a> {
+    /// Name of the package's Rust module in a [`Workspace`].
+    fn module_name(&self) -> String {
+        manifest::module_name(&self.name)
+    }
+
+    /// Builder for a dependency of this package, found at `pkg_path`, with the settings that
+    /// aren't specific to this package.
+    fn for_dependency(&self, pkg_path: &Path, manifest: &Manifest) -> Self {
+        let mut builder = crate::move_package(pkg_path, "");
+        builder.name = Cow::Owned(manifest.name.clone());
+        builder.manifest = Some(manifest.clone());
+        builder.with_implicit_iota_imports = self.with_implicit_iota_imports;
+        builder.with_implicit_sui_imports = self.with_implicit_sui_imports;
+        builder.emit_rerun_if_changed = self.emit_rerun_if_changed;
+        builder.moverox_path.clone_from(&self.moverox_path);
+        builder.address_map.clone_from(&self.address_map);
+        builder
+            .numeric_address_map
+            .clone_from(&self.numeric_address_map);
+        builder.type_map.clone_from(&self.type_map);
+        builder.dependency_sources = self.dependency_sources.clone();
+        builder
+    }
+}
@@ -21,7 +21,10 @@ fn sui_stdlib() -> TestResult<(impl AsRef<Path>, TempDir)> {
     let tempdir = tempfile::tempdir()?;
 
     let main = tempdir.path().join("sui_stdlib.rs");
+    // NOTE: trybuild compiles with `--cfg trybuild`, which gates the test-only items
     let contents = indoc! {"
+        #![allow(unexpected_cfgs)]
+
         mod move_stdlib;
         mod sui_framework;
 
@@ -32,6 +35,10 @@ fn sui_stdlib() -> TestResult<(impl AsRef<Path>, TempDir)> {
     let pkg_path = move_dir()?.join("move-stdlib");
     moverox_build::move_package(pkg_path, "move_stdlib")
         .published_at("0x1")
+        .with_call_descriptors()
+        .with_return_decoders()
+        .with_events()
+        .with_test_items_cfg("trybuild")
         .out_dir(tempdir.path())
         .build()?;
 
@@ -39,6 +46,11 @@ fn sui_stdlib() -> TestResult<(impl AsRef<Path>, TempDir)> {
     moverox_build::move_package(pkg_path, "sui_framework")
         .published_at("0x2")
         .with_implicit_sui_imports()
+        .with_call_descriptors()
+        .with_return_decoders()
+        .with_events()
+        .with_test_items_cfg("trybuild")
+        .with_file_per_module()
         .map_address("std", "crate::move_stdlib")
         .out_dir(tempdir.path())
         .build()?;
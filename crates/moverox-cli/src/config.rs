@@ -0,0 +1,225 @@
+//! Reading `moverox.toml` config files.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use moverox_build::Builder;
+use serde::Deserialize;
+
+use crate::Result;
+
+/// The Move packages to oxidize and where to write their Rust code.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Config {
+    #[serde(rename = "package", default)]
+    pub(crate) packages: Vec<Package>,
+}
+
+/// A `[[package]]` entry, with the [`Builder`] settings for it.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub(crate) struct Package {
+    /// Path to the Move package, relative to the config file.
+    pub(crate) path: PathBuf,
+    /// Path to the Rust file to write, relative to the config file.
+    pub(crate) output: PathBuf,
+    published_at: Option<String>,
+    environment: Option<String>,
+    #[serde(default)]
+    published_constants: bool,
+    /// Datatypes, as `module::Datatype`, mapped to the addresses that first defined them.
+    #[serde(default)]
+    type_origins: BTreeMap<String, String>,
+    type_origins_file: Option<PathBuf>,
+    implicit_imports: Option<ImplicitImports>,
+    moverox_path: Option<String>,
+    #[serde(default)]
+    call_descriptors: bool,
+    #[serde(default)]
+    return_decoders: bool,
+    #[serde(default)]
+    events: bool,
+    #[serde(default)]
+    file_per_module: bool,
+    #[serde(default)]
+    test_items: bool,
+    test_items_feature: Option<String>,
+    /// Named or numerical addresses mapped to Rust paths.
+    #[serde(default)]
+    addresses: BTreeMap<String, String>,
+    #[serde(default)]
+    allow_modules: Vec<String>,
+    #[serde(default)]
+    deny_modules: Vec<String>,
+    #[serde(default)]
+    allow_types: Vec<String>,
+    #[serde(default)]
+    deny_types: Vec<String>,
+    /// Relative to the config file, like the other paths.
+    package_cache: Option<PathBuf>,
+    vendor_dir: Option<PathBuf>,
+    #[serde(default)]
+    datatype_attrs: Vec<DatatypeAttrs>,
+    /// Move types mapped to Rust types.
+    #[serde(default)]
+    types: BTreeMap<String, RustType>,
+    /// Paths of Move datatypes, fields and enum variants mapped to their Rust names.
+    #[serde(default)]
+    renames: BTreeMap<String, String>,
+    /// Paths of type parameters mapped to the Move types they default to.
+    #[serde(default)]
+    type_defaults: BTreeMap<String, String>,
+}
+
+/// A Rust type for a Move type, with a serde adapter if it isn't BCS-compatible with it.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum RustType {
+    Path(String),
+    #[serde(rename_all = "kebab-case")]
+    WithAdapter {
+        rust: String,
+        serde_with: String,
+    },
+}
+
+/// A `[[package.datatype-attrs]]` entry, changing the derives and attributes of some datatypes.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct DatatypeAttrs {
+    /// `*`, a module or a `module::Datatype`.
+    #[serde(default = "all_datatypes")]
+    scope: String,
+    #[serde(default)]
+    remove_derives: Vec<String>,
+    #[serde(default)]
+    derives: Vec<String>,
+    #[serde(default)]
+    attributes: Vec<String>,
+}
+
+fn all_datatypes() -> String {
+    "*".to_owned()
+}
+
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum ImplicitImports {
+    Sui,
+    Iota,
+}
+
+impl Config {
+    pub(crate) fn read(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|err| format!("reading {}: {err}", path.display()))?;
+        Self::parse(&contents).map_err(|err| format!("{}: {err}", path.display()).into())
+    }
+
+    pub(crate) fn parse(toml: &str) -> Result<Self> {
+        Ok(toml::from_str(toml)?)
+    }
+}
+
+impl Package {
+    /// Builder for the package, with paths relative to `root`.
+    ///
+    /// It's named after the output file, so that module files go in a directory of the same name.
+    pub(crate) fn builder(&self, root: &Path) -> Builder<'_> {
+        let name = self
+            .output
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or_default();
+        let mut builder =
+            moverox_build::move_package(root.join(&self.path), name).emit_rerun_if_changed(false);
+        if let Some(address) = &self.published_at {
+            builder = builder.published_at(address);
+        }
+        if let Some(environment) = &self.environment {
+            builder = builder.environment(environment);
+        }
+        if self.published_constants {
+            builder = builder.with_published_constants();
+        }
+        for (datatype, address) in &self.type_origins {
+            builder = builder.type_origin(datatype, address);
+        }
+        if let Some(path) = &self.type_origins_file {
+            builder = builder.type_origins_file(root.join(path));
+        }
+        builder = match self.implicit_imports {
+            Some(ImplicitImports::Sui) => builder.with_implicit_sui_imports(),
+            Some(ImplicitImports::Iota) => builder.with_implicit_iota_imports(),
+            None => builder,
+        };
+        if let Some(path) = &self.moverox_path {
+            builder = builder.moverox_path(path);
+        }
+        if self.call_descriptors {
+            builder = builder.with_call_descriptors();
+        }
+        if self.return_decoders {
+            builder = builder.with_return_decoders();
+        }
+        if self.events {
+            builder = builder.with_events();
+        }
+        if self.file_per_module {
+            builder = builder.with_file_per_module();
+        }
+        if let Some(feature) = &self.test_items_feature {
+            builder = builder.with_test_items_feature(feature);
+        } else if self.test_items {
+            builder = builder.with_test_items();
+        }
+        for (address, path) in &self.addresses {
+            builder = builder.map_address(address, path);
+        }
+        for pattern in &self.allow_modules {
+            builder = builder.allow_modules(pattern);
+        }
+        for pattern in &self.deny_modules {
+            builder = builder.deny_modules(pattern);
+        }
+        for pattern in &self.allow_types {
+            builder = builder.allow_types(pattern);
+        }
+        for pattern in &self.deny_types {
+            builder = builder.deny_types(pattern);
+        }
+        if let Some(path) = &self.package_cache {
+            builder = builder.package_cache(root.join(path));
+        }
+        if let Some(path) = &self.vendor_dir {
+            builder = builder.vendor_dir(root.join(path));
+        }
+        for (move_type, rust_type) in &self.types {
+            builder = match rust_type {
+                RustType::Path(path) => builder.map_type(move_type, path),
+                RustType::WithAdapter { rust, serde_with } => {
+                    builder.map_type_with(move_type, rust, serde_with)
+                }
+            };
+        }
+        for (path, rust_name) in &self.renames {
+            builder = builder.rename(path, rust_name);
+        }
+        for (path, move_type) in &self.type_defaults {
+            builder = builder.type_default(path, move_type);
+        }
+        for rule in &self.datatype_attrs {
+            if !rule.remove_derives.is_empty() {
+                builder = builder.remove_derives(&rule.scope, &rule.remove_derives.join(","));
+            }
+            if !rule.derives.is_empty() {
+                builder = builder.add_derives(&rule.scope, &rule.derives.join(","));
+            }
+            for attribute in &rule.attributes {
+                builder = builder.add_attribute(&rule.scope, attribute);
+            }
+        }
+        builder
+    }
+}
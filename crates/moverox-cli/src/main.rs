@@ -0,0 +1,118 @@
+//! Generate Rust code for Move packages into the source tree.
+//!
+//! Runs the same pipeline as `moverox-build`, but from a `moverox.toml` config file and writing
+//! pretty-printed Rust to checked-in files, so that the generated code shows up in code review
+//! and is reachable by "go to definition". With `--check`, nothing is written and the command
+//! exits with an error if any of the files is out of date with the Move sources.
+//!
+//! ```toml
+//! [[package]]
+//! path = "move/deepbook"
+//! output = "src/deepbook.rs"
+//! implicit-imports = "sui"
+//! call-descriptors = true
+//! allow-types = ["deepbook::pool::*"]
+//!
+//! [package.addresses]
+//! std = "::moverox_sui::move_stdlib"
+//! sui = "::moverox_sui::sui"
+//! ```
+//!
+//! Paths are relative to the config file. Besides `path` and `output`, each `[[package]]` takes
+//! the settings of `moverox_build::Builder`: `published-at`, `implicit-imports` (`"sui"` or
+//! `"iota"`), `moverox-path`, `call-descriptors`, `return-decoders`, `events`,
+//! `file-per-module`, `test-items`, `test-items-feature`, `addresses`, `allow-modules`,
+//! `deny-modules`, `allow-types`, `deny-types`, `package-cache`, `vendor-dir`, `environment`,
+//! `published-constants`, `type-origins` (datatypes mapped to addresses), `type-origins-file`,
+//! `datatype-attrs` (a list of tables with an optional `scope` and `derives`, `remove-derives`
+//! and `attributes` lists), `types` (Move types mapped to Rust paths or to tables with the
+//! `rust` path and a `serde-with` adapter), `renames` (paths of Move datatypes, fields and enum
+//! variants mapped to Rust names) and `type-defaults` (paths of type parameters, e.g.,
+//! `pool::Pool::T`, mapped to Move types).
+//! With `file-per-module`, the module files are written to a directory named after the output
+//! file, next to it.
+
+use std::error::Error as StdError;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::Parser;
+
+use self::config::{Config, Package};
+
+mod config;
+#[cfg(test)]
+mod tests;
+
+type Result<T, E = Box<dyn StdError + Send + Sync + 'static>> = ::std::result::Result<T, E>;
+
+#[derive(Parser, Debug)]
+#[command(version, about)]
+struct Cli {
+    /// Path to the config file.
+    #[arg(long, default_value = "moverox.toml")]
+    config: PathBuf,
+    /// Don't write anything; exit with an error if any output file is out of date.
+    #[arg(long)]
+    check: bool,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(&cli) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+/// Generate the code of every package in the config, writing the files that changed.
+///
+/// Returns whether all files were up to date, which is always the case unless checking.
+fn run(cli: &Cli) -> Result<bool> {
+    let config = Config::read(&cli.config)?;
+    let root = match cli.config.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let mut up_to_date = true;
+    for package in &config.packages {
+        let files =
+            generate(package, root).map_err(|err| format!("{}: {err}", package.path.display()))?;
+        for (path, contents) in files {
+            if std::fs::read_to_string(&path).is_ok_and(|current| current == contents) {
+                continue;
+            }
+            if cli.check {
+                eprintln!("{} is out of date", path.display());
+                up_to_date = false;
+                continue;
+            }
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&path, contents)
+                .map_err(|err| format!("writing {}: {err}", path.display()))?;
+            eprintln!("Wrote {}", path.display());
+        }
+    }
+    Ok(up_to_date)
+}
+
+/// Paths and contents of the package's output file and, if any, its module files.
+fn generate(package: &Package, root: &Path) -> Result<Vec<(PathBuf, String)>> {
+    let generated = package.builder(root).generate()?;
+    let output = root.join(&package.output);
+    let output_dir = output.parent().unwrap_or(root).to_owned();
+    let contents = format!("{}{}", generated.header(), generated.rust_code);
+    let module_files = generated
+        .module_files
+        .into_iter()
+        .map(|(path, contents)| (output_dir.join(path), contents));
+    Ok(std::iter::once((output, contents))
+        .chain(module_files)
+        .collect())
+}
@@ -0,0 +1,109 @@
+use std::path::Path;
+
+use testresult::TestResult;
+
+use crate::{Cli, Config, run};
+
+#[test]
+fn parse_config() -> TestResult {
+    let config = Config::parse(indoc::indoc! {r#"
+        [[package]]
+        path = "move/sui-framework"
+        output = "src/sui.rs"
+        published-at = "0x2"
+        implicit-imports = "sui"
+        events = true
+        allow-modules = ["coin", "regex:balance|object"]
+
+        [package.addresses]
+        std = "::moverox_sui::move_stdlib"
+
+        [package.types]
+        "0x1::string::String" = "::std::string::String"
+        "0x2::vec_map::VecMap" = { rust = "::std::collections::BTreeMap", serde-with = "crate::vec_map" }
+
+        [package.renames]
+        "dynamic_field::Field" = "DynField"
+
+        [package.type-defaults]
+        "coin::TreasuryCap::T" = "sui::SUI"
+
+        [[package]]
+        path = "move/enums"
+        output = "src/enums.rs"
+
+        [[package.datatype-attrs]]
+        derives = ["PartialOrd", "Ord"]
+    "#})?;
+    assert_eq!(config.packages.len(), 2);
+    assert_eq!(config.packages[0].output, Path::new("src/sui.rs"));
+    assert_eq!(config.packages[1].path, Path::new("move/enums"));
+
+    let err = Config::parse(indoc::indoc! {r#"
+        [[package]]
+        path = "move/enums"
+        output = "src/enums.rs"
+        call-descriptor = true
+    "#})
+    .expect_err("Misspelled setting");
+    assert!(err.to_string().contains("unknown field `call-descriptor`"));
+    Ok(())
+}
+
+#[test]
+fn write_then_check() -> TestResult {
+    let tempdir = tempfile::tempdir()?;
+    let enums = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../move/enums");
+    let config = tempdir.path().join("moverox.toml");
+    std::fs::write(
+        &config,
+        format!(
+            "[[package]]\npath = {:?}\noutput = \"src/enums.rs\"\n",
+            enums.display()
+        ),
+    )?;
+    let output = tempdir.path().join("src/enums.rs");
+
+    let check = Cli {
+        config: config.clone(),
+        check: true,
+    };
+    let write = Cli {
+        config,
+        check: false,
+    };
+    assert!(!run(&check)?, "Missing output");
+    assert!(!output.exists());
+
+    assert!(run(&write)?);
+    let contents = std::fs::read_to_string(&output)?;
+    assert!(contents.starts_with("// This file is @generated by moverox-build.\n"));
+    assert!(contents.contains("\npub mod enums {\n"), "Pretty-printed");
+    assert!(run(&check)?);
+
+    std::fs::write(
+        &output,
+        contents.replace("pub mod enums", "pub mod renamed"),
+    )?;
+    assert!(!run(&check)?, "Edited output");
+    assert!(run(&write)?);
+    assert!(run(&check)?);
+
+    // Module files are checked as well
+    std::fs::write(
+        tempdir.path().join("moverox.toml"),
+        format!(
+            "[[package]]\npath = {:?}\noutput = \"src/enums.rs\"\nfile-per-module = true\n",
+            enums.display()
+        ),
+    )?;
+    assert!(!run(&check)?, "Split modules");
+    assert!(run(&write)?);
+    let module = tempdir.path().join("src/enums/other.rs");
+    assert!(std::fs::read_to_string(&output)?.contains("include!(\"enums/other.rs\")"));
+    std::fs::write(&module, "")?;
+    assert!(!run(&check)?, "Edited module file");
+    assert!(run(&write)?);
+    assert!(run(&check)?);
+    Ok(())
+}
@@ -0,0 +1,278 @@
+use move_syn::{ConstValue, ItemKind, ItemPath, Module};
+use proc_macro2::{Ident, Literal, TokenStream};
+use quote::quote;
+use unsynn::LiteralString;
+
+use crate::{ModuleSummary, Options, Result};
+
+/// An abort code declared as `const EName: u64 = <code>;`.
+struct AbortCode<'a> {
+    ident: &'a Ident,
+    code: u64,
+    docs: Option<String>,
+}
+
+/// Whether `ident` follows the naming convention for abort codes: `E` followed by an uppercase
+/// letter or `_`, e.g., `EInvalidAmount` or `E_INVALID_AMOUNT`.
+fn is_abort_code_name(ident: &Ident) -> bool {
+    let name = ident.to_string();
+    let mut chars = name.chars();
+    chars.next() == Some('E')
+        && chars
+            .next()
+            .is_some_and(|c| c.is_ascii_uppercase() || c == '_')
+}
+
+fn abort_codes(module: &Module) -> Result<Vec<AbortCode<'_>>> {
+    let mut codes = vec![];
+    for item in module.items() {
+        let ItemKind::Const(c) = &item.kind else {
+            continue;
+        };
+        if item.is_test_gated() {
+            continue;
+        }
+        let ident = c.ident();
+        let is_u64 = matches!(&c.type_().path, ItemPath::Ident(ty) if ty == "u64");
+        if !is_abort_code_name(ident) || !is_u64 {
+            continue;
+        }
+        let Some(ConstValue::Lit(lit)) = c.value() else {
+            continue;
+        };
+        let code = crate::move_const::u64_value(&lit.to_string())
+            .map_err(|err| format!("const {ident}: {err}"))?;
+        let docs: Vec<_> = item
+            .attrs
+            .iter()
+            .filter_map(|attr| attr.doc())
+            .map(|doc| doc.trim().to_owned())
+            .filter(|doc| !doc.is_empty())
+            .collect();
+        codes.push(AbortCode {
+            ident,
+            code,
+            docs: (!docs.is_empty()).then(|| docs.join(" ")),
+        });
+    }
+    Ok(codes)
+}
+
+/// Whether the module declares any abort codes, i.e., if [`module_to_rust`] generates an `Error`
+/// enum for it.
+pub(super) fn has_abort_codes(module: &Module) -> Result<bool> {
+    Ok(!abort_codes(module)?.is_empty())
+}
+
+/// `Error` enum with the abort codes declared in the module, if any.
+///
+/// It's an error for a datatype of the module to be generated as `Error` too, unless it's renamed;
+/// see [`Options::renames`].
+pub(super) fn module_to_rust(module: &Module, options: &Options) -> Result<TokenStream> {
+    let codes = abort_codes(module)?;
+    if codes.is_empty() {
+        return Ok(TokenStream::new());
+    }
+    let clashing = module.items().find_map(|item| {
+        let ident = match &item.kind {
+            ItemKind::Struct(s) => &s.ident,
+            ItemKind::Enum(e) => &e.ident,
+            _ => return None,
+        };
+        let rust_ident = options
+            .renames
+            .get(&format!("{}::{ident}", module.ident))
+            .unwrap_or(ident);
+        (rust_ident == "Error").then_some(ident)
+    });
+    if let Some(ident) = clashing {
+        return Err(format!(
+            "datatype {ident}: clashes with the `Error` enum of the module's abort codes; rename it \
+             with `#[ext(moverox(rename = \"...\"))]`"
+        )
+        .into());
+    }
+    let enum_doc = format!(
+        " Abort codes of the `{}` module, from its `E*` constants.",
+        module.ident
+    );
+
+    let variants: Vec<_> = codes.iter().map(|c| c.ident).collect();
+    let variant_defs = codes.iter().map(|AbortCode { ident, docs, .. }| {
+        let docs = docs.iter().map(|doc| format!(" {doc}"));
+        quote! {
+            #(#[cfg_attr(not(doctest), doc = #docs)])*
+            #ident
+        }
+    });
+    let values = codes.iter().map(|c| Literal::u64_unsuffixed(c.code));
+    let names = codes.iter().map(|c| c.ident.to_string());
+    let messages = codes
+        .iter()
+        .map(|c| c.docs.clone().unwrap_or_else(|| c.ident.to_string()));
+
+    // The first constant wins if several share the same code
+    let mut seen = std::collections::HashSet::new();
+    let (try_from_codes, try_from_variants): (Vec<_>, Vec<_>) = codes
+        .iter()
+        .filter(|c| seen.insert(c.code))
+        .map(|c| (Literal::u64_unsuffixed(c.code), c.ident))
+        .unzip();
+
+    Ok(quote! {
+        #[doc = #enum_doc]
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        #[allow(non_camel_case_types)]
+        pub enum Error {
+            #(#variant_defs,)*
+        }
+
+        impl Error {
+            /// The abort code.
+            pub const fn code(self) -> u64 {
+                match self {
+                    #(Self::#variants => #values),*
+                }
+            }
+
+            /// Name of the constant declaring the abort code.
+            pub const fn name(self) -> &'static str {
+                match self {
+                    #(Self::#variants => #names),*
+                }
+            }
+
+            /// Documentation of the constant declaring the abort code, or its name if undocumented.
+            pub const fn message(self) -> &'static str {
+                match self {
+                    #(Self::#variants => #messages),*
+                }
+            }
+        }
+
+        impl ::std::convert::TryFrom<u64> for Error {
+            type Error = u64;
+
+            fn try_from(code: u64) -> ::std::result::Result<Self, u64> {
+                match code {
+                    #(#try_from_codes => Ok(Self::#try_from_variants),)*
+                    other => Err(other),
+                }
+            }
+        }
+
+        impl ::std::fmt::Display for Error {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                f.write_str(self.message())
+            }
+        }
+
+        impl ::std::error::Error for Error {}
+    })
+}
+
+/// Package-level `AbortError` enum gathering the `Error` enums of each module, with a lookup by
+/// `(address, module, code)`.
+///
+/// It's an error for a module to be called `AbortError` too, since they're siblings.
+pub(super) fn package_to_rust<'a>(
+    modules: impl IntoIterator<Item = &'a ModuleSummary>,
+    thecrate: &TokenStream,
+    package: Option<&LiteralString>,
+) -> Result<TokenStream> {
+    let modules: Vec<_> = modules.into_iter().collect();
+    let module_idents: Vec<_> = modules
+        .iter()
+        .filter(|module| !module.test_gated && module.abort_codes)
+        .map(|module| module.ident())
+        .collect();
+    if module_idents.is_empty() {
+        return Ok(TokenStream::new());
+    }
+    if modules.iter().any(|module| module.name == "AbortError") {
+        return Err("module AbortError: clashes with the package's `AbortError` enum".into());
+    }
+    let module_names: Vec<_> = module_idents
+        .iter()
+        .map(|ident| ident.to_string())
+        .collect();
+
+    let check_address = package.map(|package| {
+        let package = Literal::byte_string(package.as_str().as_bytes());
+        quote! {
+            if *address != #thecrate::types::const_address(#package) {
+                return None;
+            }
+        }
+    });
+    let address_doc = if package.is_some() {
+        " Returns `None` if `address` isn't this package's."
+    } else {
+        " The package address wasn't known when generating this code, so `address` is ignored."
+    };
+    let allow_unused = package
+        .is_none()
+        .then(|| quote!(#[allow(unused_variables)]));
+    Ok(quote! {
+        /// Abort errors of this package's modules.
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        #[allow(non_camel_case_types)]
+        pub enum AbortError {
+            #(#module_idents(#module_idents::Error)),*
+        }
+
+        impl AbortError {
+            /// Decode a Move abort with `code` in `module` at `address`.
+            ///
+            #[doc = #address_doc]
+            #allow_unused
+            pub fn from_abort(
+                address: &#thecrate::types::Address,
+                module: &str,
+                code: u64,
+            ) -> Option<Self> {
+                #check_address
+                match module {
+                    #(#module_names => #module_idents::Error::try_from(code).ok().map(Self::#module_idents),)*
+                    _ => None,
+                }
+            }
+
+            /// Name of the module that aborted.
+            pub const fn module(self) -> &'static str {
+                match self {
+                    #(Self::#module_idents(_) => #module_names),*
+                }
+            }
+
+            /// The abort code.
+            pub const fn code(self) -> u64 {
+                match self {
+                    #(Self::#module_idents(error) => error.code()),*
+                }
+            }
+
+            /// Name of the constant declaring the abort code.
+            pub const fn name(self) -> &'static str {
+                match self {
+                    #(Self::#module_idents(error) => error.name()),*
+                }
+            }
+
+            /// Documentation of the constant declaring the abort code, or its name if undocumented.
+            pub const fn message(self) -> &'static str {
+                match self {
+                    #(Self::#module_idents(error) => error.message()),*
+                }
+            }
+        }
+
+        impl ::std::fmt::Display for AbortError {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                write!(f, "{}::{}: {}", self.module(), self.name(), self.message())
+            }
+        }
+
+        impl ::std::error::Error for AbortError {}
+    })
+}
@@ -0,0 +1,66 @@
+use std::collections::HashMap;
+
+use move_syn::Address;
+use proc_macro2::{Ident, TokenStream};
+
+/// Mapping of Move addresses to Rust paths of oxidized Move packages.
+///
+/// Used to map Move datatype paths to Rust-equivalents. Named addresses (`sui::coin::Coin`) are
+/// looked up by name and numerical ones (`0x2::coin::Coin`) by their value, so `0x2` and
+/// `0x0000..02` map to the same path.
+#[derive(Clone, Debug, Default)]
+pub struct AddressMap {
+    named: HashMap<Ident, TokenStream>,
+    numeric: HashMap<[u8; 32], TokenStream>,
+}
+
+impl AddressMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Map a named address to `rust_path`, returning the previous path, if any.
+    pub fn insert_named(&mut self, named: Ident, rust_path: TokenStream) -> Option<TokenStream> {
+        self.named.insert(named, rust_path)
+    }
+
+    /// Map a numerical address to `rust_path`, returning the previous path, if any.
+    pub fn insert_numeric(
+        &mut self,
+        address: [u8; 32],
+        rust_path: TokenStream,
+    ) -> Option<TokenStream> {
+        self.numeric.insert(address, rust_path)
+    }
+
+    /// Rust path for a named address.
+    pub fn get_named(&self, named: &Ident) -> Option<&TokenStream> {
+        self.named.get(named)
+    }
+
+    /// Rust path for a numerical address.
+    pub fn get_numeric(&self, address: &[u8; 32]) -> Option<&TokenStream> {
+        self.numeric.get(address)
+    }
+
+    /// Rust path for the leading address of a Move path.
+    pub fn get(&self, address: &Address) -> Option<&TokenStream> {
+        match address {
+            Address::Named(ident) => self.get_named(ident),
+            Address::Numeric(_) => self.get_numeric(&address.to_bytes()?),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.named.is_empty() && self.numeric.is_empty()
+    }
+}
+
+impl FromIterator<(Ident, TokenStream)> for AddressMap {
+    fn from_iter<T: IntoIterator<Item = (Ident, TokenStream)>>(iter: T) -> Self {
+        Self {
+            named: iter.into_iter().collect(),
+            numeric: HashMap::new(),
+        }
+    }
+}
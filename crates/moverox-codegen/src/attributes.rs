@@ -1,8 +1,6 @@
-use std::collections::HashSet;
-
 use move_syn::Attributes;
 use quote::quote;
-use unsynn::{IParse as _, Ident, ToTokens as _, TokenStream};
+use unsynn::{IParse as _, Ident, ToTokens as _, TokenStream, TokenTree};
 
 use crate::Result;
 
@@ -15,7 +13,13 @@ mod grammar {
 
         unsynn! {
             pub(super) keyword Moverox = "moverox";
-            pub(super) keyword Otw = "OTW";
+            pub(super) keyword Event = "event";
+            pub(super) keyword Derive = "derive";
+            pub(super) keyword Rename = "rename";
+            pub(super) keyword With = "with";
+            pub(super) keyword SkipDebug = "skip_debug";
+            pub(super) keyword RustType = "rust_type";
+            pub(super) keyword Doc = "doc";
             // NOTE: we cannot use `type` here since the Move parser will complain because it's a
             // reserved keyword
             pub(super) keyword Type = "type_";
@@ -28,9 +32,19 @@ mod grammar {
         /// # Example
         ///
         /// ```move
-        /// #[ext(moverox(type(T = OTW)))]
+        /// #[ext(moverox(type_(T = OTW), derive(PartialOrd, Ord)))]
         /// public struct BalanceUpdated<phantom T> {}
         /// ```
+        ///
+        /// On fields:
+        ///
+        /// ```move
+        /// public struct Vault has key {
+        ///     id: UID,
+        ///     #[ext(moverox(skip_debug, with = "crate::hex", rust_type = "Vec<u8>"))]
+        ///     secret: vector<u8>,
+        /// }
+        /// ```
         pub(crate) struct Annotation {
             kw: kw::Moverox,
             contents: ParenthesisGroupContaining<CommaDelimitedVec<Setting>>
@@ -38,8 +52,72 @@ mod grammar {
 
         /// The different accepted attributes inside `moverox(...)`
         pub(super) enum Setting {
-            /// Currrently only type defaults
-            Type(Type)
+            /// Type defaults
+            Type(Type),
+            /// Marks a struct as an event, see [`is_event`](super::is_event)
+            Event(Event),
+            /// Additional derives
+            Derive(Derive),
+            /// Rust name
+            Rename(Rename),
+            /// Serde adapter of a field
+            With(With),
+            /// Leaves a field out of the `Debug` output
+            SkipDebug(SkipDebug),
+            /// Rust type of a field
+            RustType(RustType),
+            /// Docs of a field, instead of the Move ones
+            Doc(Doc),
+        }
+
+        /// Custom attribute to mark a struct as an event.
+        pub(super) struct Event {
+            kw: kw::Event,
+        }
+
+        /// Custom attribute to add derives to a datatype.
+        ///
+        /// Move attributes can't contain paths, so only derives in scope of the generated module,
+        /// like `PartialOrd` or `Ord`, can be added this way.
+        pub(super) struct Derive {
+            kw: kw::Derive,
+            contents: ParenthesisGroupContaining<CommaDelimitedVec<Ident>>,
+        }
+
+        /// Custom attribute to rename the generated Rust datatype, field or enum variant.
+        pub(super) struct Rename {
+            kw: kw::Rename,
+            assign: Assign,
+            name: LiteralString,
+        }
+
+        /// Custom attribute to (de)serialize a field with the `serialize` and `deserialize`
+        /// functions of a module, as in `#[serde(with = "...")]`.
+        pub(super) struct With {
+            kw: kw::With,
+            assign: Assign,
+            path: LiteralString,
+        }
+
+        /// Custom attribute to leave a field out of the `Debug` output of its datatype, e.g., if
+        /// it's a secret.
+        pub(super) struct SkipDebug {
+            kw: kw::SkipDebug,
+        }
+
+        /// Custom attribute to set the Rust type of a field, which has to be BCS-compatible with
+        /// the Move one, unless there's also a `with` adapter.
+        pub(super) struct RustType {
+            kw: kw::RustType,
+            assign: Assign,
+            path: LiteralString,
+        }
+
+        /// Custom attribute to replace the docs of a field.
+        pub(super) struct Doc {
+            kw: kw::Doc,
+            assign: Assign,
+            text: LiteralString,
         }
 
         /// Custom attribute to set defaults for type parameters of a datatype.
@@ -53,8 +131,8 @@ mod grammar {
             /// Identifier of the annotated datatype's type parameter
             ident: Ident,
             assign: Assign,
-            /// For now, only defaults like `{T} = OTW` are supported
-            default: kw::Otw,
+            /// `OTW` or a Move type, e.g., `0x2::sui::SUI` or `SUI` if imported
+            default: move_syn::Type,
         }
     }
 
@@ -68,41 +146,249 @@ mod grammar {
     }
 
     impl Setting {
-        pub(super) fn otw_types(&self) -> impl Iterator<Item = &Ident> + '_ {
-            let Self::Type(ty) = self;
-            ty.contents
-                .content
+        pub(super) fn type_defaults(&self) -> impl Iterator<Item = (&Ident, &move_syn::Type)> + '_ {
+            let types = match self {
+                Self::Type(ty) => ty.contents.content.as_slice(),
+                _ => &[],
+            };
+            types
                 .iter()
-                .map(|delimited| &delimited.value.ident)
+                .map(|delimited| (&delimited.value.ident, &delimited.value.default))
+        }
+
+        pub(super) fn derives(&self) -> impl Iterator<Item = &Ident> + '_ {
+            let derives = match self {
+                Self::Derive(derive) => derive.contents.content.as_slice(),
+                _ => &[],
+            };
+            derives.iter().map(|delimited| &delimited.value)
+        }
+
+        /// Name of the setting, as written in the attribute.
+        pub(super) const fn name(&self) -> &'static str {
+            match self {
+                Self::Type(_) => "type_",
+                Self::Event(_) => "event",
+                Self::Derive(_) => "derive",
+                Self::Rename(_) => "rename",
+                Self::With(_) => "with",
+                Self::SkipDebug(_) => "skip_debug",
+                Self::RustType(_) => "rust_type",
+                Self::Doc(_) => "doc",
+            }
+        }
+
+        /// The string value of a `name = "..."` setting.
+        pub(super) fn value(&self) -> Option<&str> {
+            match self {
+                Self::Rename(Rename { name: value, .. })
+                | Self::With(With { path: value, .. })
+                | Self::RustType(RustType { path: value, .. })
+                | Self::Doc(Doc { text: value, .. }) => Some(value.as_str()),
+                Self::Type(_) | Self::Event(_) | Self::Derive(_) | Self::SkipDebug(_) => None,
+            }
         }
+
+        pub(super) const fn is_event(&self) -> bool {
+            matches!(self, Self::Event(_))
+        }
+    }
+}
+
+/// Settings of a datatype from its `moverox(...)` attributes.
+#[derive(Default)]
+pub(super) struct Settings {
+    /// Defaults of type parameters, `OTW` for the OTW type.
+    pub(super) type_defaults: Vec<(Ident, move_syn::Type)>,
+    /// Derives to add to the standard ones.
+    pub(super) derives: Vec<Ident>,
+    /// Name of the generated Rust item, if not the Move one.
+    pub(super) rename: Option<Ident>,
+}
+
+impl Settings {
+    pub(super) const fn is_empty(&self) -> bool {
+        self.type_defaults.is_empty() && self.derives.is_empty() && self.rename.is_none()
     }
 }
 
-/// Filter and parse Move attributes into Rust docs (1st) and OTW type defaults (2nd).
-pub(super) fn extract(attrs: &[Attributes]) -> Result<(TokenStream, HashSet<Ident>)> {
+/// Filter and parse Move attributes into Rust docs (1st) and datatype settings (2nd).
+pub(super) fn extract(attrs: &[Attributes]) -> Result<(TokenStream, Settings)> {
     let (move_docs, other): (Vec<_>, Vec<_>) = attrs.iter().partition(|attr| attr.is_doc());
 
     let rust_docs = move_docs.into_iter().map(process_doc).collect();
 
-    let custom: Vec<_> = other.into_iter().flat_map(as_moverox).collect();
-    let mut otw_types = HashSet::new();
-    for ident in custom
-        .iter()
-        .flat_map(|custom| custom.settings())
-        .flat_map(|setting| setting.otw_types())
-    {
-        if otw_types.contains(ident) {
+    let custom = other
+        .into_iter()
+        .flat_map(as_moverox)
+        .collect::<Result<Vec<_>>>()?;
+    let settings = check_allowed(
+        &custom,
+        &["type_", "event", "derive", "rename"],
+        "datatypes",
+    )?;
+    let mut type_defaults: Vec<(Ident, move_syn::Type)> = vec![];
+    for (ident, default) in settings.iter().flat_map(|setting| setting.type_defaults()) {
+        if type_defaults.iter().any(|(declared, _)| declared == ident) {
             return Err(format!("Type {ident} declared twice").into());
         }
-        otw_types.insert(ident.to_owned());
+        type_defaults.push((ident.to_owned(), default.clone()));
+    }
+    let mut derives: Vec<Ident> = vec![];
+    for ident in settings.iter().flat_map(|setting| setting.derives()) {
+        if derives.contains(ident) {
+            return Err(format!("Derive {ident} declared twice").into());
+        }
+        derives.push(ident.to_owned());
     }
+    let rename = single_value(&settings, "rename")?
+        .map(rust_ident)
+        .transpose()?;
+
+    Ok((
+        rust_docs,
+        Settings {
+            type_defaults,
+            derives,
+            rename,
+        },
+    ))
+}
+
+/// Settings of a field from its `moverox(...)` attributes.
+#[derive(Default)]
+pub(super) struct FieldSettings {
+    /// Name of the Rust field, if not the Move one.
+    pub(super) rename: Option<Ident>,
+    /// Serde adapter, taking precedence over the type map's.
+    pub(super) serde_with: Option<String>,
+    /// Whether to leave it out of the `Debug` output.
+    pub(super) skip_debug: bool,
+    /// Rust type, taking precedence over the type map's.
+    pub(super) rust_type: Option<TokenStream>,
+    /// Rust docs, instead of the Move ones.
+    pub(super) docs: Option<TokenStream>,
+}
+
+/// Parse the `moverox(...)` attributes of a field.
+pub(super) fn field_settings(attrs: &[Attributes]) -> Result<FieldSettings> {
+    let custom = attrs
+        .iter()
+        .flat_map(as_moverox)
+        .collect::<Result<Vec<_>>>()?;
+    let settings = check_allowed(
+        &custom,
+        &["rename", "with", "skip_debug", "rust_type", "doc"],
+        "fields",
+    )?;
+    let rust_type = single_value(&settings, "rust_type")?
+        .map(|ty| {
+            ty.parse::<TokenStream>()
+                .map_err(|_| format!("Invalid Rust type {ty:?}"))
+        })
+        .transpose()?;
+    let docs = single_value(&settings, "doc")?.map(|text| {
+        let lines = text.lines().map(|line| format!(" {line}"));
+        quote!(#(#[doc = #lines])*)
+    });
+    Ok(FieldSettings {
+        rename: single_value(&settings, "rename")?
+            .map(rust_ident)
+            .transpose()?,
+        serde_with: single_value(&settings, "with")?.map(str::to_owned),
+        skip_debug: settings
+            .iter()
+            .any(|setting| setting.name() == "skip_debug"),
+        rust_type,
+        docs,
+    })
+}
 
-    Ok((rust_docs, otw_types))
+/// Rust name of an enum variant from its `moverox(rename = "...")` attribute, if any.
+pub(super) fn variant_rename(attrs: &[Attributes]) -> Result<Option<Ident>> {
+    let custom = attrs
+        .iter()
+        .flat_map(as_moverox)
+        .collect::<Result<Vec<_>>>()?;
+    let settings = check_allowed(&custom, &["rename"], "enum variants")?;
+    single_value(&settings, "rename")?
+        .map(rust_ident)
+        .transpose()
+}
+
+/// The settings of the `custom` attributes, if they're all `allowed` on `what` is annotated.
+fn check_allowed<'a>(
+    custom: &'a [self::grammar::Annotation],
+    allowed: &[&str],
+    what: &str,
+) -> Result<Vec<&'a self::grammar::Setting>> {
+    let settings: Vec<_> = custom.iter().flat_map(|custom| custom.settings()).collect();
+    if let Some(setting) = settings.iter().find(|s| !allowed.contains(&s.name())) {
+        return Err(format!("`{}` can't be set on {what}", setting.name()).into());
+    }
+    Ok(settings)
+}
+
+/// The value of the `name = "..."` setting, unless declared twice.
+fn single_value<'a>(
+    settings: &[&'a self::grammar::Setting],
+    name: &str,
+) -> Result<Option<&'a str>> {
+    let mut values = settings
+        .iter()
+        .filter(|setting| setting.name() == name)
+        .filter_map(|setting| setting.value());
+    let value = values.next();
+    if values.next().is_some() {
+        return Err(format!("`{name}` declared twice").into());
+    }
+    Ok(value)
+}
+
+/// Parse a Rust identifier, made raw if it's a keyword.
+pub(super) fn rust_ident(name: &str) -> Result<Ident> {
+    let mut tokens = name
+        .parse::<TokenStream>()
+        .map_err(|_| format!("Invalid Rust name {name:?}"))?
+        .into_iter();
+    match (tokens.next(), tokens.next()) {
+        (Some(TokenTree::Ident(ident)), None) => {
+            Ok(crate::named_fields::sanitize_ident(&ident).into_owned())
+        }
+        _ => Err(format!("Invalid Rust name {name:?}").into()),
+    }
+}
+
+/// Whether the datatype is annotated with `#[ext(moverox(event))]`.
+pub(super) fn is_event(attrs: &[Attributes]) -> bool {
+    attrs
+        .iter()
+        .flat_map(as_moverox)
+        .flatten()
+        .any(|custom| custom.settings().any(|setting| setting.is_event()))
 }
 
-pub(super) fn as_moverox(attr: &Attributes) -> impl Iterator<Item = self::grammar::Annotation> {
-    attr.external_attributes()
-        .filter_map(|ext| ext.to_token_iter().parse_all().ok())
+/// The `moverox(...)` attributes inside `#[ext(...)]`, or why they couldn't be parsed.
+pub(super) fn as_moverox(
+    attr: &Attributes,
+) -> impl Iterator<Item = Result<self::grammar::Annotation>> {
+    attr.external_attributes().filter_map(|ext| {
+        let tokens = ext.to_token_stream();
+        let is_moverox = matches!(
+            tokens.clone().into_iter().next(),
+            Some(TokenTree::Ident(ident)) if ident == "moverox"
+        );
+        is_moverox.then(|| {
+            tokens.to_token_iter().parse_all().map_err(|_| {
+                format!(
+                    "Invalid attribute `{tokens}`, expected `moverox(...)` with any of the \
+                        `type_`, `event`, `derive`, `rename`, `with`, `skip_debug`, `rust_type` \
+                        and `doc` settings"
+                )
+                .into()
+            })
+        })
+    })
 }
 
 fn process_doc(attr: &Attributes) -> TokenStream {
@@ -0,0 +1,184 @@
+use move_syn::{Attributes, Const, ConstValue, ItemKind, Module};
+use proc_macro2::{Ident, Literal, TokenStream};
+use quote::quote;
+use unsynn::{LiteralString, ToTokens as _};
+
+use crate::{ModuleSummary, Result};
+
+/// A clever error constant: `#[error] const EName: vector<u8> = b"<message>";`.
+struct ErrorConst<'a> {
+    ident: &'a Ident,
+    message: String,
+    code: Option<u64>,
+    constant_index: u16,
+}
+
+/// The `#[error]` constants of a module with byte string values.
+///
+/// The index of a constant in the compiled module's constant pool is derived from the declaration
+/// order, since the compiler adds the module's constants to the pool first, deduplicating equal
+/// values. `#[test_only]` and `#[mode(...)]` constants are left out of the pool. Constants after the first one whose
+/// value isn't a literal can't be indexed, so they're skipped.
+fn error_constants(module: &Module) -> Result<Vec<ErrorConst<'_>>> {
+    let mut pool = vec![];
+    let mut indexable = true;
+    let mut constants = vec![];
+    for item in module.items() {
+        let ItemKind::Const(c) = &item.kind else {
+            continue;
+        };
+        if item.is_test_gated() {
+            continue;
+        }
+        let key = pool_key(c).filter(|_| indexable);
+        indexable = key.is_some();
+        let constant_index = key.map(|key| {
+            pool.iter().position(|k| *k == key).unwrap_or_else(|| {
+                pool.push(key);
+                pool.len() - 1
+            })
+        });
+
+        if !item.attrs.iter().any(Attributes::is_error) {
+            continue;
+        }
+        let ident = c.ident();
+        let (Some(bytes), Some(constant_index)) =
+            (c.value().and_then(ConstValue::byte_string), constant_index)
+        else {
+            continue;
+        };
+        let code = item
+            .attrs
+            .iter()
+            .find_map(Attributes::error_code)
+            .map(|lit| crate::move_const::u64_value(&lit.to_string()))
+            .transpose()
+            .map_err(|err| format!("const {ident}: error code: {err}"))?;
+        constants.push(ErrorConst {
+            ident,
+            message: String::from_utf8_lossy(&bytes).into_owned(),
+            code,
+            constant_index: constant_index
+                .try_into()
+                .map_err(|_| format!("const {ident}: too many constants"))?,
+        });
+    }
+    Ok(constants)
+}
+
+/// Identity of a constant's value in the constant pool: its type and value.
+///
+/// `None` if the value isn't a literal.
+fn pool_key(c: &Const) -> Option<String> {
+    let ty = c.type_().tokens_to_string().replace(' ', "");
+    let value = c.value()?;
+    let value = if let Some(bytes) = value.byte_string() {
+        format!("{bytes:?}")
+    } else if let ConstValue::Lit(lit) = value {
+        let number = crate::move_const::number(&lit.to_string()).ok()?;
+        format!("{:?}", move_syn::address_bytes(&number)?)
+    } else {
+        value.tokens_to_string().replace(' ', "")
+    };
+    Some(format!("{ty}={value}"))
+}
+
+/// Whether the module declares any clever errors, i.e., if [`module_to_rust`] generates a
+/// `CLEVER_ERRORS` table for it.
+pub(super) fn has_clever_errors(module: &Module) -> Result<bool> {
+    Ok(!error_constants(module)?.is_empty())
+}
+
+/// `CLEVER_ERRORS` table with the `#[error]` constants declared in the module, if any.
+pub(super) fn module_to_rust(module: &Module, thecrate: &TokenStream) -> Result<TokenStream> {
+    let constants = error_constants(module)?;
+    if constants.is_empty() {
+        return Ok(TokenStream::new());
+    }
+    let table_doc = format!(
+        " The `#[error]` constants of the `{}` module, for decoding its clever errors.",
+        module.ident
+    );
+    let entries = constants.iter().map(
+        |ErrorConst {
+             ident,
+             message,
+             code,
+             constant_index,
+         }| {
+            let name = ident.to_string();
+            let constant_index = Literal::u16_unsuffixed(*constant_index);
+            let code = code.map(|code| {
+                let code = Literal::u64_unsuffixed(code);
+                quote!(.with_code(#code))
+            });
+            quote! {
+                #thecrate::ErrorConstant::new(#name, #message, #constant_index)#code
+            }
+        },
+    );
+    Ok(quote! {
+        #[doc = #table_doc]
+        pub const CLEVER_ERRORS: &[#thecrate::ErrorConstant] = &[
+            #(#entries),*
+        ];
+    })
+}
+
+/// Package-level `clever_error` function decoding clever errors of this package's modules by
+/// `(address, module, code)`.
+pub(super) fn package_to_rust<'a>(
+    modules: impl IntoIterator<Item = &'a ModuleSummary>,
+    thecrate: &TokenStream,
+    package: Option<&LiteralString>,
+) -> Result<TokenStream> {
+    let module_idents: Vec<_> = modules
+        .into_iter()
+        .filter(|module| !module.test_gated && module.clever_errors)
+        .map(ModuleSummary::ident)
+        .collect();
+    if module_idents.is_empty() {
+        return Ok(TokenStream::new());
+    }
+    let module_names: Vec<_> = module_idents
+        .iter()
+        .map(|ident| ident.to_string())
+        .collect();
+
+    let check_address = package.map(|package| {
+        let package = Literal::byte_string(package.as_str().as_bytes());
+        quote! {
+            if *address != #thecrate::types::const_address(#package) {
+                return None;
+            }
+        }
+    });
+    let address_doc = if package.is_some() {
+        " Returns `None` if `address` isn't this package's."
+    } else {
+        " The package address wasn't known when generating this code, so `address` is ignored."
+    };
+    let allow_unused = package
+        .is_none()
+        .then(|| quote!(#[allow(unused_variables)]));
+    Ok(quote! {
+        /// Decode a Move abort with `code` in `module` at `address` as a clever error, i.e., one
+        /// from a constant annotated `#[error]`.
+        ///
+        #[doc = #address_doc]
+        #allow_unused
+        pub fn clever_error(
+            address: &#thecrate::types::Address,
+            module: &str,
+            code: u64,
+        ) -> Option<#thecrate::CleverError> {
+            #check_address
+            let abort = #thecrate::CleverAbort::from_abort_code(code)?;
+            match module {
+                #(#module_names => abort.resolve(#module_names, #module_idents::CLEVER_ERRORS),)*
+                _ => None,
+            }
+        }
+    })
+}
@@ -0,0 +1,108 @@
+//! `Debug` implementations for the datatypes with fields left out of the `Debug` output, e.g.,
+//! secrets, which can't be derived.
+
+use proc_macro2::{Ident, Literal, TokenStream};
+use quote::quote;
+
+/// Fields of a struct or enum variant to format, each with an expression of a reference to its
+/// value, e.g., `&self.field`, unless left out.
+pub(super) enum Fields {
+    Named(Vec<(Ident, Option<TokenStream>)>),
+    Positional(Vec<Option<TokenStream>>),
+}
+
+impl Fields {
+    pub(super) fn any_skipped(&self) -> bool {
+        match self {
+            Self::Named(fields) => fields.iter().any(|(_, value)| value.is_none()),
+            Self::Positional(fields) => fields.iter().any(Option::is_none),
+        }
+    }
+}
+
+/// Expression formatting the `fields` of the struct or enum variant `ident` into `f`.
+pub(super) fn format(ident: &Ident, fields: &Fields) -> TokenStream {
+    let name = unraw(ident);
+    let finish = if fields.any_skipped() {
+        quote!(finish_non_exhaustive)
+    } else {
+        quote!(finish)
+    };
+    match fields {
+        Fields::Named(fields) => {
+            let fields = fields.iter().filter_map(|(ident, value)| {
+                let (name, value) = (unraw(ident), value.as_ref()?);
+                Some(quote!(.field(#name, #value)))
+            });
+            quote!(f.debug_struct(#name) #(#fields)* .#finish())
+        }
+        Fields::Positional(fields) => {
+            let fields = fields.iter().flatten().map(|value| quote!(.field(#value)));
+            quote!(f.debug_tuple(#name) #(#fields)* .#finish())
+        }
+    }
+}
+
+/// Match pattern binding the `fields` of an enum variant, each to a reference to its value.
+pub(super) fn bind(fields: &Fields) -> TokenStream {
+    match fields {
+        Fields::Named(fields) => {
+            let bindings = fields
+                .iter()
+                .filter(|(_, value)| value.is_some())
+                .map(|(ident, _)| ident);
+            quote!({ #(#bindings,)* .. })
+        }
+        Fields::Positional(fields) => {
+            let bindings = fields.iter().enumerate().filter_map(|(i, value)| {
+                let (index, value) = (Literal::usize_unsuffixed(i), value.as_ref()?);
+                Some(quote!(#index: #value))
+            });
+            quote!({ #(#bindings,)* .. })
+        }
+    }
+}
+
+/// `Debug` implementation for the datatype `ident` with `generics`, formatting `self` into `f` with
+/// `body`.
+///
+/// Like the derived one, it's bounded on all the type parameters implementing `Debug`.
+pub(super) fn impl_debug(
+    ident: &Ident,
+    generics: Option<&move_syn::Generics>,
+    body: TokenStream,
+) -> TokenStream {
+    let params: Vec<_> = generics
+        .into_iter()
+        .flat_map(move_syn::Generics::generics)
+        .map(|generic| &generic.ident)
+        .collect();
+    let (generics, where_clause) = if params.is_empty() {
+        (None, None)
+    } else {
+        (
+            Some(quote!(<#(#params),*>)),
+            Some(quote!(where #(#params: ::std::fmt::Debug),*)),
+        )
+    };
+    quote! {
+        impl #generics ::std::fmt::Debug for #ident #generics #where_clause {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                #body
+            }
+        }
+    }
+}
+
+/// Remove the `Debug` derive, returning whether it was there.
+pub(super) fn remove_derive(derives: &mut Vec<TokenStream>) -> bool {
+    let len = derives.len();
+    derives.retain(|derive| !crate::same_derive(derive, &quote!(Debug)));
+    derives.len() != len
+}
+
+fn unraw(ident: &Ident) -> String {
+    let name = ident.to_string();
+    name.strip_prefix("r#")
+        .map_or_else(|| name.clone(), str::to_owned)
+}
@@ -0,0 +1,214 @@
+use std::collections::HashMap;
+
+use move_syn::{Ability, ItemKind, Module};
+use proc_macro2::{Ident, Literal, Span, TokenStream};
+use quote::quote;
+use unsynn::LiteralString;
+
+use crate::{ModuleSummary, Result};
+
+/// A struct to decode as an event.
+struct EventStruct {
+    module: Ident,
+    ident: Ident,
+    /// Name of the generated Rust struct.
+    rust_ident: Ident,
+}
+
+/// Names of the structs of a module that are events: those with `copy` and `drop` or annotated
+/// with `#[ext(moverox(event))]`.
+///
+/// Generic structs are skipped, unless annotated, in which case it's an error. Test-only structs
+/// are skipped as well.
+pub(super) fn event_structs(module: &Module) -> Result<Vec<String>> {
+    let mut events = vec![];
+    for item in module.items() {
+        let ItemKind::Struct(s) = &item.kind else {
+            continue;
+        };
+        if item.is_test_gated() {
+            continue;
+        }
+        let is_marked = crate::attributes::is_event(&item.attrs);
+        let is_generic = s.generics.is_some();
+        if is_marked && is_generic {
+            return Err(format!("struct {}: generic structs can't be events", s.ident).into());
+        }
+        let copy_drop = s.abilities().any(|a| matches!(a, Ability::Copy(_)))
+            && s.abilities().any(|a| matches!(a, Ability::Drop(_)));
+        if is_marked || (copy_drop && !is_generic) {
+            events.push(s.ident.to_string());
+        }
+    }
+    Ok(events)
+}
+
+/// Package-level `Event` enum with a variant for each event struct of the modules, with a
+/// dispatcher decoding an event from its struct tag and BCS bytes.
+pub(super) fn package_to_rust<'a>(
+    modules: impl IntoIterator<Item = &'a ModuleSummary>,
+    thecrate: &TokenStream,
+    package: Option<&LiteralString>,
+    type_origins: &HashMap<String, String>,
+    renames: &HashMap<String, Ident>,
+) -> Result<TokenStream> {
+    let mut events = vec![];
+    for module in modules.into_iter().filter(|m| !m.test_gated) {
+        let names = module
+            .events
+            .as_ref()
+            .map_err(|err| format!("module {}: {err}", module.name))?;
+        for name in names {
+            let ident = Ident::new(name, Span::call_site());
+            let rust_ident = match (
+                renames.get(&format!("{}::{name}", module.name)),
+                module.renames.get(name),
+            ) {
+                (Some(rust_ident), _) => rust_ident.clone(),
+                (None, Some(rust_name)) => crate::attributes::rust_ident(rust_name)?,
+                (None, None) => ident.clone(),
+            };
+            events.push(EventStruct {
+                module: module.ident(),
+                ident,
+                rust_ident,
+            });
+        }
+    }
+    if events.is_empty() {
+        return Ok(TokenStream::new());
+    }
+
+    let variants: Vec<_> = events
+        .iter()
+        .map(|EventStruct { module, ident, .. }| {
+            Ident::new(&format!("{module}_{ident}"), ident.span())
+        })
+        .collect();
+    let types: Vec<_> = events
+        .iter()
+        .map(
+            |EventStruct {
+                 module, rust_ident, ..
+             }| quote!(#module::#rust_ident),
+        )
+        .collect();
+    let module_names: Vec<_> = events.iter().map(|e| e.module.to_string()).collect();
+    let names: Vec<_> = events.iter().map(|e| e.ident.to_string()).collect();
+
+    // Events first defined by another version of the package have its address instead
+    let origins: Vec<_> = module_names
+        .iter()
+        .zip(&names)
+        .map(|(module, name)| type_origins.get(&format!("{module}::{name}")))
+        .collect();
+    let any_origin = origins.iter().any(Option::is_some);
+    let check_address =
+        if !any_origin {
+            package.map(|package| {
+                let package = Literal::byte_string(package.as_str().as_bytes());
+                quote! {
+                    if struct_tag.address != #thecrate::types::const_address(#package) {
+                        return Ok(None);
+                    }
+                }
+            })
+        } else {
+            let package = package.map(LiteralString::as_str);
+            let arms = module_names.iter().zip(&names).zip(origins).filter_map(
+                |((module, name), origin)| {
+                    let address =
+                        Literal::byte_string(origin.map(String::as_str).or(package)?.as_bytes());
+                    Some(quote! {
+                        (#module, #name) => Some(#thecrate::types::const_address(#address)),
+                    })
+                },
+            );
+            Some(quote! {
+                let address = match (struct_tag.module.as_str(), struct_tag.name.as_str()) {
+                    #(#arms)*
+                    _ => None,
+                };
+                if address.is_some_and(|address| struct_tag.address != address) {
+                    return Ok(None);
+                }
+            })
+        };
+    let address_doc = match (package, any_origin) {
+        (Some(_), _) => {
+            " Returns `Ok(None)` if the struct tag isn't of one of this package's events."
+        }
+        (None, false) => {
+            " Returns `Ok(None)` if the struct tag isn't of one of this package's events. The \
+            package address wasn't known when generating this code, so any address is accepted."
+        }
+        (None, true) => {
+            " Returns `Ok(None)` if the struct tag isn't of one of this package's events. The \
+            package address wasn't known when generating this code, so only the addresses of \
+            events with a known type origin are checked."
+        }
+    };
+    Ok(quote! {
+        /// Events of this package's modules, i.e., structs with `copy` and `drop` or annotated with
+        /// `#[ext(moverox(event))]`.
+        #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+        #[allow(non_camel_case_types)]
+        pub enum Event {
+            #(#variants(#types)),*
+        }
+
+        impl Event {
+            /// Decode an event from its struct tag and BCS bytes.
+            ///
+            /// The decoder is looked up by the module and name in the struct tag, which is then
+            /// checked against the event's specialized struct tag before deserializing.
+            ///
+            #[doc = #address_doc]
+            pub fn from_raw(
+                struct_tag: &#thecrate::types::StructTag,
+                bytes: &[u8],
+            ) -> ::std::result::Result<::std::option::Option<Self>, #thecrate::FromRawDatatypeError> {
+                type Decoder = fn(
+                    &#thecrate::types::StructTag,
+                    &[u8],
+                ) -> ::std::result::Result<Event, #thecrate::FromRawDatatypeError>;
+                static DECODERS: ::std::sync::LazyLock<
+                    ::std::collections::HashMap<(&str, &str), Decoder>,
+                > = ::std::sync::LazyLock::new(|| {
+                    ::std::collections::HashMap::from([
+                        #(
+                            (
+                                (#module_names, #names),
+                                (|struct_tag, bytes| {
+                                    let (_, event) = #thecrate::parse_move_datatype(struct_tag, bytes)?;
+                                    Ok(Event::#variants(event))
+                                }) as Decoder,
+                            )
+                        ),*
+                    ])
+                });
+
+                #check_address
+                let key = (struct_tag.module.as_str(), struct_tag.name.as_str());
+                let Some(decode) = DECODERS.get(&key) else {
+                    return Ok(None);
+                };
+                decode(struct_tag, bytes).map(Some)
+            }
+
+            /// Name of the module declaring the event.
+            pub const fn module(&self) -> &'static str {
+                match self {
+                    #(Self::#variants(_) => #module_names),*
+                }
+            }
+
+            /// Name of the event struct.
+            pub const fn name(&self) -> &'static str {
+                match self {
+                    #(Self::#variants(_) => #names),*
+                }
+            }
+        }
+    })
+}
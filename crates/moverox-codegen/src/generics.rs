@@ -1,18 +1,20 @@
-use std::collections::HashSet;
-
+use move_syn::{ItemPath, Type};
 use quote::quote;
-use unsynn::{Ident, ToTokens as _, TokenStream};
+use unsynn::{Ident, TokenStream};
 
-use crate::Result;
+use crate::{ItemContext, Result, TypeMap, move_type};
 
 pub(super) trait GenericsExt {
     fn to_rust(&self) -> TokenStream;
 
-    /// Generics declaration for the enum/struct definition.
+    /// Generics declaration for the definition of the enum/struct `datatype`, with the defaults
+    /// of its type parameters: the ones in [`Options::type_defaults`](crate::Options) or else the
+    /// `annotated` ones.
     fn type_generics(
         &self,
-        thecrate: &TokenStream,
-        otw_types: HashSet<Ident>,
+        ctx: ItemContext<'_>,
+        datatype: &Ident,
+        annotated: Vec<(Ident, Type)>,
     ) -> Result<TokenStream>;
 
     fn phantoms(&self) -> impl Iterator<Item = &Ident>;
@@ -28,25 +30,30 @@ impl GenericsExt for move_syn::Generics {
 
     fn type_generics(
         &self,
-        thecrate: &TokenStream,
-        mut otw_types: HashSet<Ident>,
+        ctx: ItemContext<'_>,
+        datatype: &Ident,
+        mut annotated: Vec<(Ident, Type)>,
     ) -> Result<TokenStream> {
-        let idents: Vec<_> = self
-            .generics()
-            .map(|g| &g.ident)
-            .map(|ident| {
-                if otw_types.remove(ident) {
-                    quote!(#ident = #thecrate::Otw)
-                } else {
-                    ident.to_token_stream()
-                }
-            })
-            .collect();
+        let mut idents = vec![];
+        for ident in self.generics().map(|g| &g.ident) {
+            let annotated = annotated
+                .iter()
+                .position(|(param, _)| param == ident)
+                .map(|i| annotated.swap_remove(i).1);
+            let default = ctx.type_default(&format!("{datatype}::{ident}"), annotated)?;
+            idents.push(default.map_or_else(
+                || quote!(#ident),
+                |default| {
+                    let default = default_to_rust(&default, ctx);
+                    quote!(#ident = #default)
+                },
+            ));
+        }
 
-        if !otw_types.is_empty() {
-            let excess = otw_types
+        if !annotated.is_empty() {
+            let excess = annotated
                 .into_iter()
-                .map(|ident| ident.to_string())
+                .map(|(ident, _)| ident.to_string())
                 .reduce(|a, b| a + ", " + &b)
                 .unwrap_or_default();
             return Err(format!("Not a type parameter of this datatype: {excess}").into());
@@ -63,3 +70,16 @@ impl GenericsExt for move_syn::Generics {
             .map(|d| &d.ident)
     }
 }
+
+/// Rust type for the default of a type parameter: the `Otw` type for `OTW`, else the oxidized
+/// Move type.
+fn default_to_rust(default: &Type, ctx: ItemContext<'_>) -> TokenStream {
+    match &default.path {
+        ItemPath::Ident(ident) if ident == "OTW" && default.type_args.is_none() => {
+            let thecrate = ctx.thecrate;
+            quote!(#thecrate::Otw)
+        }
+        // NOTE: not mapped to custom Rust types, which don't have type tags
+        _ => move_type::to_rust_with_substitutions(default, ctx, &TypeMap::default()),
+    }
+}
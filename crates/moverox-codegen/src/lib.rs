@@ -5,29 +5,48 @@
 //! Defines extension traits to generate Rust code from Move intermediate representation.
 //!
 //! `thecrate` in arguments here is the path to a crate/module which exports:
-//! - a `types` module with `Address` and `U256` types from `moverox-types`
+//! - a `types` module with `Address` and `U256` types and the `const_address` function from
+//!   `moverox-types`
 //! - a `traits` module with `HasKey`, `MoveDatatype` and `MoveType` traits from `moverox-traits`
 //! - the `serde` crate
+//! - the `CleverAbort`, `CleverError` and `ErrorConstant` types from `moverox`
+//! - the `MoveCall`, `ObjectArg`, `Parameter` and `ParameterKind` types from `moverox`, if
+//!   generating [call descriptors](Options::call_descriptors)
+//! - the `FromReturnValuesError` type and the `check_return_count` and `parse_return_value`
+//!   functions from `moverox`, if generating [return decoders](Options::return_decoders)
+//! - the `parse_move_datatype` function and `FromRawDatatypeError` type from `moverox`, if
+//!   generating [events]
 
+use std::borrow::Cow;
 use std::collections::HashMap;
 
-use move_syn::{Attributes, Item, Module};
+use move_syn::{Attributes, Item, ItemKind, Module, Type};
 use proc_macro2::{Ident, TokenStream};
 use quote::quote;
-use unsynn::LiteralString;
+use unsynn::{IParse as _, LiteralString, ToTokens as _};
 
+mod abort_errors;
+mod address_map;
 mod attributes;
+mod clever_errors;
+mod debug;
+mod events;
 mod generics;
 mod iter;
+mod move_const;
 mod move_enum;
+mod move_fun;
 mod move_struct;
 mod move_type;
 mod named_fields;
 mod positional_fields;
 #[cfg(test)]
 mod tests;
+mod type_map;
 
+pub use self::address_map::AddressMap;
 use self::move_struct::StructGen as _;
+pub use self::type_map::{TypeMap, TypeMapping};
 
 type BoxError = Box<dyn std::error::Error + 'static>;
 type Result<T = (), E = BoxError> = std::result::Result<T, E>;
@@ -38,18 +57,120 @@ pub trait ModuleGen {
         &self,
         thecrate: &TokenStream,
         package: Option<&LiteralString>,
-        address_map: &HashMap<Ident, TokenStream>,
+        address_map: &AddressMap,
+        options: &Options,
     ) -> Result<TokenStream>;
 }
 
+/// Optional Rust code generation, all disabled by default.
+#[derive(Clone, Debug, Default)]
+#[non_exhaustive]
+pub struct Options {
+    /// Generate a function returning a `MoveCall` for each `public` or `entry` Move function.
+    ///
+    /// The generated function takes the type arguments as type tags, pure values as their Rust
+    /// equivalents and the other arguments, i.e., objects, as `ObjectArg`s, whether they're passed
+    /// by reference or by value.
+    pub call_descriptors: bool,
+    /// Generate a `<name>_returns` function decoding the return values of each `public` or `entry`
+    /// Move function that returns any.
+    ///
+    /// The generated function checks the type tag of each return value and BCS-decodes them into
+    /// a tuple. Needs the `bcs` feature of `moverox`.
+    pub return_decoders: bool,
+    /// Configuration predicate, e.g., `test` or `feature = "test-only"`, for generating Move items
+    /// and modules that are left out of regular Move builds, i.e., annotated with `#[test_only]`,
+    /// `#[test]` or `#[mode(...)]`.
+    ///
+    /// Those are generated behind `#[cfg(<predicate>)]` if set, else they're left out. Either way,
+    /// they're left out of the package-level code, like [`abort_errors`] and [`events`].
+    pub test_cfg: Option<TokenStream>,
+    /// Addresses of the package versions that first defined the datatypes, keyed by
+    /// `module::Datatype`, for the type tags of packages that were upgraded.
+    ///
+    /// Datatypes not in it get the package address.
+    pub type_origins: HashMap<String, String>,
+    /// Derives and outer attributes to add to or remove from the generated datatypes.
+    ///
+    /// They're applied from the least to the most specific scope, then in order, and finally the
+    /// derives in `#[ext(moverox(derive(...)))]` annotations are added.
+    pub datatype_attrs: Vec<DatatypeAttrs>,
+    /// Custom Rust types for Move datatypes in the fields of the generated datatypes, e.g.,
+    /// `::std::string::String` for `0x1::string::String`.
+    ///
+    /// Function signatures keep the oxidized Move datatypes, since their type tags are needed to
+    /// call the functions and decode their return values.
+    pub type_map: TypeMap,
+    /// Rust names of Move datatypes, keyed by `module::Datatype`, of their fields and enum
+    /// variants, keyed by `module::Datatype::member`, and of the fields of enum variants, keyed by
+    /// `module::Enum::Variant::field`.
+    ///
+    /// They take precedence over the `#[ext(moverox(rename = "..."))]` annotations. Renamed
+    /// datatypes keep their Move name in type tags, and references to them are renamed within
+    /// their module and its sibling modules, i.e., through addresses mapped to `super`.
+    pub renames: HashMap<String, Ident>,
+    /// Defaults of the type parameters of Move datatypes, keyed by `module::Datatype::T`, e.g.,
+    /// `sui::sui::SUI` or `OTW` for the `Otw` type.
+    ///
+    /// They take precedence over the `#[ext(moverox(type_(T = ...)))]` annotations. Like field
+    /// types, they're resolved through the module's imports and the address map.
+    pub type_defaults: HashMap<String, TokenStream>,
+}
+
+/// Derives and outer attributes to add to or remove from some generated datatypes; see
+/// [`Options::datatype_attrs`].
+#[derive(Clone, Debug, Default)]
+#[non_exhaustive]
+pub struct DatatypeAttrs {
+    /// Datatypes it applies to: all of them if `None`, else the ones of a module, e.g., `pool`, or
+    /// a single one, e.g., `pool::Pool`.
+    pub scope: Option<String>,
+    /// Derives to add, e.g., `PartialOrd` or `schemars::JsonSchema`.
+    pub derives: Vec<TokenStream>,
+    /// Derives to remove, by path or by name, e.g., `Hash`.
+    ///
+    /// Removing the `serde` or `MoveDatatype` derives breaks the rest of the generated code, as
+    /// does removing any of the standard ones from an [event](events).
+    pub remove_derives: Vec<TokenStream>,
+    /// Outer attributes to add, without the `#[...]`, e.g., `serde(deny_unknown_fields)`.
+    pub attrs: Vec<TokenStream>,
+}
+
+impl DatatypeAttrs {
+    /// How specific the scope is for the datatype `ident` of `module`, if it applies to it.
+    fn specificity(&self, module: Option<&Ident>, ident: &Ident) -> Option<u8> {
+        let Some(scope) = &self.scope else {
+            return Some(0);
+        };
+        let module = module?.to_string();
+        match scope.split_once("::") {
+            None => (*scope == module).then_some(1),
+            Some((scope_module, name)) => (scope_module == module && ident == name).then_some(2),
+        }
+    }
+}
+
+impl Options {
+    /// Whether to generate any code for Move functions.
+    const fn functions(&self) -> bool {
+        self.call_descriptors || self.return_decoders
+    }
+}
+
 #[sealed::sealed]
 impl ModuleGen for Module {
     fn to_rust(
         &self,
         thecrate: &TokenStream,
         package: Option<&LiteralString>,
-        address_map: &HashMap<Ident, TokenStream>,
+        address_map: &AddressMap,
+        options: &Options,
     ) -> Result<TokenStream> {
+        let cfg = match (self.is_test_gated(), &options.test_cfg) {
+            (false, _) => None,
+            (true, None) => return Ok(TokenStream::new()),
+            (true, Some(predicate)) => Some(quote!(#[cfg(#predicate)])),
+        };
         let (docs, other) = crate::attributes::extract(&self.attrs)
             .map_err(|err| format!("Parsing `moverox` attributes: {err}"))?;
 
@@ -58,19 +179,44 @@ impl ModuleGen for Module {
         }
 
         let ident = &self.ident;
+        let mut options = Cow::Borrowed(options);
+        for (datatype, rust_ident) in datatype_renames(self)? {
+            if !options.renames.contains_key(&datatype) {
+                options.to_mut().renames.insert(datatype, rust_ident);
+            }
+        }
+        self::move_fun::check_decoder_names(self, &options)?;
+        let type_defaults = datatype_type_defaults(self, &options)?;
+        if !type_defaults.is_empty() {
+            options.to_mut().type_defaults.extend(type_defaults);
+        }
+        let datatype_idents: Vec<_> = self
+            .items()
+            .filter_map(|item| match &item.kind {
+                ItemKind::Struct(s) => Some(s.ident.clone()),
+                ItemKind::Enum(e) => Some(e.ident.clone()),
+                _ => None,
+            })
+            .collect();
         let item_ctx = ItemContext {
             thecrate,
             package,
             module: Some(ident),
+            datatypes: &datatype_idents,
             address_map,
+            options: &options,
         };
         let datatypes: TokenStream = self
             .items()
             .map(|item| item.to_rust(item_ctx))
             .collect::<Result<_>>()?;
+        let abort_codes = self::abort_errors::module_to_rust(self, &options)?;
+        let clever_errors = self::clever_errors::module_to_rust(self, thecrate)
+            .map_err(|err| format!("clever errors: {err}"))?;
 
         Ok(quote! {
             #docs
+            #cfg
             #[allow(rustdoc::all, clippy::too_long_first_doc_paragraph)]
             pub mod #ident {
                 #[allow(non_camel_case_types, unused)]
@@ -81,28 +227,329 @@ impl ModuleGen for Module {
                 type vector<T> = ::std::vec::Vec<T>;
 
                 #datatypes
+                #abort_codes
+                #clever_errors
             }
         })
     }
 }
 
+/// What the package-level code, i.e., [`abort_errors`], [`clever_errors`] and [`events`], needs
+/// to know about a module.
+///
+/// Unlike [`Module`], it can be sent to other threads, so that modules can be oxidized in parallel
+/// and the package-level code generated from their summaries afterwards.
+#[derive(Clone, Debug)]
+pub struct ModuleSummary {
+    name: String,
+    test_gated: bool,
+    abort_codes: bool,
+    clever_errors: bool,
+    /// Names of the event structs, or why they couldn't be told apart. Only an error when
+    /// generating [`events`].
+    events: Result<Vec<String>, String>,
+    /// Rust names of the datatypes annotated with `#[ext(moverox(rename = "..."))]`.
+    renames: HashMap<String, String>,
+}
+
+impl ModuleSummary {
+    /// Summarize `module` for the package-level code.
+    pub fn new(module: &Module) -> Result<Self> {
+        Ok(Self {
+            name: module.ident.to_string(),
+            test_gated: module.is_test_gated(),
+            abort_codes: self::abort_errors::has_abort_codes(module)?,
+            clever_errors: self::clever_errors::has_clever_errors(module)?,
+            events: self::events::event_structs(module).map_err(|err| err.to_string()),
+            renames: datatype_renames(module)?
+                .into_iter()
+                .map(|(datatype, rust_ident)| {
+                    let name = datatype
+                        .split_once("::")
+                        .map_or(&*datatype, |(_, name)| name);
+                    (name.to_owned(), rust_ident.to_string())
+                })
+                .collect(),
+        })
+    }
+
+    fn ident(&self) -> Ident {
+        Ident::new(&self.name, proc_macro2::Span::call_site())
+    }
+}
+
+/// Rust names of the datatypes of `module` annotated with `#[ext(moverox(rename = "..."))]`,
+/// keyed by `module::Datatype`, for [`Options::renames`] of the modules referencing them.
+pub fn renamed_datatypes(module: &Module) -> Result<Vec<(String, String)>> {
+    Ok(datatype_renames(module)?
+        .into_iter()
+        .map(|(datatype, rust_ident)| (datatype, rust_ident.to_string()))
+        .collect())
+}
+
+fn datatype_renames(module: &Module) -> Result<Vec<(String, Ident)>> {
+    let mut renames = vec![];
+    for item in module.items() {
+        let (kind, ident) = match &item.kind {
+            ItemKind::Struct(s) => ("struct", &s.ident),
+            ItemKind::Enum(e) => ("enum", &e.ident),
+            _ => continue,
+        };
+        let (_, settings) = crate::attributes::extract(&item.attrs)
+            .map_err(|err| format!("{kind} {ident}: {err}"))?;
+        if let Some(rust_ident) = settings.rename {
+            renames.push((format!("{}::{ident}", module.ident), rust_ident));
+        }
+    }
+    Ok(renames)
+}
+
+/// Defaults of the type parameters of the datatypes of `module`, the ones in `options` or else the
+/// annotated ones, resolved through the module's imports.
+fn datatype_type_defaults(
+    module: &Module,
+    options: &Options,
+) -> Result<Vec<(String, TokenStream)>> {
+    let prefix = format!("{}::", module.ident);
+    let mut defaults: Vec<(String, Type)> = vec![];
+    for (path, tokens) in &options.type_defaults {
+        if path.starts_with(&prefix) {
+            defaults.push((path.clone(), parse_type_default(path, tokens)?));
+        }
+    }
+    for item in module.items() {
+        let (kind, ident) = match &item.kind {
+            ItemKind::Struct(s) => ("struct", &s.ident),
+            ItemKind::Enum(e) => ("enum", &e.ident),
+            _ => continue,
+        };
+        let (_, settings) = crate::attributes::extract(&item.attrs)
+            .map_err(|err| format!("{kind} {ident}: {err}"))?;
+        for (param, default) in settings.type_defaults {
+            let path = format!("{prefix}{ident}::{param}");
+            if !options.type_defaults.contains_key(&path) {
+                defaults.push((path, default));
+            }
+        }
+    }
+    Ok(defaults
+        .into_iter()
+        .map(|(path, mut default)| {
+            module.fully_qualify_type(&mut default);
+            (path, default.to_token_stream())
+        })
+        .collect())
+}
+
+fn parse_type_default(path: &str, tokens: &TokenStream) -> Result<Type> {
+    tokens
+        .to_token_iter()
+        .parse_all()
+        .map_err(|err| format!("Invalid default type `{tokens}` for {path}: {err}").into())
+}
+
+/// Generate a package-level `AbortError` enum wrapping the `Error` enums that [`ModuleGen`]
+/// generates for modules declaring abort codes (`const E*: u64` items).
+///
+/// `AbortError::from_abort` decodes a Move abort from its `(address, module, code)`. The generated
+/// code expects the oxidized modules to be its siblings.
+///
+/// Empty if none of the modules declare abort codes.
+pub fn abort_errors<'a>(
+    modules: impl IntoIterator<Item = &'a ModuleSummary>,
+    thecrate: &TokenStream,
+    package: Option<&LiteralString>,
+) -> Result<TokenStream> {
+    self::abort_errors::package_to_rust(modules, thecrate, package)
+}
+
+/// Generate a package-level `clever_error` function decoding Move 2024 clever errors with the
+/// `CLEVER_ERRORS` tables that [`ModuleGen`] generates for modules declaring `#[error]` constants.
+///
+/// `clever_error` decodes a Move abort from its `(address, module, code)` into the constant it
+/// aborted with and the source line of the abort. The generated code expects the oxidized modules
+/// to be its siblings.
+///
+/// Empty if none of the modules declare `#[error]` constants.
+pub fn clever_errors<'a>(
+    modules: impl IntoIterator<Item = &'a ModuleSummary>,
+    thecrate: &TokenStream,
+    package: Option<&LiteralString>,
+) -> Result<TokenStream> {
+    self::clever_errors::package_to_rust(modules, thecrate, package)
+}
+
+/// Generate a package-level `Event` enum with a variant for each non-generic struct that has `copy`
+/// and `drop` or is annotated with `#[ext(moverox(event))]`.
+///
+/// `Event::from_raw` decodes an event from its struct tag and BCS bytes, looking up the decoder by
+/// the struct tag's module and name. The generated code expects the oxidized modules to be its
+/// siblings and `thecrate` to export `parse_move_datatype` and `FromRawDatatypeError`, i.e., the
+/// `bcs` feature of `moverox`.
+///
+/// Struct tags are checked against the event's address in the [`type_origins`], if any, or else
+/// against the `package` address. Events are referred to by their Rust names in the [`renames`],
+/// if any, or else by their annotated ones.
+///
+/// Empty if none of the modules declare events.
+///
+/// [`type_origins`]: Options::type_origins
+/// [`renames`]: Options::renames
+pub fn events<'a>(
+    modules: impl IntoIterator<Item = &'a ModuleSummary>,
+    thecrate: &TokenStream,
+    package: Option<&LiteralString>,
+    type_origins: &HashMap<String, String>,
+    renames: &HashMap<String, Ident>,
+) -> Result<TokenStream> {
+    self::events::package_to_rust(modules, thecrate, package, type_origins, renames)
+}
+
 /// Context for Rust code generation from a Move item.
 #[derive(Clone, Copy)]
 pub struct ItemContext<'a> {
     /// Path to a crate/module which exports:
-    /// - a `types` module with `Address` and `U256` types from `moverox-types`
+    /// - a `types` module with `Address` and `U256` types and the `const_address` function from
+    ///   `moverox-types`
     /// - a `traits` module with `HasKey`, `MoveDatatype` and `MoveType` traits from `moverox-traits`
     /// - the `serde` crate
     /// - an `Otw` type
+    /// - the `CleverAbort`, `CleverError` and `ErrorConstant` types
+    /// - the `MoveCall`, `ObjectArg`, `Parameter` and `ParameterKind` types
+    /// - the `FromReturnValuesError` type and the `check_return_count` and `parse_return_value`
+    ///   functions
     pub thecrate: &'a TokenStream,
     /// Move package address as an `0x`-prefixed hex string.
     pub package: Option<&'a LiteralString>,
     /// Move module name.
     pub module: Option<&'a Ident>,
-    /// Mapping of Move named addresses to Rust paths.
+    /// Names of the datatypes declared in the module, which shadow the implicit imports.
+    pub datatypes: &'a [Ident],
+    /// Mapping of Move addresses to Rust paths.
     ///
     /// Used to map Move datatype paths to Rust-equivalents.
-    pub address_map: &'a HashMap<Ident, TokenStream>,
+    pub address_map: &'a AddressMap,
+    /// Optional code generation.
+    pub options: &'a Options,
+}
+
+impl ItemContext<'_> {
+    /// Address for the type tags of the datatype `ident`: the one it was first defined at, if
+    /// known, or else the package's.
+    fn datatype_address(&self, ident: &Ident) -> Option<LiteralString> {
+        self.module
+            .and_then(|module| self.options.type_origins.get(&format!("{module}::{ident}")))
+            .map(LiteralString::from_str)
+            .or_else(|| self.package.cloned())
+    }
+
+    /// `#[move_(...)]` attributes of the datatype `ident` for its type tags: its
+    /// [address](Self::datatype_address) and module, if known, and its `move_name`, if renamed.
+    fn datatype_move_attrs(&self, ident: &Ident, move_name: Option<String>) -> TokenStream {
+        let address = self.datatype_address(ident).map(|addr| {
+            let addr = addr.to_token_stream();
+            quote!(#[move_(address = #addr)])
+        });
+        let module = self.module.map(|ident| quote!(#[move_(module = #ident)]));
+        let name = move_name.map(|name| quote!(#[move_(name = #name)]));
+        quote!(#address #module #name)
+    }
+
+    /// Rust name of the item at `path` in the module, e.g., `Datatype` or `Datatype::field`, if
+    /// renamed: the one in [`Options::renames`], if any, or else the `annotated` one.
+    fn renamed(&self, path: &str, annotated: Option<Ident>) -> Option<Ident> {
+        self.module
+            .and_then(|module| self.options.renames.get(&format!("{module}::{path}")))
+            .cloned()
+            .or(annotated)
+    }
+
+    /// Default of the type parameter at `path` in the module, e.g., `Datatype::T`: the one in
+    /// [`Options::type_defaults`], if any, or else the `annotated` one.
+    fn type_default(&self, path: &str, annotated: Option<Type>) -> Result<Option<Type>> {
+        let Some((path, tokens)) = self.module.and_then(|module| {
+            let path = format!("{module}::{path}");
+            let tokens = self.options.type_defaults.get(&path)?;
+            Some((path, tokens))
+        }) else {
+            return Ok(annotated);
+        };
+        parse_type_default(&path, tokens).map(Some)
+    }
+
+    /// Derives of the datatype `ident`: the standard ones, after `extra` ones, changed by the
+    /// matching [`Options::datatype_attrs`] and followed by the `annotated` ones.
+    fn datatype_derives(
+        &self,
+        ident: &Ident,
+        extra: Option<TokenStream>,
+        annotated: &[Ident],
+    ) -> Vec<TokenStream> {
+        let thecrate = self.thecrate;
+        let mut derives: Vec<TokenStream> = extra
+            .into_iter()
+            .chain([
+                quote!(Clone),
+                quote!(Debug),
+                quote!(PartialEq),
+                quote!(Eq),
+                quote!(Hash),
+                quote!(#thecrate::traits::MoveDatatype),
+                quote!(#thecrate::serde::Deserialize),
+                quote!(#thecrate::serde::Serialize),
+            ])
+            .collect();
+        let add = |derives: &mut Vec<TokenStream>, derive: TokenStream| {
+            let key = derive_key(&derive);
+            if !derives.iter().any(|d| derive_key(d) == key) {
+                derives.push(derive);
+            }
+        };
+        for rule in self.datatype_attrs(ident) {
+            for removed in &rule.remove_derives {
+                derives.retain(|derive| !same_derive(derive, removed));
+            }
+            for derive in &rule.derives {
+                add(&mut derives, derive.clone());
+            }
+        }
+        for derive in annotated {
+            add(&mut derives, quote!(#derive));
+        }
+        derives
+    }
+
+    /// Outer attributes of the datatype `ident` from the matching [`Options::datatype_attrs`].
+    fn datatype_outer_attrs(&self, ident: &Ident) -> TokenStream {
+        self.datatype_attrs(ident)
+            .flat_map(|rule| &rule.attrs)
+            .map(|attr| quote!(#[#attr]))
+            .collect()
+    }
+
+    /// The [`Options::datatype_attrs`] for the datatype `ident`, from the least to the most
+    /// specific.
+    fn datatype_attrs(&self, ident: &Ident) -> impl Iterator<Item = &DatatypeAttrs> + '_ {
+        let mut rules: Vec<_> = self
+            .options
+            .datatype_attrs
+            .iter()
+            .filter_map(|rule| Some((rule.specificity(self.module, ident)?, rule)))
+            .collect();
+        rules.sort_by_key(|(specificity, _)| *specificity);
+        rules.into_iter().map(|(_, rule)| rule)
+    }
+}
+
+/// A derive's path as a string without whitespace, for comparing derives.
+fn derive_key(derive: &TokenStream) -> String {
+    derive.to_string().replace(' ', "")
+}
+
+/// Whether `derive` is the `removed` one, which may be given by name only.
+fn same_derive(derive: &TokenStream, removed: &TokenStream) -> bool {
+    let (derive, removed) = (derive_key(derive), derive_key(removed));
+    derive == removed || (!removed.contains("::") && derive.rsplit("::").next() == Some(&removed))
 }
 
 #[sealed::sealed]
@@ -119,22 +566,70 @@ impl ItemGen for Item {
         let (docs, generated) = match kind {
             K::Struct(s) => {
                 let err_ctx = |err| format!("struct {}: {err}", s.ident);
-                let (docs, otw_types) = crate::attributes::extract(attrs).map_err(err_ctx)?;
-                let generated = s.to_rust(otw_types, ctx).map_err(err_ctx)?;
+                let (docs, settings) = crate::attributes::extract(attrs).map_err(err_ctx)?;
+                let generated = s.to_rust(settings, ctx).map_err(err_ctx)?;
                 (docs, generated)
             }
             K::Enum(e) => {
                 let err_ctx = |err| format!("enum {}: {err}", e.ident);
-                let (docs, otw_types) = crate::attributes::extract(attrs).map_err(err_ctx)?;
-                let generated = self::move_enum::to_rust(e, otw_types, ctx).map_err(err_ctx)?;
+                let (docs, settings) = crate::attributes::extract(attrs).map_err(err_ctx)?;
+                let generated = self::move_enum::to_rust(e, settings, ctx).map_err(err_ctx)?;
+                (docs, generated)
+            }
+            K::Const(c) => {
+                let err_ctx = |err| format!("const {}: {err}", c.ident());
+                if attrs.iter().flat_map(self::attributes::as_moverox).count() > 0 {
+                    return Err(err_ctx(
+                        "constants cannot be annotated with custom `moverox` attributes".into(),
+                    )
+                    .into());
+                }
+                let (docs, _) = crate::attributes::extract(attrs).map_err(err_ctx)?;
+                let generated = self::move_const::to_rust(c, ctx.thecrate).map_err(err_ctx)?;
+                (docs, generated)
+            }
+            K::Function(f) if ctx.options.functions() => {
+                let err_ctx = |err| format!("fun {}: {err}", f.ident());
+                non_datatype_gen(attrs).map_err(err_ctx)?;
+                if !(self.visibility().is_public() || f.is_entry()) {
+                    return Ok(TokenStream::new());
+                }
+                let (docs, _) = crate::attributes::extract(attrs).map_err(err_ctx)?;
+                let generated = self::move_fun::to_rust(f, ctx).map_err(err_ctx)?;
+                (docs, generated)
+            }
+            K::NativeFun(f) if ctx.options.functions() => {
+                let err_ctx = |err| format!("native fun {}: {err}", f.ident());
+                non_datatype_gen(attrs).map_err(err_ctx)?;
+                if !self.visibility().is_public() {
+                    return Ok(TokenStream::new());
+                }
+                let (docs, _) = crate::attributes::extract(attrs).map_err(err_ctx)?;
+                let generated = self::move_fun::native_to_rust(f, ctx).map_err(err_ctx)?;
                 (docs, generated)
             }
             _ => return non_datatype_gen(attrs),
         };
 
+        if !self.is_test_gated() {
+            return Ok(quote! {
+                #docs
+                #generated
+            });
+        }
+        let Some(predicate) = ctx
+            .options
+            .test_cfg
+            .as_ref()
+            .filter(|_| !generated.is_empty())
+        else {
+            return Ok(TokenStream::new());
+        };
+        // NOTE: a Move item may generate several Rust items, which all need the attribute
+        let file: syn::File = syn::parse2(quote!(#docs #generated))?;
+        let items = file.items.iter();
         Ok(quote! {
-            #docs
-            #generated
+            #(#[cfg(#predicate)] #items)*
         })
     }
 }
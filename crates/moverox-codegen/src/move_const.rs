@@ -0,0 +1,181 @@
+use std::str::FromStr as _;
+
+use move_syn::{Const, ConstValue, ItemPath, Type};
+use proc_macro2::{Literal, TokenStream};
+use quote::quote;
+use unsynn::ToTokens as _;
+
+use crate::Result;
+
+const INT_SUFFIXES: [&str; 6] = ["u8", "u16", "u32", "u64", "u128", "u256"];
+
+/// Rust `const` item equivalent to a Move constant.
+///
+/// Empty if the constant's value isn't a literal, e.g., `1 << 8`, since those aren't evaluated,
+/// or if it's a named address, e.g., `@pkg`, since its value isn't known when generating the code.
+pub(super) fn to_rust(this: &Const, thecrate: &TokenStream) -> Result<TokenStream> {
+    let Some(value) = this.value() else {
+        return Ok(TokenStream::new());
+    };
+    let ident = this.ident();
+    let ty = ConstType::new(this.type_())?;
+    let rust_type = ty.to_rust(thecrate);
+    let rust_value = ty.value_to_rust(value, thecrate)?;
+    Ok(quote! {
+        #[allow(non_upper_case_globals)]
+        pub const #ident: #rust_type = #rust_value;
+    })
+}
+
+/// The types Move constants can have.
+enum ConstType {
+    Bool,
+    Int(String),
+    U256,
+    Address,
+    Vector(Box<Self>),
+}
+
+impl ConstType {
+    fn new(ty: &Type) -> Result<Self> {
+        let ItemPath::Ident(ident) = &ty.path else {
+            return Err(format!("unsupported constant type {}", ty.path.tokens_to_string()).into());
+        };
+        let name = ident.to_string();
+        let mut type_args = ty.type_args.iter().flat_map(|args| args.types());
+        Ok(match (name.as_str(), type_args.next(), type_args.next()) {
+            ("vector", Some(inner), None) => Self::Vector(Box::new(Self::new(inner)?)),
+            ("bool", None, _) => Self::Bool,
+            ("address", None, _) => Self::Address,
+            ("u256", None, _) => Self::U256,
+            (int, None, _) if INT_SUFFIXES.contains(&int) => Self::Int(name),
+            _ => return Err(format!("unsupported constant type {name}").into()),
+        })
+    }
+
+    fn to_rust(&self, thecrate: &TokenStream) -> TokenStream {
+        match self {
+            Self::Bool => quote!(bool),
+            Self::Int(name) => TokenStream::from_str(name).expect("Valid Rust type"),
+            Self::U256 => quote!(#thecrate::types::U256),
+            Self::Address => quote!(#thecrate::types::Address),
+            Self::Vector(inner) => {
+                let inner = inner.to_rust(thecrate);
+                quote!(&[#inner])
+            }
+        }
+    }
+
+    fn value_to_rust(&self, value: &ConstValue, thecrate: &TokenStream) -> Result<TokenStream> {
+        use ConstValue as V;
+        Ok(match (self, value) {
+            (Self::Bool, V::True(_)) => quote!(true),
+            (Self::Bool, V::False(_)) => quote!(false),
+            (Self::Int(int), V::Lit(lit)) => {
+                let number = number(&lit.to_string())?;
+                check_range(&number, int)?;
+                let number = Literal::from_str(&number)?;
+                quote!(#number)
+            }
+            (Self::U256, V::Lit(lit)) => {
+                let number = number(&lit.to_string())?;
+                let bytes = bytes_of(&number)?.map(Literal::u8_unsuffixed);
+                quote!(#thecrate::types::U256::from_be_bytes(&[#(#bytes),*]))
+            }
+            (Self::Address, V::Address { address, .. }) => {
+                let bytes = bytes_of(&address.to_string())?;
+                let hex = Literal::byte_string(format!("0x{}", hex(&bytes)).as_bytes());
+                quote!(#thecrate::types::const_address(#hex))
+            }
+            (Self::Vector(inner), V::Lit(lit)) if matches!(**inner, Self::Int(ref n) if n == "u8") =>
+            {
+                let lit = lit.to_string();
+                if !lit.starts_with("b\"") {
+                    return Err(format!("expected a byte string, found {lit}").into());
+                }
+                let bytes = Literal::from_str(&lit)?;
+                quote!(#bytes)
+            }
+            (Self::Vector(inner), V::HexString { hex, .. }) if matches!(**inner, Self::Int(ref n) if n == "u8") =>
+            {
+                let hex = hex.as_str();
+                if hex.len() % 2 != 0 {
+                    return Err(format!("odd number of digits in hex string {hex:?}").into());
+                }
+                let bytes = (0..hex.len())
+                    .step_by(2)
+                    .map(|i| {
+                        let byte = u8::from_str_radix(&hex[i..i + 2], 16)
+                            .map_err(|_| format!("invalid hex string {hex:?}"))?;
+                        Ok(Literal::u8_unsuffixed(byte))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                quote!(&[#(#bytes),*])
+            }
+            (Self::Vector(inner), V::Vector { .. }) => {
+                let elements = value
+                    .elements()
+                    .into_iter()
+                    .flatten()
+                    .map(|element| inner.value_to_rust(element, thecrate))
+                    .collect::<Result<Vec<_>>>()?;
+                quote!(&[#(#elements),*])
+            }
+            (_, value) => {
+                return Err(format!("unexpected value {}", value.tokens_to_string()).into());
+            }
+        })
+    }
+}
+
+/// Strip the Move type suffix from a number literal, e.g., `1_000u64` -> `1_000`.
+pub(super) fn number(lit: &str) -> Result<String> {
+    let number = INT_SUFFIXES
+        .iter()
+        .find_map(|suffix| lit.strip_suffix(suffix))
+        .unwrap_or(lit);
+    let is_number = number.strip_prefix("0x").map_or_else(
+        || number.chars().all(|c| c.is_ascii_digit() || c == '_'),
+        |hex| hex.chars().all(|c| c.is_ascii_hexdigit() || c == '_'),
+    );
+    if number.is_empty() || !is_number {
+        return Err(format!("expected a number, found {lit}").into());
+    }
+    Ok(number.to_owned())
+}
+
+/// Value of a `u64` literal, e.g., `0x10u64` -> `16`.
+pub(super) fn u64_value(lit: &str) -> Result<u64> {
+    let number = number(lit)?.replace('_', "");
+    Ok(number
+        .strip_prefix("0x")
+        .map_or_else(|| number.parse(), |hex| u64::from_str_radix(hex, 16))?)
+}
+
+/// Check that a number fits the Move integer type `int`, e.g., `u8`, which Rust would reject.
+fn check_range(number: &str, int: &str) -> Result<()> {
+    let bits: u32 = int.trim_start_matches('u').parse()?;
+    let digits = number.replace('_', "");
+    let value = digits.strip_prefix("0x").map_or_else(
+        || digits.parse::<u128>(),
+        |hex| u128::from_str_radix(hex, 16),
+    );
+    match value {
+        Ok(value) if bits == 128 || value >> bits == 0 => Ok(()),
+        _ => Err(format!("{number} is out of range for {int}").into()),
+    }
+}
+
+fn bytes_of(number: &str) -> Result<[u8; 32]> {
+    move_syn::address_bytes(number).ok_or_else(|| format!("invalid 256-bit number {number}").into())
+}
+
+fn hex(bytes: &[u8]) -> String {
+    let hex: String = bytes.iter().map(|b| format!("{b:02x}")).collect();
+    let trimmed = hex.trim_start_matches('0');
+    if trimmed.is_empty() {
+        "0".to_owned()
+    } else {
+        trimmed.to_owned()
+    }
+}
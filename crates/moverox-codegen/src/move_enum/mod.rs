@@ -1,34 +1,34 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashSet;
 
 use move_syn::{FieldsKind, ItemPath};
 use quote::quote;
 use unsynn::{Ident, ToTokens as _, TokenStream};
 
+use crate::attributes::Settings;
 use crate::generics::GenericsExt;
 use crate::iter::BoxedIter as _;
-use crate::{ItemContext, Result, named_fields, positional_fields};
+use crate::{ItemContext, Result, debug, named_fields, positional_fields};
 
 /// The full Rust struct declaration and its `new` constructor.
 pub(super) fn to_rust(
     this: &move_syn::Enum,
-    otw_types: HashSet<Ident>,
+    settings: Settings,
     ctx: ItemContext,
 ) -> Result<TokenStream> {
     let move_syn::Enum {
         ident, generics, ..
     } = this;
 
-    let extra_attrs: TokenStream = ctx
-        .package
-        .into_iter()
-        .map(unsynn::ToTokens::to_token_stream)
-        .map(|addr| quote!(#[move_(address = #addr)]))
-        .chain(ctx.module.map(|ident| quote!(#[move_(module = #ident)])))
-        .collect();
+    let rust_ident = ctx.renamed(&ident.to_string(), settings.rename);
+    let move_name = rust_ident.is_some().then(|| ident.to_string());
+    let move_attrs = ctx.datatype_move_attrs(ident, move_name);
+    let outer_attrs = ctx.datatype_outer_attrs(ident);
+    let extra_attrs = quote!(#move_attrs #outer_attrs);
+    let mut derives = ctx.datatype_derives(ident, None, &settings.derives);
 
     let type_generics = generics
         .as_ref()
-        .map(|g| g.type_generics(ctx.thecrate, otw_types))
+        .map(|g| g.type_generics(ctx, ident, settings.type_defaults))
         .transpose()
         .map(Option::unwrap_or_default)?;
 
@@ -36,33 +36,118 @@ pub(super) fn to_rust(
     let variants = this
         .variants()
         // HACK: pipe unused phantom parameters into the first variant to become phantom data fields
-        .map(|var| variant_to_rust(var, &std::mem::take(&mut phantoms), ctx.address_map));
+        .map(|var| {
+            let phantoms = std::mem::take(&mut phantoms);
+            variant_to_rust(var, &phantoms, ctx, ident)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let rust_ident = rust_ident.as_ref().unwrap_or(ident);
+    let impl_debug = impl_debug(this, rust_ident, &mut derives, ctx)?;
 
     // NOTE: this has to be formatted as a string first, so that `quote!` will turn it into a
     // string literal later, which is what `#[serde(crate = ...)]` accepts
     let thecrate = ctx.thecrate;
     let serde_crate = format!("{thecrate}::serde").replace(" ", "");
     Ok(quote! {
-        #[derive(
-            Clone,
-            Debug,
-            PartialEq,
-            Eq,
-            Hash,
-            #thecrate::traits::MoveDatatype,
-            #thecrate::serde::Deserialize,
-            #thecrate::serde::Serialize,
-        )]
+        #[derive(#(#derives,)*)]
         #[move_(crate = #thecrate::traits)]
         #[serde(crate = #serde_crate)]
         #extra_attrs
         #[allow(non_snake_case)]
-        pub enum #ident #type_generics {
+        pub enum #rust_ident #type_generics {
             #(#variants),*
         }
+        #impl_debug
+    })
+}
+
+/// A `Debug` implementation replacing the derived one in `derives`, if any of the variants' fields
+/// are left out of it.
+fn impl_debug(
+    this: &move_syn::Enum,
+    rust_ident: &Ident,
+    derives: &mut Vec<TokenStream>,
+    ctx: ItemContext,
+) -> Result<Option<TokenStream>> {
+    let variants = this
+        .variants()
+        .map(|var| {
+            Ok((
+                variant_rust_ident(var, ctx, &this.ident)?,
+                debug_fields(var, ctx, &this.ident)?,
+            ))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    if !variants.iter().any(|(_, fields)| fields.any_skipped()) || !debug::remove_derive(derives) {
+        return Ok(None);
+    }
+    let arms = variants.iter().map(|(ident, fields)| {
+        let pattern = debug::bind(fields);
+        let body = debug::format(ident, fields);
+        quote!(Self::#ident #pattern => #body)
+    });
+    let body = quote! {
+        match self {
+            #(#arms,)*
+        }
+    };
+    Ok(Some(debug::impl_debug(
+        rust_ident,
+        this.generics.as_ref(),
+        body,
+    )))
+}
+
+/// The fields of the variant `this` of the enum `enum_ident` to format in the `Debug`
+/// implementation, bound by [`debug::bind`].
+fn debug_fields(
+    this: &move_syn::EnumVariant,
+    ctx: ItemContext,
+    enum_ident: &Ident,
+) -> Result<debug::Fields> {
+    let ident = &this.ident;
+    let err_ctx = |err| format!("variant {ident}: {err}");
+    Ok(match &this.fields {
+        Some(FieldsKind::Named(named)) => debug::Fields::Named(
+            named_fields::to_rust_fields(named, ctx, &format!("{enum_ident}::{ident}"))
+                .map_err(err_ctx)?
+                .into_iter()
+                .map(|field| {
+                    let ident = field.ident.into_owned();
+                    let value = (!field.skip_debug).then(|| quote!(#ident));
+                    (ident, value)
+                })
+                .collect(),
+        ),
+        Some(FieldsKind::Positional(positional)) => debug::Fields::Positional(
+            positional_fields::to_rust_fields(positional, ctx)
+                .map_err(err_ctx)?
+                .into_iter()
+                .enumerate()
+                .map(|(i, field)| {
+                    let binding = Ident::new(&format!("_{i}"), ident.span());
+                    (!field.skip_debug).then(|| quote!(#binding))
+                })
+                .collect(),
+        ),
+        None => debug::Fields::Positional(Vec::new()),
     })
 }
 
+/// The Rust name of the variant `this` of the enum `enum_ident`.
+fn variant_rust_ident(
+    this: &move_syn::EnumVariant,
+    ctx: ItemContext,
+    enum_ident: &Ident,
+) -> Result<Ident> {
+    let ident = &this.ident;
+    let annotated = crate::attributes::variant_rename(&this.attrs)
+        .map_err(|err| format!("variant {ident}: {err}"))?;
+    Ok(ctx
+        .renamed(&format!("{enum_ident}::{ident}"), annotated)
+        .unwrap_or_else(|| ident.clone()))
+}
+
 /// Collect `this` enum's phantom types that aren't used in any of its field types.
 fn unused_phantoms(this: &move_syn::Enum) -> Vec<Ident> {
     let Some(generics) = this.generics.as_ref() else {
@@ -114,21 +199,32 @@ fn leaf_types_recursive<'a>(
         .boxed()
 }
 
+/// A variant of the enum `enum_ident` in Rust.
 fn variant_to_rust(
     this: &move_syn::EnumVariant,
     phantoms: &[Ident],
-    address_map: &HashMap<Ident, TokenStream>,
-) -> TokenStream {
+    ctx: ItemContext,
+    enum_ident: &Ident,
+) -> Result<TokenStream> {
     use move_syn::FieldsKind as K;
     let move_syn::EnumVariant {
         attrs,
         ident,
         fields,
+        ..
     } = this;
+    let err_ctx = |err| format!("variant {ident}: {err}");
+    let path = format!("{enum_ident}::{ident}");
+    let rust_ident = variant_rust_ident(this, ctx, enum_ident)?;
+    let serde_rename = (rust_ident != *ident).then(|| {
+        let name = ident.to_string();
+        quote!(#[serde(rename = #name)])
+    });
     let attrs = attrs
         .iter()
         .filter(|attr| attr.is_doc())
-        .map(|attr| attr.to_token_stream());
+        .map(|attr| attr.to_token_stream())
+        .chain(serde_rename);
 
     // Move enum variants can have empty fields
     let bool_if_empty = false;
@@ -137,35 +233,39 @@ fn variant_to_rust(
 
     // If the variant is a unit (empty) one but there are phantom parameters, make it into a
     // positional fields one with just the phantom data
-    let default_fields = (!phantoms.is_empty()).then(|| {
-        positional_fields::to_rust(
-            &Default::default(),
-            phantoms.iter(),
-            address_map,
-            bool_if_empty,
-            visibility,
-        )
-    });
+    let default_fields = (!phantoms.is_empty())
+        .then(|| {
+            positional_fields::to_rust(
+                &Default::default(),
+                phantoms.iter(),
+                ctx,
+                bool_if_empty,
+                visibility,
+            )
+        })
+        .transpose()?;
 
     let fields = fields
         .as_ref()
         .map(|kind| match kind {
             K::Named(named) => {
-                named_fields::to_rust(named, phantoms.iter(), address_map, visibility)
+                named_fields::to_rust(named, phantoms.iter(), ctx, &path, visibility)
             }
             K::Positional(positional) => positional_fields::to_rust(
                 positional,
                 phantoms.iter(),
-                address_map,
+                ctx,
                 bool_if_empty,
                 visibility,
             ),
         })
+        .transpose()
+        .map_err(err_ctx)?
         .or(default_fields)
         .unwrap_or_default();
 
-    quote! {
+    Ok(quote! {
         #(#attrs)*
-        #ident #fields
-    }
+        #rust_ident #fields
+    })
 }
@@ -0,0 +1,380 @@
+use std::collections::HashSet;
+
+use move_syn::{
+    Address,
+    Function,
+    FunctionArg,
+    Generics,
+    ItemKind,
+    ItemPath,
+    MaybeRefType,
+    Module,
+    NativeFun,
+    Type,
+};
+use proc_macro2::{Ident, Literal, Span, TokenStream};
+use quote::quote;
+
+use crate::named_fields::sanitize_ident;
+use crate::{ItemContext, Options, Result, TypeMap, move_type};
+
+/// Rust functions for a Move function, depending on the [options](crate::Options):
+/// - a call descriptor, returning a `MoveCall` with its arguments. Arguments are `ObjectArg`s,
+///   unless their type is [pure](is_pure). The implicit `TxContext` argument is left out.
+/// - a decoder of its return values, if it returns any
+pub(super) fn to_rust(this: &Function, ctx: ItemContext<'_>) -> Result<TokenStream> {
+    fun_to_rust(
+        this.ident(),
+        this.generics(),
+        this.arguments(),
+        this.returns(),
+        ctx,
+    )
+}
+
+/// Rust functions for a native Move function; see [`to_rust`].
+pub(super) fn native_to_rust(this: &NativeFun, ctx: ItemContext<'_>) -> Result<TokenStream> {
+    fun_to_rust(
+        this.ident(),
+        this.generics(),
+        this.arguments(),
+        this.returns(),
+        ctx,
+    )
+}
+
+/// Error if the [return decoder](return_decoder) of a function has the name of another function's
+/// call descriptor, e.g., for Move functions `balance` and `balance_returns`.
+pub(super) fn check_decoder_names(module: &Module, options: &Options) -> Result<()> {
+    if !(options.call_descriptors && options.return_decoders) {
+        return Ok(());
+    }
+    let callable: Vec<_> = module
+        .items()
+        .filter_map(|item| match &item.kind {
+            ItemKind::Function(f) if item.visibility().is_public() || f.is_entry() => {
+                Some((f.ident(), f.returns().len() > 0))
+            }
+            ItemKind::NativeFun(f) if item.visibility().is_public() => {
+                Some((f.ident(), f.returns().len() > 0))
+            }
+            _ => None,
+        })
+        .collect();
+    for (ident, _) in callable.iter().filter(|(_, returns)| *returns) {
+        let decoder = format!("{ident}_returns");
+        if callable.iter().any(|(other, _)| **other == decoder) {
+            return Err(format!(
+                "fun {ident}: the decoder of its return values would have the same name as the \
+                 call descriptor of `{decoder}`"
+            )
+            .into());
+        }
+    }
+    Ok(())
+}
+
+fn fun_to_rust<'a>(
+    ident: &Ident,
+    generics: Option<&Generics>,
+    arguments: impl Iterator<Item = &'a FunctionArg>,
+    returns: impl ExactSizeIterator<Item = &'a MaybeRefType>,
+    ctx: ItemContext<'_>,
+) -> Result<TokenStream> {
+    let mut generated = TokenStream::new();
+    if ctx.options.call_descriptors {
+        generated.extend(call_descriptor(ident, generics, arguments, ctx)?);
+    }
+    if ctx.options.return_decoders {
+        generated.extend(return_decoder(ident, generics, returns, ctx)?);
+    }
+    Ok(generated)
+}
+
+fn call_descriptor<'a>(
+    ident: &Ident,
+    generics: Option<&Generics>,
+    arguments: impl Iterator<Item = &'a FunctionArg>,
+    ctx: ItemContext<'_>,
+) -> Result<TokenStream> {
+    let ItemContext {
+        thecrate,
+        package,
+        module,
+        ..
+    } = ctx;
+    let module = module.ok_or("call descriptors need the module name")?;
+    let doc = format!(" Call `{module}::{ident}`.");
+    let module_name = module.to_string();
+    let function_name = ident.to_string();
+    let fn_ident = sanitize_ident(ident);
+
+    let (package_param, package) = package.map_or_else(
+        || {
+            (
+                Some(quote!(package: #thecrate::types::Address,)),
+                quote!(package),
+            )
+        },
+        |package| {
+            let package = Literal::byte_string(package.as_str().as_bytes());
+            (None, quote!(#thecrate::types::const_address(#package)))
+        },
+    );
+
+    let type_params: Vec<_> = generics
+        .into_iter()
+        .flat_map(Generics::generics)
+        .map(|g| &g.ident)
+        .collect();
+    let type_args_param = (!type_params.is_empty()).then(|| {
+        quote! {
+            type_args: (#(<#type_params as #thecrate::traits::MoveType>::TypeTag,)*),
+        }
+    });
+    let type_arguments = (0..type_params.len()).map(|i| {
+        let i = Literal::usize_unsuffixed(i);
+        quote!(#thecrate::traits::MoveTypeTag::to_type_tag(&type_args.#i))
+    });
+
+    // Names of the other parameters, including those for `_` arguments
+    let arguments: Vec<_> = arguments.collect();
+    let reserved: HashSet<_> = (arguments.iter().enumerate())
+        .filter(|(_, arg)| arg.ident() == "_")
+        .map(|(i, _)| format!("arg{i}"))
+        .chain(package_param.is_some().then(|| "package".to_owned()))
+        .chain(type_args_param.is_some().then(|| "type_args".to_owned()))
+        .collect();
+
+    let mut params = vec![];
+    let mut parameters = vec![];
+    let (mut objects, mut object_types) = (vec![], vec![]);
+    let (mut pures, mut pure_types) = (vec![], vec![]);
+    for (i, arg) in arguments.into_iter().enumerate() {
+        let maybe_ref = arg.type_();
+        if is_tx_context(maybe_ref, ctx) {
+            continue;
+        }
+        let name = arg.ident().to_string();
+        let arg_ident = match name.as_str() {
+            "_" => Ident::new(&format!("arg{i}"), Span::call_site()),
+            // Can't be raw identifiers
+            "self" | "Self" | "super" | "crate" => {
+                Ident::new(&format!("{name}_"), arg.ident().span())
+            }
+            _ if reserved.contains(&name) => Ident::new(&format!("{name}_"), arg.ident().span()),
+            _ => sanitize_ident(arg.ident()).into_owned(),
+        };
+        // NOTE: not mapped to custom Rust types, which don't have type tags
+        let rust_type =
+            move_type::to_rust_with_substitutions(maybe_ref.type_(), ctx, &TypeMap::default());
+        let (kind, rust_type) = if !is_pure(maybe_ref.type_(), ctx) {
+            let kind = if maybe_ref.is_ref() {
+                quote!(Ref)
+            } else if maybe_ref.is_mut_ref() {
+                quote!(MutRef)
+            } else {
+                quote!(Owned)
+            };
+            let object_type = quote!(#thecrate::ObjectArg<#rust_type>);
+            objects.push(arg_ident.clone());
+            object_types.push(object_type.clone());
+            (kind, object_type)
+        } else {
+            pures.push(arg_ident.clone());
+            pure_types.push(rust_type.clone());
+            (quote!(Value), rust_type)
+        };
+        params.push(quote!(#arg_ident: #rust_type));
+        parameters.push(quote! {
+            #thecrate::Parameter { name: #name, kind: #thecrate::ParameterKind::#kind }
+        });
+    }
+
+    Ok(quote! {
+        #[doc = #doc]
+        #[allow(
+            non_camel_case_types,
+            clippy::too_many_arguments,
+            clippy::type_complexity,
+            clippy::used_underscore_binding,
+        )]
+        pub fn #fn_ident<#(#type_params: #thecrate::traits::MoveType),*>(
+            #package_param
+            #type_args_param
+            #(#params),*
+        ) -> #thecrate::MoveCall<(#(#object_types,)*), (#(#pure_types,)*)> {
+            #thecrate::MoveCall {
+                package: #package,
+                module: #thecrate::types::IdentStr::cast(#module_name),
+                function: #thecrate::types::IdentStr::cast(#function_name),
+                type_arguments: ::std::vec![#(#type_arguments),*],
+                parameters: &[#(#parameters),*],
+                objects: (#(#objects,)*),
+                pures: (#(#pures,)*),
+            }
+        }
+    })
+}
+
+/// `<name>_returns` function decoding the return values of a call to the Move function, e.g., from
+/// a dev-inspect transaction, into a tuple. Empty if the function doesn't return anything.
+///
+/// References are decoded as the values they point to. Only the type parameters appearing in the
+/// return types are kept.
+///
+/// Empty as well if any return type has a bare `Option`, i.e., one that isn't imported, since it's
+/// generated as Rust's `Option` which has no type tag.
+fn return_decoder<'a>(
+    ident: &Ident,
+    generics: Option<&Generics>,
+    returns: impl ExactSizeIterator<Item = &'a MaybeRefType>,
+    ctx: ItemContext<'_>,
+) -> Result<TokenStream> {
+    let ItemContext {
+        thecrate, module, ..
+    } = ctx;
+    if returns.len() == 0 {
+        return Ok(TokenStream::new());
+    }
+    let module = module.ok_or("return decoders need the module name")?;
+    let doc = format!(" Decode the return values of a call to `{module}::{ident}`.");
+    let fn_ident = Ident::new(&format!("{ident}_returns"), ident.span());
+
+    let return_types: Vec<_> = returns.map(MaybeRefType::type_).collect();
+    if return_types.iter().any(|ty| has_bare_option(ty, ctx)) {
+        return Ok(TokenStream::new());
+    }
+    let type_params: Vec<_> = generics
+        .into_iter()
+        .flat_map(Generics::generics)
+        .map(|g| &g.ident)
+        .filter(|param| return_types.iter().any(|ty| mentions(ty, param)))
+        .collect();
+    let rust_types: Vec<_> = return_types
+        .iter()
+        .map(|ty| move_type::to_rust_with_substitutions(ty, ctx, &TypeMap::default()))
+        .collect();
+    let where_clause = (!type_params.is_empty()).then(|| {
+        quote! {
+            where #(#rust_types: #thecrate::traits::MoveType + for<'de> #thecrate::serde::Deserialize<'de>),*
+        }
+    });
+    let count = Literal::usize_unsuffixed(rust_types.len());
+    let indices = (0..rust_types.len()).map(Literal::usize_unsuffixed);
+
+    Ok(quote! {
+        #[doc = #doc]
+        #[allow(non_camel_case_types, non_snake_case, clippy::type_complexity)]
+        pub fn #fn_ident<#(#type_params: #thecrate::traits::MoveType),*>(
+            return_values: &[(#thecrate::types::TypeTag, impl ::std::convert::AsRef<[u8]>)],
+        ) -> ::std::result::Result<(#(#rust_types,)*), #thecrate::FromReturnValuesError>
+        #where_clause
+        {
+            #thecrate::check_return_count(return_values, #count)?;
+            Ok((#(#thecrate::parse_return_value(return_values, #indices)?,)*))
+        }
+    })
+}
+
+/// Whether `ty` is or has type parameter `param` among its type arguments.
+fn mentions(ty: &Type, param: &Ident) -> bool {
+    matches!(&ty.path, ItemPath::Ident(ident) if ident == param)
+        || ty
+            .type_args
+            .iter()
+            .flat_map(|args| args.types())
+            .any(|ty| mentions(ty, param))
+}
+
+/// Whether `ty` is or has among its type arguments an unqualified `Option` that isn't declared in
+/// the module.
+fn has_bare_option(ty: &Type, ctx: ItemContext<'_>) -> bool {
+    matches!(&ty.path, ItemPath::Ident(ident) if ident == "Option" && !ctx.datatypes.contains(ident))
+        || ty
+            .type_args
+            .iter()
+            .flat_map(|args| args.types())
+            .any(|ty| has_bare_option(ty, ctx))
+}
+
+/// Whether this is a `&TxContext` or `&mut TxContext`, which is passed implicitly.
+fn is_tx_context(maybe_ref: &MaybeRefType, ctx: ItemContext<'_>) -> bool {
+    let is_ref = maybe_ref.is_ref() || maybe_ref.is_mut_ref();
+    is_ref && Framework::Sui.has_type(maybe_ref.type_(), "tx_context", "TxContext", ctx)
+}
+
+/// Whether values of this type can be passed as pure arguments, even by reference, e.g.,
+/// `vector<u8>`, `&String` or `Option<ID>`.
+fn is_pure(ty: &Type, ctx: ItemContext<'_>) -> bool {
+    let mut type_args = ty.type_args.iter().flat_map(|args| args.types());
+    let mut single_pure_arg = || type_args.next().is_some_and(|ty| is_pure(ty, ctx));
+    if let ItemPath::Ident(ident) = &ty.path {
+        match ident.to_string().as_str() {
+            "bool" | "u8" | "u16" | "u32" | "u64" | "u128" | "u256" | "address" => return true,
+            "vector" => return single_pure_arg(),
+            _ => (),
+        }
+    }
+    if Framework::Std.has_type(ty, "option", "Option", ctx) {
+        return single_pure_arg();
+    }
+    Framework::Std.has_type(ty, "string", "String", ctx)
+        || Framework::Std.has_type(ty, "ascii", "String", ctx)
+        || Framework::Sui.has_type(ty, "object", "ID", ctx)
+}
+
+/// Packages whose datatypes get special treatment in function signatures.
+#[derive(Clone, Copy)]
+enum Framework {
+    /// The Move standard library, at `std` or `0x1`
+    Std,
+    /// The Sui (or IOTA) framework, at `sui`, `iota` or `0x2`
+    Sui,
+}
+
+impl Framework {
+    /// Whether `ty` is the datatype `module::item` of this package: by its full path, or through
+    /// the implicit imports of the module and datatype, unless the module declares a datatype by
+    /// the same name.
+    ///
+    /// NOTE: function signature types are resolved through the explicit imports, so only the
+    /// implicit ones are left unqualified.
+    fn has_type(self, ty: &Type, module: &str, item: &str, ctx: ItemContext<'_>) -> bool {
+        let is_implicit = matches!(
+            (module, item),
+            ("option", "Option") | ("object", "ID") | ("tx_context", "TxContext")
+        );
+        match &ty.path {
+            ItemPath::Full {
+                address,
+                module: module_,
+                item: item_,
+                ..
+            } => self.has_address(address) && module_ == module && item_ == item,
+            ItemPath::Module {
+                module: module_,
+                item: item_,
+                ..
+            } => is_implicit && module_ == module && item_ == item,
+            ItemPath::Ident(ident) => {
+                is_implicit && ident == item && !ctx.datatypes.contains(ident)
+            }
+        }
+    }
+
+    fn has_address(self, address: &Address) -> bool {
+        let (names, number): (&[&str], u8) = match self {
+            Self::Std => (&["std"], 1),
+            Self::Sui => (&["sui", "iota"], 2),
+        };
+        match address {
+            Address::Named(ident) => names.iter().any(|name| ident == name),
+            Address::Numeric(_) => {
+                let mut bytes = [0; 32];
+                bytes[31] = number;
+                address.to_bytes() == Some(bytes)
+            }
+        }
+    }
+}
@@ -1,36 +1,40 @@
-use std::collections::HashMap;
-
 use proc_macro2::{Ident, TokenStream};
 use quote::quote;
 
-use crate::named_fields;
+use crate::{ItemContext, Result, named_fields};
 
 pub(super) trait BracedStructExt {
     /// The contents `{ ... }`, including the braces, for the generated Rust struct.
+    ///
+    /// `ident` is the Move name of the struct, for renaming its fields.
     fn to_rust_contents<'a>(
         &'a self,
         phantoms: impl Iterator<Item = &'a Ident>,
-        address_map: &HashMap<Ident, TokenStream>,
-    ) -> TokenStream;
+        ctx: ItemContext<'_>,
+        ident: &Ident,
+    ) -> Result<TokenStream>;
 
     /// The `pub fn new` implementation for the generated Rust struct.
     fn impl_new<'a>(
         &'a self,
         phantoms: impl Iterator<Item = &'a Ident>,
-        address_map: &HashMap<Ident, TokenStream>,
-    ) -> (TokenStream, TokenStream);
+        ctx: ItemContext<'_>,
+        ident: &Ident,
+    ) -> Result<(TokenStream, TokenStream)>;
 }
 
 impl BracedStructExt for move_syn::BracedStruct {
     fn to_rust_contents<'a>(
         &'a self,
         phantoms: impl Iterator<Item = &'a Ident>,
-        address_map: &HashMap<Ident, TokenStream>,
-    ) -> TokenStream {
+        ctx: ItemContext<'_>,
+        ident: &Ident,
+    ) -> Result<TokenStream> {
         named_fields::to_rust(
             &self.fields,
             phantoms,
-            address_map,
+            ctx,
+            &ident.to_string(),
             true, // visibility
         )
     }
@@ -38,9 +42,12 @@ impl BracedStructExt for move_syn::BracedStruct {
     fn impl_new<'a>(
         &'a self,
         phantoms: impl Iterator<Item = &'a Ident>,
-        address_map: &HashMap<Ident, TokenStream>,
-    ) -> (TokenStream, TokenStream) {
-        let mut move_fields = named_fields::to_rust_fields(&self.fields, address_map).peekable();
+        ctx: ItemContext<'_>,
+        ident: &Ident,
+    ) -> Result<(TokenStream, TokenStream)> {
+        let mut move_fields = named_fields::to_rust_fields(&self.fields, ctx, &ident.to_string())?
+            .into_iter()
+            .peekable();
         let has_fields = move_fields.peek().is_some();
 
         let args: &mut dyn Iterator<Item = TokenStream> = if has_fields {
@@ -73,6 +80,6 @@ impl BracedStructExt for move_syn::BracedStruct {
                 #( #phantom_data ),*
             }
         };
-        (args, assignments)
+        Ok((args, assignments))
     }
 }
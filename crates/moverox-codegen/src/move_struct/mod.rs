@@ -1,9 +1,11 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashSet;
 
 use move_syn::ItemPath;
 use proc_macro2::{Ident, TokenStream};
 use quote::quote;
 
+use crate::attributes::Settings;
+
 mod braced;
 mod tuple;
 
@@ -12,19 +14,24 @@ use tuple::TupleStructExt as _;
 
 use crate::generics::GenericsExt;
 use crate::iter::BoxedIter as _;
-use crate::{ItemContext, Result};
+use crate::{ItemContext, Result, debug, named_fields, positional_fields};
 
 pub(super) trait StructGen {
     /// The full Rust struct declaration, its `new` constructor and potentially its `HasKey`
     /// implementation.
-    fn to_rust(&self, otw_types: HashSet<Ident>, ctx: ItemContext<'_>) -> Result<TokenStream>;
+    fn to_rust(&self, settings: Settings, ctx: ItemContext<'_>) -> Result<TokenStream>;
 }
 
 impl StructGen for move_syn::Struct {
-    fn to_rust(&self, otw_types: HashSet<Ident>, ctx: ItemContext<'_>) -> Result<TokenStream> {
-        let decl = self.rust_declaration(otw_types, ctx)?;
-        let impl_new = self.impl_new(ctx.address_map);
-        let impl_has_key_maybe = self.impl_has_key(ctx.thecrate).unwrap_or_default();
+    fn to_rust(&self, mut settings: Settings, ctx: ItemContext<'_>) -> Result<TokenStream> {
+        let rust_ident = ctx
+            .renamed(&self.ident.to_string(), settings.rename.take())
+            .unwrap_or_else(|| self.ident.clone());
+        let decl = self.rust_declaration(&rust_ident, settings, ctx)?;
+        let impl_new = self.impl_new(&rust_ident, ctx)?;
+        let impl_has_key_maybe = self
+            .impl_has_key(&rust_ident, ctx.thecrate)
+            .unwrap_or_default();
         Ok(quote! {
             #decl
             #impl_new
@@ -33,21 +40,26 @@ impl StructGen for move_syn::Struct {
     }
 }
 
+/// `rust_ident` is the name of the generated Rust struct, which may not be the Move one.
 trait StructExt {
     fn rust_declaration(
         &self,
-        otw_types: HashSet<Ident>,
+        rust_ident: &Ident,
+        settings: Settings,
         ctx: ItemContext<'_>,
     ) -> Result<TokenStream>;
 
     /// The Rust code for the struct's `new` constructor.
-    fn impl_new(&self, address_map: &HashMap<Ident, TokenStream>) -> TokenStream;
+    fn impl_new(&self, rust_ident: &Ident, ctx: ItemContext<'_>) -> Result<TokenStream>;
 
     /// If this is a braced struct and `key` is one of its abilities, then return the
     /// `moverox_traits::HasKey` implementation for it.
-    fn impl_has_key(&self, thecrate: &TokenStream) -> Option<TokenStream>;
+    fn impl_has_key(&self, rust_ident: &Ident, thecrate: &TokenStream) -> Option<TokenStream>;
+
+    /// The fields to format in the `Debug` implementation, if any are left out of it.
+    fn debug_fields(&self, ctx: ItemContext<'_>) -> Result<Option<debug::Fields>>;
 
-    /// Any additional derives to prepend to the standard ones.
+    /// Any additional derive to prepend to the standard ones.
     ///
     /// Currently only `Default` if this struct is empty. Avoids the `clippy::new_without_default`
     /// lint.
@@ -61,8 +73,8 @@ trait StructExt {
     /// Generics for the main struct declaration. These may have defaults set.
     fn type_generics(
         &self,
-        thecrate: &TokenStream,
-        otw_types: HashSet<Ident>,
+        ctx: ItemContext<'_>,
+        type_defaults: Vec<(Ident, move_syn::Type)>,
     ) -> Result<TokenStream>;
 
     /// Identifiers of each phantom type of the struct.
@@ -72,65 +84,61 @@ trait StructExt {
 impl StructExt for move_syn::Struct {
     fn rust_declaration(
         &self,
-        otw_types: HashSet<Ident>,
+        rust_ident: &Ident,
+        settings: Settings,
         ctx: ItemContext<'_>,
     ) -> Result<TokenStream> {
         use move_syn::StructKind as K;
         let Self { ident, kind, .. } = self;
 
-        let extra_attrs: TokenStream = ctx
-            .package
-            .into_iter()
-            .map(unsynn::ToTokens::to_token_stream)
-            .map(|addr| quote!(#[move_(address = #addr)]))
-            .chain(ctx.module.map(|ident| quote!(#[move_(module = #ident)])))
-            .collect();
-        let extra_derives = self.extra_derives().unwrap_or_default();
-        let type_generics = self.type_generics(ctx.thecrate, otw_types)?;
+        let move_name = (rust_ident != ident).then(|| ident.to_string());
+        let move_attrs = ctx.datatype_move_attrs(ident, move_name);
+        let outer_attrs = ctx.datatype_outer_attrs(ident);
+        let extra_attrs = quote!(#move_attrs #outer_attrs);
+        let mut derives = ctx.datatype_derives(ident, self.extra_derives(), &settings.derives);
+        let impl_debug = match self.debug_fields(ctx)? {
+            Some(fields) if debug::remove_derive(&mut derives) => {
+                let body = debug::format(rust_ident, &fields);
+                Some(debug::impl_debug(rust_ident, self.generics.as_ref(), body))
+            }
+            _ => None,
+        };
+        let type_generics = self.type_generics(ctx, settings.type_defaults)?;
         let contents = match kind {
-            K::Braced(braced) => braced.to_rust_contents(self.unused_phantoms(), ctx.address_map),
-            K::Tuple(tuple) => tuple.to_rust_contents(self.unused_phantoms(), ctx.address_map),
+            K::Braced(braced) => braced.to_rust_contents(self.unused_phantoms(), ctx, ident)?,
+            K::Tuple(tuple) => tuple.to_rust_contents(self.unused_phantoms(), ctx)?,
         };
         // NOTE: this has to be formatted as a string first, so that `quote!` will turn it into a
         // string literal later, which is what `#[serde(crate = ...)]` accepts
         let thecrate = ctx.thecrate;
         let serde_crate = format!("{thecrate}::serde").replace(" ", "");
         Ok(quote! {
-            #[derive(
-                #extra_derives
-                Clone,
-                Debug,
-                PartialEq,
-                Eq,
-                Hash,
-                #thecrate::traits::MoveDatatype,
-                #thecrate::serde::Deserialize,
-                #thecrate::serde::Serialize,
-            )]
+            #[derive(#(#derives,)*)]
             #[move_(crate = #thecrate::traits)]
             #[serde(crate = #serde_crate)]
             #extra_attrs
             #[allow(non_snake_case)]
-            pub struct #ident #type_generics #contents
+            pub struct #rust_ident #type_generics #contents
+            #impl_debug
         })
     }
 
-    fn impl_new(&self, address_map: &HashMap<Ident, TokenStream>) -> TokenStream {
+    fn impl_new(&self, rust_ident: &Ident, ctx: ItemContext<'_>) -> Result<TokenStream> {
         use move_syn::StructKind;
         let Self { ident, kind, .. } = self;
         let generics = self.generics();
         let (args, assignments) = match kind {
-            StructKind::Braced(braced) => braced.impl_new(self.unused_phantoms(), address_map),
-            StructKind::Tuple(tuple) => tuple.impl_new(self.unused_phantoms(), address_map),
+            StructKind::Braced(braced) => braced.impl_new(self.unused_phantoms(), ctx, ident)?,
+            StructKind::Tuple(tuple) => tuple.impl_new(self.unused_phantoms(), ctx)?,
         };
-        quote! {
-            impl #generics #ident #generics {
+        Ok(quote! {
+            impl #generics #rust_ident #generics {
                 #[allow(clippy::just_underscores_and_digits, clippy::too_many_arguments)]
                 pub const fn new(#args) -> Self {
                     Self #assignments
                 }
             }
-        }
+        })
     }
 
     /// Impl `moverox_traits::HasKey` if the struct has the 'key' ability.
@@ -143,15 +151,14 @@ impl StructExt for move_syn::Struct {
     /// - `ID` has a `bytes: address` field
     ///
     /// Hence this generates code to return that innermost `bytes` field.
-    fn impl_has_key(&self, thecrate: &TokenStream) -> Option<TokenStream> {
+    fn impl_has_key(&self, rust_ident: &Ident, thecrate: &TokenStream) -> Option<TokenStream> {
         use move_syn::Ability;
         if !self.abilities().any(|a| matches!(a, Ability::Key(_))) {
             return None;
         }
-        let ident = &self.ident;
         let generics = self.generics();
         Some(quote! {
-            impl #generics #thecrate::traits::HasKey for  #ident #generics {
+            impl #generics #thecrate::traits::HasKey for  #rust_ident #generics {
                 fn address(&self) -> #thecrate::types::Address {
                     self.id.id.bytes
                 }
@@ -159,13 +166,40 @@ impl StructExt for move_syn::Struct {
         })
     }
 
+    fn debug_fields(&self, ctx: ItemContext<'_>) -> Result<Option<debug::Fields>> {
+        use move_syn::StructKind;
+        let fields = match &self.kind {
+            StructKind::Braced(braced) => debug::Fields::Named(
+                named_fields::to_rust_fields(&braced.fields, ctx, &self.ident.to_string())?
+                    .into_iter()
+                    .map(|field| {
+                        let ident = field.ident.into_owned();
+                        let value = (!field.skip_debug).then(|| quote!(&self.#ident));
+                        (ident, value)
+                    })
+                    .collect(),
+            ),
+            StructKind::Tuple(tuple) => debug::Fields::Positional(
+                positional_fields::to_rust_fields(&tuple.fields, ctx)?
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, field)| {
+                        let index = proc_macro2::Literal::usize_unsuffixed(i);
+                        (!field.skip_debug).then(|| quote!(&self.#index))
+                    })
+                    .collect(),
+            ),
+        };
+        Ok(fields.any_skipped().then_some(fields))
+    }
+
     fn extra_derives(&self) -> Option<TokenStream> {
         use move_syn::StructKind;
         let is_empty = match &self.kind {
             StructKind::Braced(braced) => braced.is_empty(),
             StructKind::Tuple(tuple) => tuple.is_empty(),
         };
-        is_empty.then_some(quote!(Default,))
+        is_empty.then_some(quote!(Default))
     }
 
     fn generics(&self) -> TokenStream {
@@ -177,12 +211,12 @@ impl StructExt for move_syn::Struct {
 
     fn type_generics(
         &self,
-        thecrate: &TokenStream,
-        otw_types: HashSet<Ident>,
+        ctx: ItemContext<'_>,
+        type_defaults: Vec<(Ident, move_syn::Type)>,
     ) -> Result<TokenStream> {
         self.generics
             .as_ref()
-            .map(|g| g.type_generics(thecrate, otw_types))
+            .map(|g| g.type_generics(ctx, &self.ident, type_defaults))
             .transpose()
             .map(Option::unwrap_or_default)
     }
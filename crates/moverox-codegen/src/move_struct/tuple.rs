@@ -1,58 +1,56 @@
-use std::collections::HashMap;
-
 use proc_macro2::{Ident, Span, TokenStream};
 use quote::quote;
 use unsynn::ToTokens as _;
 
-use crate::move_type;
+use crate::{ItemContext, Result, positional_fields};
 
 pub(super) trait TupleStructExt {
     /// The contents `( ... )`, including the parenthesis, for the generated Rust struct.
     fn to_rust_contents<'a>(
         &'a self,
         phantoms: impl Iterator<Item = &'a Ident>,
-        address_map: &HashMap<Ident, TokenStream>,
-    ) -> TokenStream;
+        ctx: ItemContext<'_>,
+    ) -> Result<TokenStream>;
 
     /// The `pub fn new` implementation for the generated Rust struct.
     fn impl_new<'a>(
         &'a self,
         phantoms: impl Iterator<Item = &'a Ident>,
-        address_map: &HashMap<Ident, TokenStream>,
-    ) -> (TokenStream, TokenStream);
+        ctx: ItemContext<'_>,
+    ) -> Result<(TokenStream, TokenStream)>;
 }
 
 impl TupleStructExt for move_syn::TupleStruct {
     fn to_rust_contents<'a>(
         &'a self,
         phantoms: impl Iterator<Item = &'a Ident>,
-        address_map: &HashMap<Ident, TokenStream>,
-    ) -> TokenStream {
-        let parenthesized_fields = crate::positional_fields::to_rust(
+        ctx: ItemContext<'_>,
+    ) -> Result<TokenStream> {
+        let parenthesized_fields = positional_fields::to_rust(
             &self.fields,
             phantoms,
-            address_map,
+            ctx,
             true, // bool_if_empty
             true, // visibility
-        );
+        )?;
 
-        quote! {
+        Ok(quote! {
             #parenthesized_fields;
-        }
+        })
     }
 
     fn impl_new<'a>(
         &'a self,
         phantoms: impl Iterator<Item = &'a Ident>,
-        address_map: &HashMap<Ident, TokenStream>,
-    ) -> (TokenStream, TokenStream) {
-        let move_fields = self.fields();
+        ctx: ItemContext<'_>,
+    ) -> Result<(TokenStream, TokenStream)> {
+        let move_fields = positional_fields::to_rust_fields(&self.fields, ctx)?;
 
         let args: &mut dyn Iterator<Item = TokenStream> = if self.is_empty() {
             &mut std::iter::empty()
         } else {
-            &mut move_fields.clone().enumerate().map(|(i, d)| {
-                let ty = move_type::to_rust_with_substitutions(&d.ty, address_map);
+            &mut move_fields.iter().enumerate().map(|(i, field)| {
+                let ty = &field.ty;
                 let ident = Ident::new(&format!("_{i}"), Span::call_site());
                 quote! (#ident: #ty)
             })
@@ -62,6 +60,7 @@ impl TupleStructExt for move_syn::TupleStruct {
             &mut std::iter::once(quote!(false))
         } else {
             &mut move_fields
+                .iter()
                 .enumerate()
                 .map(|(i, _)| Ident::new(&format!("_{i}"), Span::call_site()).to_token_stream())
         };
@@ -75,6 +74,6 @@ impl TupleStructExt for move_syn::TupleStruct {
                 #( #phantom_data ),*
             )
         };
-        (args, assignments)
+        Ok((args, assignments))
     }
 }
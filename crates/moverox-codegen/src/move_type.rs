@@ -1,15 +1,21 @@
-use std::collections::HashMap;
-
-use move_syn::{ItemPath, Type};
-use proc_macro2::{Ident, TokenStream};
+use move_syn::{Address, ItemPath, Type};
+use proc_macro2::TokenStream;
 use quote::{ToTokens, quote};
 use unsynn::ToTokens as _;
 
-/// Generate Rust-equivalent type, substituting Move named addresses with Rust paths to oxidized
-/// Move packages using `address_map`.
+use crate::attributes::FieldSettings;
+use crate::{ItemContext, TypeMap};
+
+/// Generate Rust-equivalent type, substituting Move addresses with Rust paths to oxidized Move
+/// packages using the context's address map, Move datatypes with custom Rust types using
+/// `type_map` and renamed datatypes with their Rust names.
+///
+/// Unmapped named addresses are kept as is, while unmapped numerical addresses become a
+/// `compile_error!` since they aren't valid Rust paths.
 pub(super) fn to_rust_with_substitutions(
     this: &Type,
-    address_map: &HashMap<Ident, TokenStream>,
+    ctx: ItemContext<'_>,
+    type_map: &TypeMap,
 ) -> TokenStream {
     let type_args = this
         .type_args
@@ -17,26 +23,82 @@ pub(super) fn to_rust_with_substitutions(
         .map_or_else(TokenStream::new, |type_args| {
             let types = type_args
                 .types()
-                .map(|ty| to_rust_with_substitutions(ty, address_map));
+                .map(|ty| to_rust_with_substitutions(ty, ctx, type_map));
             quote!(<#(#types),*>)
         });
 
-    let path = if let ItemPath::Full {
-        named_address,
-        module,
-        item: type_,
-        ..
-    } = &this.path
-    {
-        let prefix: &dyn ToTokens = address_map
-            .get(named_address)
-            .map_or(named_address, |path| path);
-        quote!(#prefix::#module::#type_)
-    } else {
-        this.path.to_token_stream()
+    let path = match &this.path {
+        ItemPath::Full {
+            address,
+            module,
+            item: type_,
+            ..
+        } => {
+            if let Some(mapping) = type_map.get(address, module, type_) {
+                let rust_type = &mapping.rust_type;
+                return quote!(#rust_type #type_args);
+            }
+            let prefix: &dyn ToTokens = match (ctx.address_map.get(address), address) {
+                (Some(path), _) => path,
+                (None, Address::Named(named)) => named,
+                (None, Address::Numeric(number)) => {
+                    let msg = format!("Move address `{number}` is not mapped to a Rust path");
+                    return quote!(::core::compile_error!(#msg));
+                }
+            };
+            let is_sibling = prefix.to_token_stream().to_string() == "super";
+            let type_ = is_sibling
+                .then(|| ctx.options.renames.get(&format!("{module}::{type_}")))
+                .flatten()
+                .unwrap_or(type_);
+            quote!(#prefix::#module::#type_)
+        }
+        ItemPath::Ident(ident) => {
+            let ident = ctx
+                .renamed(&ident.to_string(), None)
+                .unwrap_or_else(|| ident.clone());
+            quote!(#ident)
+        }
+        path => path.to_token_stream(),
     };
 
     quote! {
         #path #type_args
     }
 }
+
+/// `#[serde(with = ...)]` for a field of type `this`, if it's mapped to a Rust type with a serde
+/// adapter in `type_map`.
+pub(super) fn serde_with(this: &Type, type_map: &TypeMap) -> Option<TokenStream> {
+    let ItemPath::Full {
+        address,
+        module,
+        item,
+        ..
+    } = &this.path
+    else {
+        return None;
+    };
+    let adapter = type_map.get(address, module, item)?.serde_with.as_ref()?;
+    Some(quote!(#[serde(with = #adapter)]))
+}
+
+/// Rust type of a field of type `this` and its `#[serde(with = ...)]` attribute, if any, from the
+/// field's `settings` or else the context's type map.
+pub(super) fn field_to_rust(
+    this: &Type,
+    settings: &FieldSettings,
+    ctx: ItemContext<'_>,
+) -> (TokenStream, Option<TokenStream>) {
+    let type_map = &ctx.options.type_map;
+    let serde_with = match &settings.serde_with {
+        Some(adapter) => Some(quote!(#[serde(with = #adapter)])),
+        None if settings.rust_type.is_some() => None,
+        None => serde_with(this, type_map),
+    };
+    let ty = settings
+        .rust_type
+        .clone()
+        .unwrap_or_else(|| to_rust_with_substitutions(this, ctx, type_map));
+    (ty, serde_with)
+}
@@ -1,10 +1,9 @@
 use std::borrow::Cow;
-use std::collections::HashMap;
 
 use quote::quote;
 use unsynn::{Ident, ToTokens as _, TokenStream};
 
-use crate::move_type;
+use crate::{ItemContext, Result, move_type};
 
 /// Move named field converted to Rust.
 #[derive(Clone)]
@@ -12,18 +11,23 @@ pub(super) struct Rust<'a> {
     pub(super) attrs: TokenStream,
     pub(super) ident: Cow<'a, Ident>,
     pub(super) ty: TokenStream,
+    /// Whether it's left out of the `Debug` output.
+    pub(super) skip_debug: bool,
 }
 
 /// `{ name: T, .. }` in Move to Rust.
 ///
-/// `visibility` controls whether a `pub` visibility modifier is added to the field.
+/// `parent` is the Move path of the fields' datatype or enum variant in the module, e.g.,
+/// `Datatype` or `Enum::Variant`, for renaming them. `visibility` controls whether a `pub`
+/// visibility modifier is added to the field.
 pub(super) fn to_rust<'a>(
     this: &'a move_syn::NamedFields,
     phantoms: impl Iterator<Item = &'a Ident>,
-    address_map: &HashMap<Ident, TokenStream>,
+    ctx: ItemContext<'_>,
+    parent: &str,
     visibility: bool,
-) -> TokenStream {
-    let mut move_fields = to_rust_fields(this, address_map);
+) -> Result<TokenStream> {
+    let mut move_fields = to_rust_fields(this, ctx, parent)?.into_iter();
 
     let phantom_data = phantoms.map(|ty| {
         let field = Ident::new(&format!("_{ty}"), ty.span());
@@ -36,14 +40,18 @@ pub(super) fn to_rust<'a>(
     let rs_fields: &mut dyn Iterator<Item = TokenStream> = if let Some(field) = move_fields.next() {
         &mut std::iter::once(field)
             .chain(move_fields)
-            .map(|Rust { attrs, ident, ty }| {
-                #[expect(clippy::obfuscated_if_else)]
-                let vis = visibility.then(|| quote!(pub)).unwrap_or_default();
-                quote! {
-                    #attrs
-                    #vis #ident: #ty
-                }
-            })
+            .map(
+                |Rust {
+                     attrs, ident, ty, ..
+                 }| {
+                    #[expect(clippy::obfuscated_if_else)]
+                    let vis = visibility.then(|| quote!(pub)).unwrap_or_default();
+                    quote! {
+                        #attrs
+                        #vis #ident: #ty
+                    }
+                },
+            )
             .chain(phantom_data)
     } else {
         &mut std::iter::once(quote! {
@@ -53,33 +61,52 @@ pub(super) fn to_rust<'a>(
         .chain(phantom_data)
     };
 
-    quote! {
+    Ok(quote! {
         { #(#rs_fields),* }
-    }
+    })
 }
 
+/// The fields of `this` in Rust; see [`to_rust`] for `parent`.
 pub(super) fn to_rust_fields<'a>(
     this: &'a move_syn::NamedFields,
-    address_map: &HashMap<Ident, TokenStream>,
-) -> impl Iterator<Item = Rust<'a>> + Clone {
-    this.fields().map(
-        |move_syn::NamedField {
-             attrs, ident, ty, ..
-         }| {
-            Rust {
-                attrs: attrs
-                    .iter()
-                    .filter(|attr| attr.is_doc())
-                    .map(|attr| attr.to_token_stream())
-                    .collect(),
-                ident: sanitize_ident(ident),
-                ty: move_type::to_rust_with_substitutions(ty, address_map),
-            }
-        },
-    )
+    ctx: ItemContext<'_>,
+    parent: &str,
+) -> Result<Vec<Rust<'a>>> {
+    this.fields()
+        .map(
+            |move_syn::NamedField {
+                 attrs, ident, ty, ..
+             }| {
+                let err_ctx = |err| format!("field {ident}: {err}");
+                let mut settings = crate::attributes::field_settings(attrs).map_err(err_ctx)?;
+                let rust_ident = ctx.renamed(&format!("{parent}::{ident}"), settings.rename.take());
+                let serde_rename = rust_ident.is_some().then(|| {
+                    let name = ident.to_string();
+                    quote!(#[serde(rename = #name)])
+                });
+                let (ty, serde_with) = move_type::field_to_rust(ty, &settings, ctx);
+                let docs = settings.docs.unwrap_or_else(|| {
+                    attrs
+                        .iter()
+                        .filter(|attr| attr.is_doc())
+                        .map(|attr| attr.to_token_stream())
+                        .collect()
+                });
+                Ok(Rust {
+                    attrs: std::iter::once(docs)
+                        .chain(serde_rename)
+                        .chain(serde_with)
+                        .collect(),
+                    ident: rust_ident.map_or_else(|| sanitize_ident(ident), Cow::Owned),
+                    ty,
+                    skip_debug: settings.skip_debug,
+                })
+            },
+        )
+        .collect()
 }
 
-fn sanitize_ident(ident: &Ident) -> Cow<'_, Ident> {
+pub(super) fn sanitize_ident(ident: &Ident) -> Cow<'_, Ident> {
     let ident_str = ident.to_string();
     // https://doc.rust-lang.org/reference/keywords.html
     match ident_str.as_str() {
@@ -1,9 +1,15 @@
-use std::collections::HashMap;
-
 use quote::quote;
 use unsynn::{Ident, ToTokens as _, TokenStream};
 
-use crate::move_type;
+use crate::{ItemContext, Result, move_type};
+
+/// Move positional field converted to Rust.
+pub(super) struct Rust {
+    pub(super) attrs: TokenStream,
+    pub(super) ty: TokenStream,
+    /// Whether it's left out of the `Debug` output.
+    pub(super) skip_debug: bool,
+}
 
 /// Transform `(T, U, V)` in Move to the equivalent in Rust.
 ///
@@ -12,11 +18,11 @@ use crate::move_type;
 pub(super) fn to_rust<'a>(
     this: &move_syn::PositionalFields,
     phantoms: impl Iterator<Item = &'a Ident>,
-    address_map: &HashMap<Ident, TokenStream>,
+    ctx: ItemContext<'_>,
     bool_if_empty: bool,
     visibility: bool,
-) -> TokenStream {
-    let move_fields = this.fields();
+) -> Result<TokenStream> {
+    let move_fields = to_rust_fields(this, ctx)?;
 
     let phantom_data = phantoms.map(|ty| {
         quote! {
@@ -29,9 +35,8 @@ pub(super) fn to_rust<'a>(
         &mut std::iter::once(quote!(bool)).chain(phantom_data)
     } else {
         &mut move_fields
-            .map(|d| {
-                let attrs = d.attrs.to_token_stream();
-                let ty = move_type::to_rust_with_substitutions(&d.ty, address_map);
+            .into_iter()
+            .map(|Rust { attrs, ty, .. }| {
                 #[expect(clippy::obfuscated_if_else)]
                 let vis = visibility.then(|| quote!(pub)).unwrap_or_default();
                 quote! {
@@ -41,7 +46,38 @@ pub(super) fn to_rust<'a>(
             .chain(phantom_data)
     };
 
-    quote! {
+    Ok(quote! {
         ( #(#rs_fields),* )
-    }
+    })
+}
+
+/// The fields of `this` in Rust.
+pub(super) fn to_rust_fields(
+    this: &move_syn::PositionalFields,
+    ctx: ItemContext<'_>,
+) -> Result<Vec<Rust>> {
+    this.fields()
+        .enumerate()
+        .map(|(i, move_syn::UnnamedField { attrs, ty, .. })| {
+            let settings = crate::attributes::field_settings(attrs)
+                .and_then(|settings| match settings.rename {
+                    Some(_) => Err("`rename` can't be set on positional fields".into()),
+                    None => Ok(settings),
+                })
+                .map_err(|err| format!("field {i}: {err}"))?;
+            let (ty, serde_with) = move_type::field_to_rust(ty, &settings, ctx);
+            let docs = settings.docs.unwrap_or_else(|| {
+                attrs
+                    .iter()
+                    .filter(|attr| attr.is_doc())
+                    .map(|attr| attr.to_token_stream())
+                    .collect()
+            });
+            Ok(Rust {
+                attrs: quote!(#docs #serde_with),
+                ty,
+                skip_debug: settings.skip_debug,
+            })
+        })
+        .collect()
 }
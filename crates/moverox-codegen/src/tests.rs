@@ -7,8 +7,13 @@ use unsynn::{IParse as _, ToTokens as _};
 use crate::*;
 
 fn from_module(s: &str) -> impl Display {
-    let module: Module = s.to_token_iter().parse_all().unwrap();
-    let result = module.to_rust(&quote!(::moverox), None, &Default::default());
+    from_module_with_options(s, None, &Options::default())
+}
+
+fn from_module_with_options(s: &str, package: Option<&LiteralString>, options: &Options) -> String {
+    let mut module: Module = s.to_token_iter().parse_all().unwrap();
+    module.fully_qualify_fun_signature_types();
+    let result = module.to_rust(&quote!(::moverox), package, &Default::default(), options);
     let rust_code = match result {
         Ok(tokens) => tokens.to_string(),
         Err(err) => return format!("{err:#}"),
@@ -29,7 +34,9 @@ fn from_struct(s: &str) -> impl Display {
         thecrate: &quote!(::moverox),
         package: None,
         module: None,
+        datatypes: &[],
         address_map: &Default::default(),
+        options: &Default::default(),
     };
     let result = ast.to_rust(ctx);
     let rust_code = match result {
@@ -39,6 +46,20 @@ fn from_struct(s: &str) -> impl Display {
     prettyplease::unparse(&syn::parse_file(&rust_code).unwrap())
 }
 
+fn from_struct_with_addresses(s: &str, address_map: &AddressMap) -> impl Display {
+    let ast: Item = s.to_token_iter().parse_all().unwrap();
+    let ctx = ItemContext {
+        thecrate: &quote!(::moverox),
+        package: None,
+        module: None,
+        datatypes: &[],
+        address_map,
+        options: &Default::default(),
+    };
+    let rust_code = ast.to_rust(ctx).unwrap().to_string();
+    prettyplease::unparse(&syn::parse_file(&rust_code).unwrap())
+}
+
 fn from_enum(s: &str) -> impl Display {
     let ast: Item = s.to_token_iter().parse_all().unwrap();
     assert!(matches!(
@@ -52,7 +73,9 @@ fn from_enum(s: &str) -> impl Display {
         thecrate: &quote!(::moverox),
         package: None,
         module: None,
+        datatypes: &[],
         address_map: &Default::default(),
+        options: &Default::default(),
     };
     let result = ast.to_rust(ctx);
     let rust_code = match result {
@@ -951,3 +974,1716 @@ fn module_with_annotations() {
     "};
     insta::assert_snapshot!(from_module(move_module), @"Move modules cannot have custom `moverox` attributes");
 }
+
+#[test]
+fn numeric_address_paths() {
+    let move_struct = indoc! {"
+        public struct Vault<phantom T> has key {
+            id: 0x2::object::UID,
+            balance: 0x0000000000000000000000000000000000000000000000000000000000000002::balance::Balance<T>,
+            coin: sui::coin::Coin<0x1::string::String>,
+        }
+    "};
+    let mut address_map = AddressMap::new();
+    address_map.insert_named(quote::format_ident!("sui"), quote!(::sui_rs));
+    let mut sui = [0; 32];
+    sui[31] = 2;
+    address_map.insert_numeric(sui, quote!(::sui_rs));
+    insta::assert_snapshot!(from_struct_with_addresses(move_struct, &address_map), @r#"
+    #[derive(
+        Clone,
+        Debug,
+        PartialEq,
+        Eq,
+        Hash,
+        ::moverox::traits::MoveDatatype,
+        ::moverox::serde::Deserialize,
+        ::moverox::serde::Serialize,
+    )]
+    #[move_(crate = ::moverox::traits)]
+    #[serde(crate = "::moverox::serde")]
+    #[allow(non_snake_case)]
+    pub struct Vault<T> {
+        pub id: ::sui_rs::object::UID,
+        pub balance: ::sui_rs::balance::Balance<T>,
+        pub coin: ::sui_rs::coin::Coin<
+            ::core::compile_error!("Move address `0x1` is not mapped to a Rust path"),
+        >,
+    }
+    impl<T> Vault<T> {
+        #[allow(clippy::just_underscores_and_digits, clippy::too_many_arguments)]
+        pub const fn new(
+            id: ::sui_rs::object::UID,
+            balance: ::sui_rs::balance::Balance<T>,
+            coin: ::sui_rs::coin::Coin<
+                ::core::compile_error!("Move address `0x1` is not mapped to a Rust path"),
+            >,
+        ) -> Self {
+            Self { id, balance, coin }
+        }
+    }
+    impl<T> ::moverox::traits::HasKey for Vault<T> {
+        fn address(&self) -> ::moverox::types::Address {
+            self.id.id.bytes
+        }
+    }
+    "#);
+}
+
+#[test]
+fn module_with_constants() {
+    let move_module = indoc! {r#"
+    module package::config {
+        /// Maximum fee, in basis points.
+        const MAX_FEE_BPS: u64 = 10_000u64;
+        const ENABLED: bool = false;
+        const ADMIN: address = @0x2;
+        const BIG: u256 = 0x1_0000_0000_0000_0000_0000_0000_0000_0000;
+        const DOMAIN: vector<u8> = b"config";
+        const MAGIC: vector<u8> = x"00ff";
+        const NAMES: vector<vector<u8>> = vector[b"a", x"62", vector[]];
+        const DERIVED: u64 = MAX_FEE_BPS / 2;
+    }
+    "#};
+    insta::assert_snapshot!(from_module(move_module), @r#"
+    #[allow(rustdoc::all, clippy::too_long_first_doc_paragraph)]
+    pub mod config {
+        #[allow(non_camel_case_types, unused)]
+        type address = ::moverox::types::Address;
+        #[allow(non_camel_case_types, unused)]
+        type u256 = ::moverox::types::U256;
+        #[allow(non_camel_case_types, unused)]
+        type vector<T> = ::std::vec::Vec<T>;
+        #[cfg_attr(not(doctest), doc = " Maximum fee, in basis points.")]
+        #[allow(non_upper_case_globals)]
+        pub const MAX_FEE_BPS: u64 = 10_000;
+        #[allow(non_upper_case_globals)]
+        pub const ENABLED: bool = false;
+        #[allow(non_upper_case_globals)]
+        pub const ADMIN: ::moverox::types::Address = ::moverox::types::const_address(b"0x2");
+        #[allow(non_upper_case_globals)]
+        pub const BIG: ::moverox::types::U256 = ::moverox::types::U256::from_be_bytes(
+            &[
+                0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+                0, 0, 0, 0, 0, 0,
+            ],
+        );
+        #[allow(non_upper_case_globals)]
+        pub const DOMAIN: &[u8] = b"config";
+        #[allow(non_upper_case_globals)]
+        pub const MAGIC: &[u8] = &[0, 255];
+        #[allow(non_upper_case_globals)]
+        pub const NAMES: &[&[u8]] = &[b"a", &[98], &[]];
+    }
+    "#);
+}
+
+#[test]
+fn constant_with_mismatched_value() {
+    let move_module = indoc! {"
+    module package::config {
+        const ENABLED: bool = 1;
+    }
+    "};
+    insta::assert_snapshot!(from_module(move_module), @"const ENABLED: unexpected value 1");
+}
+
+#[test]
+fn constant_out_of_range() {
+    let move_module = indoc! {"
+    module package::config {
+        const MAX: u8 = 256;
+    }
+    "};
+    insta::assert_snapshot!(from_module(move_module), @"const MAX: 256 is out of range for u8");
+
+    let move_module = indoc! {"
+    module package::config {
+        const MASKS: vector<u16> = vector[0xffff, 0x1_0000];
+    }
+    "};
+    insta::assert_snapshot!(from_module(move_module), @"const MASKS: 0x1_0000 is out of range for u16");
+}
+
+#[test]
+fn constant_with_named_address() {
+    let move_module = indoc! {"
+    module package::config {
+        const ADMIN: address = @package;
+        const MAX: u64 = 18_446_744_073_709_551_615;
+    }
+    "};
+    // The value of a named address isn't known, so `ADMIN` is skipped
+    insta::assert_snapshot!(from_module(move_module), @r#"
+    #[allow(rustdoc::all, clippy::too_long_first_doc_paragraph)]
+    pub mod config {
+        #[allow(non_camel_case_types, unused)]
+        type address = ::moverox::types::Address;
+        #[allow(non_camel_case_types, unused)]
+        type u256 = ::moverox::types::U256;
+        #[allow(non_camel_case_types, unused)]
+        type vector<T> = ::std::vec::Vec<T>;
+        #[allow(non_upper_case_globals)]
+        pub const MAX: u64 = 18_446_744_073_709_551_615;
+    }
+    "#);
+}
+
+#[test]
+fn module_with_abort_codes() {
+    let move_module = indoc! {"
+    module package::vault {
+        /// Not enough funds in the vault.
+        const ENotEnough: u64 = 0;
+        /// Only the owner can withdraw.
+        const E_NOT_OWNER: u64 = 0x1;
+        const EAlias: u64 = 1;
+        const EPSILON: u8 = 1;
+        const MAX: u64 = 2;
+    }
+    "};
+    insta::assert_snapshot!(from_module(move_module), @r#"
+    #[allow(rustdoc::all, clippy::too_long_first_doc_paragraph)]
+    pub mod vault {
+        #[allow(non_camel_case_types, unused)]
+        type address = ::moverox::types::Address;
+        #[allow(non_camel_case_types, unused)]
+        type u256 = ::moverox::types::U256;
+        #[allow(non_camel_case_types, unused)]
+        type vector<T> = ::std::vec::Vec<T>;
+        #[cfg_attr(not(doctest), doc = " Not enough funds in the vault.")]
+        #[allow(non_upper_case_globals)]
+        pub const ENotEnough: u64 = 0;
+        #[cfg_attr(not(doctest), doc = " Only the owner can withdraw.")]
+        #[allow(non_upper_case_globals)]
+        pub const E_NOT_OWNER: u64 = 0x1;
+        #[allow(non_upper_case_globals)]
+        pub const EAlias: u64 = 1;
+        #[allow(non_upper_case_globals)]
+        pub const EPSILON: u8 = 1;
+        #[allow(non_upper_case_globals)]
+        pub const MAX: u64 = 2;
+        /// Abort codes of the `vault` module, from its `E*` constants.
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        #[allow(non_camel_case_types)]
+        pub enum Error {
+            #[cfg_attr(not(doctest), doc = " Not enough funds in the vault.")]
+            ENotEnough,
+            #[cfg_attr(not(doctest), doc = " Only the owner can withdraw.")]
+            E_NOT_OWNER,
+            EAlias,
+        }
+        impl Error {
+            /// The abort code.
+            pub const fn code(self) -> u64 {
+                match self {
+                    Self::ENotEnough => 0,
+                    Self::E_NOT_OWNER => 1,
+                    Self::EAlias => 1,
+                }
+            }
+            /// Name of the constant declaring the abort code.
+            pub const fn name(self) -> &'static str {
+                match self {
+                    Self::ENotEnough => "ENotEnough",
+                    Self::E_NOT_OWNER => "E_NOT_OWNER",
+                    Self::EAlias => "EAlias",
+                }
+            }
+            /// Documentation of the constant declaring the abort code, or its name if undocumented.
+            pub const fn message(self) -> &'static str {
+                match self {
+                    Self::ENotEnough => "Not enough funds in the vault.",
+                    Self::E_NOT_OWNER => "Only the owner can withdraw.",
+                    Self::EAlias => "EAlias",
+                }
+            }
+        }
+        impl ::std::convert::TryFrom<u64> for Error {
+            type Error = u64;
+            fn try_from(code: u64) -> ::std::result::Result<Self, u64> {
+                match code {
+                    0 => Ok(Self::ENotEnough),
+                    1 => Ok(Self::E_NOT_OWNER),
+                    other => Err(other),
+                }
+            }
+        }
+        impl ::std::fmt::Display for Error {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                f.write_str(self.message())
+            }
+        }
+        impl ::std::error::Error for Error {}
+    }
+    "#);
+}
+
+#[test]
+fn abort_codes_clashing_with_datatypes() {
+    let move_module = indoc! {"
+    module package::vault {
+        public struct Error has drop {}
+
+        const ENotEnough: u64 = 0;
+    }
+    "};
+    insta::assert_snapshot!(from_module(move_module), @r#"
+    datatype Error: clashes with the `Error` enum of the module's abort codes; rename it with `#[ext(moverox(rename = "..."))]`
+    "#);
+
+    // Unless renamed
+    let move_module = indoc! {r#"
+    module package::vault {
+        #[ext(moverox(rename = "VaultError"))]
+        public struct Error has drop {}
+
+        const ENotEnough: u64 = 0;
+    }
+    "#};
+    let rust_code = from_module_with_options(move_module, None, &Options::default());
+    assert!(rust_code.contains("pub struct VaultError"));
+}
+
+#[test]
+fn module_with_clever_errors() {
+    let move_module = indoc! {r#"
+    module package::vault {
+        const SCALE: u64 = 1_000;
+        #[error]
+        const ENotEnough: vector<u8> = b"Not enough funds";
+        #[test_only]
+        const ETest: vector<u8> = b"Only in tests";
+        #[error(code = 3)]
+        const EEmpty: vector<u8> = b"Not enough funds";
+        const THOUSAND: u64 = 1000u64;
+        #[error]
+        const EHex: vector<u8> = x"4f7574";
+        const SHIFTED: u64 = 1 << 8;
+        #[error]
+        const EUnindexed: vector<u8> = b"Unknown index";
+    }
+    "#};
+    insta::assert_snapshot!(from_module(move_module), @r#"
+    #[allow(rustdoc::all, clippy::too_long_first_doc_paragraph)]
+    pub mod vault {
+        #[allow(non_camel_case_types, unused)]
+        type address = ::moverox::types::Address;
+        #[allow(non_camel_case_types, unused)]
+        type u256 = ::moverox::types::U256;
+        #[allow(non_camel_case_types, unused)]
+        type vector<T> = ::std::vec::Vec<T>;
+        #[allow(non_upper_case_globals)]
+        pub const SCALE: u64 = 1_000;
+        #[allow(non_upper_case_globals)]
+        pub const ENotEnough: &[u8] = b"Not enough funds";
+        #[allow(non_upper_case_globals)]
+        pub const EEmpty: &[u8] = b"Not enough funds";
+        #[allow(non_upper_case_globals)]
+        pub const THOUSAND: u64 = 1000;
+        #[allow(non_upper_case_globals)]
+        pub const EHex: &[u8] = &[79, 117, 116];
+        #[allow(non_upper_case_globals)]
+        pub const EUnindexed: &[u8] = b"Unknown index";
+        /// The `#[error]` constants of the `vault` module, for decoding its clever errors.
+        pub const CLEVER_ERRORS: &[::moverox::ErrorConstant] = &[
+            ::moverox::ErrorConstant::new("ENotEnough", "Not enough funds", 1),
+            ::moverox::ErrorConstant::new("EEmpty", "Not enough funds", 1).with_code(3),
+            ::moverox::ErrorConstant::new("EHex", "Out", 2),
+        ];
+    }
+    "#);
+}
+
+#[test]
+fn package_clever_errors() {
+    let modules: Vec<ModuleSummary> = [
+        r#"module package::a { #[error] const EA: vector<u8> = b"A"; }"#,
+        "module package::b { const EB: u64 = 0; }",
+    ]
+    .into_iter()
+    .map(|s| ModuleSummary::new(&s.to_token_iter().parse_all().unwrap()).unwrap())
+    .collect();
+    let rust_code = clever_errors(&modules, &quote!(::moverox), None)
+        .unwrap()
+        .to_string();
+    insta::assert_snapshot!(prettyplease::unparse(&syn::parse_file(&rust_code).unwrap()), @r#"
+    /// Decode a Move abort with `code` in `module` at `address` as a clever error, i.e., one
+    /// from a constant annotated `#[error]`.
+    ///
+    /// The package address wasn't known when generating this code, so `address` is ignored.
+    #[allow(unused_variables)]
+    pub fn clever_error(
+        address: &::moverox::types::Address,
+        module: &str,
+        code: u64,
+    ) -> Option<::moverox::CleverError> {
+        let abort = ::moverox::CleverAbort::from_abort_code(code)?;
+        match module {
+            "a" => abort.resolve("a", a::CLEVER_ERRORS),
+            _ => None,
+        }
+    }
+    "#);
+}
+
+#[test]
+fn package_events() {
+    let modules: Vec<ModuleSummary> = [
+        indoc! {"
+        module package::a {
+            public struct Deposited has copy, drop { amount: u64 }
+            public struct Config has copy, drop, store { fee: u64 }
+            public struct Vault has key { id: UID }
+            public struct Wrapped<T> has copy, drop { value: T }
+        }
+        "},
+        indoc! {"
+        module package::b {
+            #[ext(moverox(event))]
+            public struct Withdrawn { amount: u64 }
+        }
+        "},
+    ]
+    .into_iter()
+    .map(|s| ModuleSummary::new(&s.to_token_iter().parse_all().unwrap()).unwrap())
+    .collect();
+    let package = LiteralString::from_str("0x2");
+    let rust_code = events(
+        &modules,
+        &quote!(::moverox),
+        Some(&package),
+        &HashMap::new(),
+        &HashMap::new(),
+    )
+    .unwrap()
+    .to_string();
+    insta::assert_snapshot!(prettyplease::unparse(&syn::parse_file(&rust_code).unwrap()), @r#"
+    /// Events of this package's modules, i.e., structs with `copy` and `drop` or annotated with
+    /// `#[ext(moverox(event))]`.
+    #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+    #[allow(non_camel_case_types)]
+    pub enum Event {
+        a_Deposited(a::Deposited),
+        a_Config(a::Config),
+        b_Withdrawn(b::Withdrawn),
+    }
+    impl Event {
+        /// Decode an event from its struct tag and BCS bytes.
+        ///
+        /// The decoder is looked up by the module and name in the struct tag, which is then
+        /// checked against the event's specialized struct tag before deserializing.
+        ///
+        /// Returns `Ok(None)` if the struct tag isn't of one of this package's events.
+        pub fn from_raw(
+            struct_tag: &::moverox::types::StructTag,
+            bytes: &[u8],
+        ) -> ::std::result::Result<
+            ::std::option::Option<Self>,
+            ::moverox::FromRawDatatypeError,
+        > {
+            type Decoder = fn(
+                &::moverox::types::StructTag,
+                &[u8],
+            ) -> ::std::result::Result<Event, ::moverox::FromRawDatatypeError>;
+            static DECODERS: ::std::sync::LazyLock<
+                ::std::collections::HashMap<(&str, &str), Decoder>,
+            > = ::std::sync::LazyLock::new(|| {
+                ::std::collections::HashMap::from([
+                    (
+                        ("a", "Deposited"),
+                        (|struct_tag, bytes| {
+                            let (_, event) = ::moverox::parse_move_datatype(
+                                struct_tag,
+                                bytes,
+                            )?;
+                            Ok(Event::a_Deposited(event))
+                        }) as Decoder,
+                    ),
+                    (
+                        ("a", "Config"),
+                        (|struct_tag, bytes| {
+                            let (_, event) = ::moverox::parse_move_datatype(
+                                struct_tag,
+                                bytes,
+                            )?;
+                            Ok(Event::a_Config(event))
+                        }) as Decoder,
+                    ),
+                    (
+                        ("b", "Withdrawn"),
+                        (|struct_tag, bytes| {
+                            let (_, event) = ::moverox::parse_move_datatype(
+                                struct_tag,
+                                bytes,
+                            )?;
+                            Ok(Event::b_Withdrawn(event))
+                        }) as Decoder,
+                    ),
+                ])
+            });
+            if struct_tag.address != ::moverox::types::const_address(b"0x2") {
+                return Ok(None);
+            }
+            let key = (struct_tag.module.as_str(), struct_tag.name.as_str());
+            let Some(decode) = DECODERS.get(&key) else {
+                return Ok(None);
+            };
+            decode(struct_tag, bytes).map(Some)
+        }
+        /// Name of the module declaring the event.
+        pub const fn module(&self) -> &'static str {
+            match self {
+                Self::a_Deposited(_) => "a",
+                Self::a_Config(_) => "a",
+                Self::b_Withdrawn(_) => "b",
+            }
+        }
+        /// Name of the event struct.
+        pub const fn name(&self) -> &'static str {
+            match self {
+                Self::a_Deposited(_) => "Deposited",
+                Self::a_Config(_) => "Config",
+                Self::b_Withdrawn(_) => "Withdrawn",
+            }
+        }
+    }
+    "#);
+
+    let generic: Module = indoc! {"
+    module package::c {
+        #[ext(moverox(event))]
+        public struct Wrapped<T> has copy, drop { value: T }
+    }
+    "}
+    .to_token_iter()
+    .parse_all()
+    .unwrap();
+    let generic = ModuleSummary::new(&generic).unwrap();
+    let err = events(
+        [&generic],
+        &quote!(::moverox),
+        None,
+        &HashMap::new(),
+        &HashMap::new(),
+    )
+    .unwrap_err();
+    insta::assert_snapshot!(err, @"module c: struct Wrapped: generic structs can't be events");
+}
+
+#[test]
+fn datatypes_with_derive_rules() {
+    let move_module = indoc! {"
+    module package::pool {
+        #[ext(moverox(derive(PartialOrd, Ord)))]
+        public enum Status has copy, drop, store { Active, Paused }
+        public struct Fee has copy, drop, store { value: u64 }
+    }
+    "};
+    let rule = |scope: Option<&str>, derives: &[&str], remove_derives: &[&str], attrs: &[&str]| {
+        let tokens = |items: &[&str]| items.iter().map(|item| item.parse().unwrap()).collect();
+        DatatypeAttrs {
+            scope: scope.map(str::to_owned),
+            derives: tokens(derives),
+            remove_derives: tokens(remove_derives),
+            attrs: tokens(attrs),
+        }
+    };
+    let options = Options {
+        datatype_attrs: vec![
+            rule(Some("pool::Fee"), &["Copy"], &[], &["serde(transparent)"]),
+            rule(None, &["schemars::JsonSchema"], &["Hash"], &[]),
+            rule(
+                Some("pool"),
+                &[],
+                &["schemars::JsonSchema"],
+                &["non_exhaustive"],
+            ),
+        ],
+        ..Options::default()
+    };
+    insta::assert_snapshot!(from_module_with_options(move_module, None, &options), @r#"
+    #[allow(rustdoc::all, clippy::too_long_first_doc_paragraph)]
+    pub mod pool {
+        #[allow(non_camel_case_types, unused)]
+        type address = ::moverox::types::Address;
+        #[allow(non_camel_case_types, unused)]
+        type u256 = ::moverox::types::U256;
+        #[allow(non_camel_case_types, unused)]
+        type vector<T> = ::std::vec::Vec<T>;
+        #[derive(
+            Clone,
+            Debug,
+            PartialEq,
+            Eq,
+            ::moverox::traits::MoveDatatype,
+            ::moverox::serde::Deserialize,
+            ::moverox::serde::Serialize,
+            PartialOrd,
+            Ord,
+        )]
+        #[move_(crate = ::moverox::traits)]
+        #[serde(crate = "::moverox::serde")]
+        #[move_(module = pool)]
+        #[non_exhaustive]
+        #[allow(non_snake_case)]
+        pub enum Status {
+            Active,
+            Paused,
+        }
+        #[derive(
+            Clone,
+            Debug,
+            PartialEq,
+            Eq,
+            ::moverox::traits::MoveDatatype,
+            ::moverox::serde::Deserialize,
+            ::moverox::serde::Serialize,
+            Copy,
+        )]
+        #[move_(crate = ::moverox::traits)]
+        #[serde(crate = "::moverox::serde")]
+        #[move_(module = pool)]
+        #[non_exhaustive]
+        #[serde(transparent)]
+        #[allow(non_snake_case)]
+        pub struct Fee {
+            pub value: u64,
+        }
+        impl Fee {
+            #[allow(clippy::just_underscores_and_digits, clippy::too_many_arguments)]
+            pub const fn new(value: u64) -> Self {
+                Self { value }
+            }
+        }
+    }
+    "#);
+}
+
+#[test]
+fn datatypes_with_type_map() {
+    let move_module = indoc! {"
+    module package::pool {
+        use std::string::String;
+        use sui::vec_map::VecMap;
+
+        public struct Pool has copy, drop { name: String, tags: vector<String>, fees: VecMap<address, u64> }
+        public struct Name(String) has copy, drop;
+        public enum Status has copy, drop { Active { since: 0x1::option::Option<u64> } }
+    }
+    "};
+    let mut type_map = TypeMap::new();
+    let std = Ident::new("std", proc_macro2::Span::call_site());
+    let sui = Ident::new("sui", proc_macro2::Span::call_site());
+    let rust_type = |path: &str| path.parse().unwrap();
+    type_map.insert_named(
+        std,
+        "string::String".to_owned(),
+        TypeMapping::new(rust_type("::std::string::String"), None),
+    );
+    type_map.insert_numeric(
+        move_syn::address_bytes("0x1").unwrap(),
+        "option::Option".to_owned(),
+        TypeMapping::new(rust_type("::std::option::Option"), None),
+    );
+    type_map.insert_named(
+        sui,
+        "vec_map::VecMap".to_owned(),
+        TypeMapping::new(
+            rust_type("::std::collections::BTreeMap"),
+            Some("crate::vec_map_serde".to_owned()),
+        ),
+    );
+    let options = Options {
+        type_map,
+        ..Options::default()
+    };
+    let mut module: Module = move_module.to_token_iter().parse_all().unwrap();
+    module.fully_qualify_datatype_field_types();
+    let rust_code = module
+        .to_rust(&quote!(::moverox), None, &Default::default(), &options)
+        .unwrap();
+    let rust_code = prettyplease::unparse(&syn::parse2(rust_code).unwrap());
+    let fields = rust_code
+        .lines()
+        .filter(|line| line.contains("::std::") || line.contains("serde(with"))
+        .filter(|line| !line.contains("type vector"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    insta::assert_snapshot!(fields, @r#"
+        pub name: ::std::string::String,
+        pub tags: vector<::std::string::String>,
+        #[serde(with = "crate::vec_map_serde")]
+        pub fees: ::std::collections::BTreeMap<address, u64>,
+            name: ::std::string::String,
+            tags: vector<::std::string::String>,
+            fees: ::std::collections::BTreeMap<address, u64>,
+    pub struct Name(pub ::std::string::String);
+        pub const fn new(_0: ::std::string::String) -> Self {
+        Active { since: ::std::option::Option<u64> },
+    "#);
+}
+
+#[test]
+fn datatypes_with_renames() {
+    let move_module = indoc! {r#"
+    module package::dynamic {
+        #[ext(moverox(rename = "DynField"))]
+        public struct Field<Name, Value> has store { name: Name, value: Value }
+        public struct Wrapper has store {
+            field: Field<u64, bool>,
+            #[ext(moverox(rename = "kind"))]
+            type: u8,
+        }
+        public enum Type has copy, drop {
+            #[ext(moverox(rename = "Simple"))]
+            Plain,
+            Nested { type: u8 },
+        }
+    }
+    "#};
+    let ident = |name| Ident::new(name, proc_macro2::Span::call_site());
+    let options = Options {
+        renames: HashMap::from([
+            ("dynamic::Type".to_owned(), ident("MoveType")),
+            ("dynamic::Type::Nested::type".to_owned(), ident("kind")),
+        ]),
+        ..Options::default()
+    };
+    insta::assert_snapshot!(from_module_with_options(move_module, None, &options), @r#"
+    #[allow(rustdoc::all, clippy::too_long_first_doc_paragraph)]
+    pub mod dynamic {
+        #[allow(non_camel_case_types, unused)]
+        type address = ::moverox::types::Address;
+        #[allow(non_camel_case_types, unused)]
+        type u256 = ::moverox::types::U256;
+        #[allow(non_camel_case_types, unused)]
+        type vector<T> = ::std::vec::Vec<T>;
+        #[derive(
+            Clone,
+            Debug,
+            PartialEq,
+            Eq,
+            Hash,
+            ::moverox::traits::MoveDatatype,
+            ::moverox::serde::Deserialize,
+            ::moverox::serde::Serialize,
+        )]
+        #[move_(crate = ::moverox::traits)]
+        #[serde(crate = "::moverox::serde")]
+        #[move_(module = dynamic)]
+        #[move_(name = "Field")]
+        #[allow(non_snake_case)]
+        pub struct DynField<Name, Value> {
+            pub name: Name,
+            pub value: Value,
+        }
+        impl<Name, Value> DynField<Name, Value> {
+            #[allow(clippy::just_underscores_and_digits, clippy::too_many_arguments)]
+            pub const fn new(name: Name, value: Value) -> Self {
+                Self { name, value }
+            }
+        }
+        #[derive(
+            Clone,
+            Debug,
+            PartialEq,
+            Eq,
+            Hash,
+            ::moverox::traits::MoveDatatype,
+            ::moverox::serde::Deserialize,
+            ::moverox::serde::Serialize,
+        )]
+        #[move_(crate = ::moverox::traits)]
+        #[serde(crate = "::moverox::serde")]
+        #[move_(module = dynamic)]
+        #[allow(non_snake_case)]
+        pub struct Wrapper {
+            pub field: DynField<u64, bool>,
+            #[serde(rename = "type")]
+            pub kind: u8,
+        }
+        impl Wrapper {
+            #[allow(clippy::just_underscores_and_digits, clippy::too_many_arguments)]
+            pub const fn new(field: DynField<u64, bool>, kind: u8) -> Self {
+                Self { field, kind }
+            }
+        }
+        #[derive(
+            Clone,
+            Debug,
+            PartialEq,
+            Eq,
+            Hash,
+            ::moverox::traits::MoveDatatype,
+            ::moverox::serde::Deserialize,
+            ::moverox::serde::Serialize,
+        )]
+        #[move_(crate = ::moverox::traits)]
+        #[serde(crate = "::moverox::serde")]
+        #[move_(module = dynamic)]
+        #[move_(name = "Type")]
+        #[allow(non_snake_case)]
+        pub enum MoveType {
+            #[serde(rename = "Plain")]
+            Simple,
+            Nested { #[serde(rename = "type")] kind: u8 },
+        }
+    }
+    "#);
+
+    let move_module = indoc! {"
+    module package::dynamic {
+        public struct Wrapper has store { #[ext(moverox(event))] value: u8 }
+    }
+    "};
+    insta::assert_snapshot!(from_module(move_module), @r#"
+    struct Wrapper: field value: `event` can't be set on fields
+    "#);
+}
+
+#[test]
+fn datatypes_with_field_settings() {
+    let move_module = indoc! {r#"
+    module package::vault {
+        public struct Vault<phantom T> has key {
+            id: UID,
+            #[ext(moverox(with = "crate::hex", rust_type = "Vec<u8>"))]
+            key: vector<u8>,
+            #[ext(moverox(skip_debug, doc = "The vault's secret, never logged."))]
+            secret: vector<u8>,
+        }
+        public struct Pin(#[ext(moverox(skip_debug))] u64, bool) has store;
+        public enum Access has store {
+            Open,
+            Locked { #[ext(moverox(skip_debug))] code: u64, attempts: u8 },
+            Sealed(#[ext(moverox(skip_debug))] u64),
+        }
+    }
+    "#};
+    insta::assert_snapshot!(from_module(move_module), @r#"
+    #[allow(rustdoc::all, clippy::too_long_first_doc_paragraph)]
+    pub mod vault {
+        #[allow(non_camel_case_types, unused)]
+        type address = ::moverox::types::Address;
+        #[allow(non_camel_case_types, unused)]
+        type u256 = ::moverox::types::U256;
+        #[allow(non_camel_case_types, unused)]
+        type vector<T> = ::std::vec::Vec<T>;
+        #[derive(
+            Clone,
+            PartialEq,
+            Eq,
+            Hash,
+            ::moverox::traits::MoveDatatype,
+            ::moverox::serde::Deserialize,
+            ::moverox::serde::Serialize,
+        )]
+        #[move_(crate = ::moverox::traits)]
+        #[serde(crate = "::moverox::serde")]
+        #[move_(module = vault)]
+        #[allow(non_snake_case)]
+        pub struct Vault<T> {
+            pub id: UID,
+            #[serde(with = "crate::hex")]
+            pub key: Vec<u8>,
+            /// The vault's secret, never logged.
+            pub secret: vector<u8>,
+            #[serde(skip)]
+            _T: ::std::marker::PhantomData<T>,
+        }
+        impl<T> ::std::fmt::Debug for Vault<T>
+        where
+            T: ::std::fmt::Debug,
+        {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                f.debug_struct("Vault")
+                    .field("id", &self.id)
+                    .field("key", &self.key)
+                    .finish_non_exhaustive()
+            }
+        }
+        impl<T> Vault<T> {
+            #[allow(clippy::just_underscores_and_digits, clippy::too_many_arguments)]
+            pub const fn new(id: UID, key: Vec<u8>, secret: vector<u8>) -> Self {
+                Self {
+                    id,
+                    key,
+                    secret,
+                    _T: ::std::marker::PhantomData,
+                }
+            }
+        }
+        impl<T> ::moverox::traits::HasKey for Vault<T> {
+            fn address(&self) -> ::moverox::types::Address {
+                self.id.id.bytes
+            }
+        }
+        #[derive(
+            Clone,
+            PartialEq,
+            Eq,
+            Hash,
+            ::moverox::traits::MoveDatatype,
+            ::moverox::serde::Deserialize,
+            ::moverox::serde::Serialize,
+        )]
+        #[move_(crate = ::moverox::traits)]
+        #[serde(crate = "::moverox::serde")]
+        #[move_(module = vault)]
+        #[allow(non_snake_case)]
+        pub struct Pin(pub u64, pub bool);
+        impl ::std::fmt::Debug for Pin {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                f.debug_tuple("Pin").field(&self.1).finish_non_exhaustive()
+            }
+        }
+        impl Pin {
+            #[allow(clippy::just_underscores_and_digits, clippy::too_many_arguments)]
+            pub const fn new(_0: u64, _1: bool) -> Self {
+                Self(_0, _1)
+            }
+        }
+        #[derive(
+            Clone,
+            PartialEq,
+            Eq,
+            Hash,
+            ::moverox::traits::MoveDatatype,
+            ::moverox::serde::Deserialize,
+            ::moverox::serde::Serialize,
+        )]
+        #[move_(crate = ::moverox::traits)]
+        #[serde(crate = "::moverox::serde")]
+        #[move_(module = vault)]
+        #[allow(non_snake_case)]
+        pub enum Access {
+            Open,
+            Locked { code: u64, attempts: u8 },
+            Sealed(u64),
+        }
+        impl ::std::fmt::Debug for Access {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                match self {
+                    Self::Open { .. } => f.debug_tuple("Open").finish(),
+                    Self::Locked { attempts, .. } => {
+                        f.debug_struct("Locked")
+                            .field("attempts", attempts)
+                            .finish_non_exhaustive()
+                    }
+                    Self::Sealed { .. } => f.debug_tuple("Sealed").finish_non_exhaustive(),
+                }
+            }
+        }
+    }
+    "#);
+}
+
+#[test]
+fn field_with_invalid_settings() {
+    let misspelled = indoc! {r#"
+    module package::vault {
+        public struct Vault has store { #[ext(moverox(rnme = "name"))] value: u64 }
+    }
+    "#};
+    insta::assert_snapshot!(from_module(misspelled), @r#"
+    struct Vault: field value: Invalid attribute `moverox (rnme = "name")`, expected `moverox(...)` with any of the `type_`, `event`, `derive`, `rename`, `with`, `skip_debug`, `rust_type` and `doc` settings
+    "#);
+    let positional_rename = indoc! {r#"
+    module package::vault {
+        public struct Vault(#[ext(moverox(rename = "value"))] u64) has store;
+    }
+    "#};
+    insta::assert_snapshot!(from_module(positional_rename), @r#"
+    struct Vault: field 0: `rename` can't be set on positional fields
+    "#);
+}
+
+#[test]
+fn datatypes_with_type_defaults() {
+    let move_module = indoc! {r#"
+    module package::vault {
+        use sui::coin;
+        use sui::sui::SUI;
+
+        #[ext(moverox(type_(T = SUI)))]
+        public struct Vault<phantom T> has key { id: UID }
+        public struct Wrapper<T> has store { inner: T }
+        #[ext(moverox(type_(T = OTW)))]
+        public struct Reward<phantom T> has store { amount: u64 }
+        public enum Pool<phantom T> has store { Empty }
+    }
+    "#};
+    let options = Options {
+        type_defaults: HashMap::from([
+            ("vault::Reward::T".to_owned(), quote!(sui::sui::SUI)),
+            ("vault::Pool::T".to_owned(), quote!(SUI)),
+            ("vault::Wrapper::T".to_owned(), quote!(coin::Coin<SUI>)),
+        ]),
+        ..Options::default()
+    };
+    insta::assert_snapshot!(from_module_with_options(move_module, None, &options), @r#"
+    #[allow(rustdoc::all, clippy::too_long_first_doc_paragraph)]
+    pub mod vault {
+        #[allow(non_camel_case_types, unused)]
+        type address = ::moverox::types::Address;
+        #[allow(non_camel_case_types, unused)]
+        type u256 = ::moverox::types::U256;
+        #[allow(non_camel_case_types, unused)]
+        type vector<T> = ::std::vec::Vec<T>;
+        #[derive(
+            Clone,
+            Debug,
+            PartialEq,
+            Eq,
+            Hash,
+            ::moverox::traits::MoveDatatype,
+            ::moverox::serde::Deserialize,
+            ::moverox::serde::Serialize,
+        )]
+        #[move_(crate = ::moverox::traits)]
+        #[serde(crate = "::moverox::serde")]
+        #[move_(module = vault)]
+        #[allow(non_snake_case)]
+        pub struct Vault<T = sui::sui::SUI> {
+            pub id: UID,
+            #[serde(skip)]
+            _T: ::std::marker::PhantomData<T>,
+        }
+        impl<T> Vault<T> {
+            #[allow(clippy::just_underscores_and_digits, clippy::too_many_arguments)]
+            pub const fn new(id: UID) -> Self {
+                Self {
+                    id,
+                    _T: ::std::marker::PhantomData,
+                }
+            }
+        }
+        impl<T> ::moverox::traits::HasKey for Vault<T> {
+            fn address(&self) -> ::moverox::types::Address {
+                self.id.id.bytes
+            }
+        }
+        #[derive(
+            Clone,
+            Debug,
+            PartialEq,
+            Eq,
+            Hash,
+            ::moverox::traits::MoveDatatype,
+            ::moverox::serde::Deserialize,
+            ::moverox::serde::Serialize,
+        )]
+        #[move_(crate = ::moverox::traits)]
+        #[serde(crate = "::moverox::serde")]
+        #[move_(module = vault)]
+        #[allow(non_snake_case)]
+        pub struct Wrapper<T = sui::coin::Coin<sui::sui::SUI>> {
+            pub inner: T,
+        }
+        impl<T> Wrapper<T> {
+            #[allow(clippy::just_underscores_and_digits, clippy::too_many_arguments)]
+            pub const fn new(inner: T) -> Self {
+                Self { inner }
+            }
+        }
+        #[derive(
+            Clone,
+            Debug,
+            PartialEq,
+            Eq,
+            Hash,
+            ::moverox::traits::MoveDatatype,
+            ::moverox::serde::Deserialize,
+            ::moverox::serde::Serialize,
+        )]
+        #[move_(crate = ::moverox::traits)]
+        #[serde(crate = "::moverox::serde")]
+        #[move_(module = vault)]
+        #[allow(non_snake_case)]
+        pub struct Reward<T = sui::sui::SUI> {
+            pub amount: u64,
+            #[serde(skip)]
+            _T: ::std::marker::PhantomData<T>,
+        }
+        impl<T> Reward<T> {
+            #[allow(clippy::just_underscores_and_digits, clippy::too_many_arguments)]
+            pub const fn new(amount: u64) -> Self {
+                Self {
+                    amount,
+                    _T: ::std::marker::PhantomData,
+                }
+            }
+        }
+        #[derive(
+            Clone,
+            Debug,
+            PartialEq,
+            Eq,
+            Hash,
+            ::moverox::traits::MoveDatatype,
+            ::moverox::serde::Deserialize,
+            ::moverox::serde::Serialize,
+        )]
+        #[move_(crate = ::moverox::traits)]
+        #[serde(crate = "::moverox::serde")]
+        #[move_(module = vault)]
+        #[allow(non_snake_case)]
+        pub enum Pool<T = sui::sui::SUI> {
+            Empty(#[serde(skip)] ::std::marker::PhantomData<T>),
+        }
+    }
+    "#);
+}
+
+#[test]
+fn datatypes_with_type_origins() {
+    let move_module = indoc! {"
+    module package::pool {
+        public struct Pool has key { id: UID }
+        public struct Created has copy, drop { pool: ID }
+        public struct Upgraded has copy, drop { pool: ID }
+        public enum Status has copy, drop, store { Active, Paused }
+    }
+    "};
+    let package = LiteralString::from_str("0x3");
+    let options = Options {
+        type_origins: HashMap::from([
+            ("pool::Pool".to_owned(), "0x1".to_owned()),
+            ("pool::Created".to_owned(), "0x1".to_owned()),
+            ("pool::Status".to_owned(), "0x2".to_owned()),
+        ]),
+        ..Options::default()
+    };
+    let rust_code = from_module_with_options(move_module, Some(&package), &options);
+    let addresses = rust_code
+        .lines()
+        .filter(|line| line.contains("move_(address"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    insta::assert_snapshot!(addresses, @r#"
+    #[move_(address = "0x1")]
+    #[move_(address = "0x1")]
+    #[move_(address = "0x3")]
+    #[move_(address = "0x2")]
+    "#);
+
+    let module: Module = move_module.to_token_iter().parse_all().unwrap();
+    let summary = ModuleSummary::new(&module).unwrap();
+    let rust_code = events(
+        [&summary],
+        &quote!(::moverox),
+        Some(&package),
+        &options.type_origins,
+        &options.renames,
+    )
+    .unwrap()
+    .to_string();
+    let rust_code = prettyplease::unparse(&syn::parse_file(&rust_code).unwrap());
+    let check = rust_code
+        .lines()
+        .skip_while(|line| !line.contains("let address = match"))
+        .take_while(|line| !line.contains("let key"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    insta::assert_snapshot!(check, @r#"
+    let address = match (struct_tag.module.as_str(), struct_tag.name.as_str()) {
+        ("pool", "Created") => Some(::moverox::types::const_address(b"0x1")),
+        ("pool", "Upgraded") => Some(::moverox::types::const_address(b"0x3")),
+        _ => None,
+    };
+    if address.is_some_and(|address| struct_tag.address != address) {
+        return Ok(None);
+    }
+    "#);
+}
+
+#[test]
+fn module_with_test_items() {
+    let move_module = indoc! {"
+    module package::vault {
+        public struct Vault has key { id: UID }
+
+        /// Only in tests.
+        #[test_only]
+        public struct Fixture has drop { value: u64 }
+
+        #[test_only]
+        const ETest: u64 = 1;
+
+        #[mode(bench)]
+        public fun bench(): u64 { 0 }
+
+        #[test]
+        fun it_works() {}
+    }
+    "};
+    let options = Options {
+        call_descriptors: true,
+        ..Options::default()
+    };
+    let rust_code = from_module_with_options(move_module, None, &options);
+    assert!(!rust_code.contains("Fixture"));
+    assert!(!rust_code.contains("ETest"));
+    assert!(!rust_code.contains("bench"));
+
+    let options = Options {
+        call_descriptors: true,
+        test_cfg: Some(quote!(feature = "test-only")),
+        ..Options::default()
+    };
+    let rust_code = from_module_with_options(move_module, None, &options);
+    let test_items = rust_code
+        .split_inclusive('\n')
+        .skip_while(|line| !line.contains("#[cfg("))
+        .collect::<String>();
+    insta::assert_snapshot!(test_items, @r#"
+        #[cfg(feature = "test-only")]
+        #[cfg_attr(not(doctest), doc = " Only in tests.")]
+        #[derive(
+            Clone,
+            Debug,
+            PartialEq,
+            Eq,
+            Hash,
+            ::moverox::traits::MoveDatatype,
+            ::moverox::serde::Deserialize,
+            ::moverox::serde::Serialize,
+        )]
+        #[move_(crate = ::moverox::traits)]
+        #[serde(crate = "::moverox::serde")]
+        #[move_(module = vault)]
+        #[allow(non_snake_case)]
+        pub struct Fixture {
+            pub value: u64,
+        }
+        #[cfg(feature = "test-only")]
+        impl Fixture {
+            #[allow(clippy::just_underscores_and_digits, clippy::too_many_arguments)]
+            pub const fn new(value: u64) -> Self {
+                Self { value }
+            }
+        }
+        #[cfg(feature = "test-only")]
+        #[allow(non_upper_case_globals)]
+        pub const ETest: u64 = 1;
+        #[cfg(feature = "test-only")]
+        /// Call `vault::bench`.
+        #[allow(
+            non_camel_case_types,
+            clippy::too_many_arguments,
+            clippy::type_complexity,
+            clippy::used_underscore_binding,
+        )]
+        pub fn bench(package: ::moverox::types::Address) -> ::moverox::MoveCall<(), ()> {
+            ::moverox::MoveCall {
+                package: package,
+                module: ::moverox::types::IdentStr::cast("vault"),
+                function: ::moverox::types::IdentStr::cast("bench"),
+                type_arguments: ::std::vec![],
+                parameters: &[],
+                objects: (),
+                pures: (),
+            }
+        }
+    }
+    "#);
+
+    let test_module = "#[test_only] module package::vault_tests { public struct Fixture {} }";
+    let rust_code = from_module_with_options(test_module, None, &Options::default());
+    assert_eq!(rust_code, "");
+    let options = Options {
+        test_cfg: Some(quote!(test)),
+        ..Options::default()
+    };
+    let rust_code = from_module_with_options(test_module, None, &options);
+    assert!(rust_code.starts_with("#[cfg(test)]\n"));
+}
+
+#[test]
+fn module_with_call_descriptors() {
+    let move_module = indoc! {"
+    module package::vault {
+        use std::option::Option;
+        use std::string::String;
+        use sui::balance::Balance;
+        use sui::coin::Coin;
+        use sui::object::ID;
+        use sui::tx_context::TxContext;
+
+        public struct Vault<phantom T> has key {
+            id: UID,
+            balance: Balance<T>,
+        }
+
+        /// Put `coin` in the vault.
+        public fun deposit<T>(vault: &mut Vault<T>, coin: Coin<T>, ctx: &TxContext) {}
+
+        public fun balance<T>(vault: &Vault<T>): u64 { 0 }
+
+        entry fun rename(vault: &mut Vault<SUI>, name: &String, ref: Option<ID>, _: bool) {}
+
+        public(package) fun internal(vault: &Vault<SUI>) {}
+
+        fun private(vault: &Vault<SUI>) {}
+
+        public native fun hash(data: &vector<u8>): vector<u8>;
+    }
+    "};
+    let package = LiteralString::from_str("0x2");
+    let options = Options {
+        call_descriptors: true,
+        ..Options::default()
+    };
+    let rust_code = from_module_with_options(move_module, Some(&package), &options);
+    let functions = rust_code
+        .split_inclusive('\n')
+        .skip_while(|line| !line.contains("Put `coin` in the vault."))
+        .collect::<String>();
+    insta::assert_snapshot!(functions, @r#"
+        #[cfg_attr(not(doctest), doc = " Put `coin` in the vault.")]
+        /// Call `vault::deposit`.
+        #[allow(
+            non_camel_case_types,
+            clippy::too_many_arguments,
+            clippy::type_complexity,
+            clippy::used_underscore_binding,
+        )]
+        pub fn deposit<T: ::moverox::traits::MoveType>(
+            type_args: (<T as ::moverox::traits::MoveType>::TypeTag,),
+            vault: ::moverox::ObjectArg<Vault<T>>,
+            coin: ::moverox::ObjectArg<sui::coin::Coin<T>>,
+        ) -> ::moverox::MoveCall<
+            (::moverox::ObjectArg<Vault<T>>, ::moverox::ObjectArg<sui::coin::Coin<T>>),
+            (),
+        > {
+            ::moverox::MoveCall {
+                package: ::moverox::types::const_address(b"0x2"),
+                module: ::moverox::types::IdentStr::cast("vault"),
+                function: ::moverox::types::IdentStr::cast("deposit"),
+                type_arguments: ::std::vec![
+                    ::moverox::traits::MoveTypeTag::to_type_tag(& type_args.0)
+                ],
+                parameters: &[
+                    ::moverox::Parameter {
+                        name: "vault",
+                        kind: ::moverox::ParameterKind::MutRef,
+                    },
+                    ::moverox::Parameter {
+                        name: "coin",
+                        kind: ::moverox::ParameterKind::Owned,
+                    },
+                ],
+                objects: (vault, coin),
+                pures: (),
+            }
+        }
+        /// Call `vault::balance`.
+        #[allow(
+            non_camel_case_types,
+            clippy::too_many_arguments,
+            clippy::type_complexity,
+            clippy::used_underscore_binding,
+        )]
+        pub fn balance<T: ::moverox::traits::MoveType>(
+            type_args: (<T as ::moverox::traits::MoveType>::TypeTag,),
+            vault: ::moverox::ObjectArg<Vault<T>>,
+        ) -> ::moverox::MoveCall<(::moverox::ObjectArg<Vault<T>>,), ()> {
+            ::moverox::MoveCall {
+                package: ::moverox::types::const_address(b"0x2"),
+                module: ::moverox::types::IdentStr::cast("vault"),
+                function: ::moverox::types::IdentStr::cast("balance"),
+                type_arguments: ::std::vec![
+                    ::moverox::traits::MoveTypeTag::to_type_tag(& type_args.0)
+                ],
+                parameters: &[
+                    ::moverox::Parameter {
+                        name: "vault",
+                        kind: ::moverox::ParameterKind::Ref,
+                    },
+                ],
+                objects: (vault,),
+                pures: (),
+            }
+        }
+        /// Call `vault::rename`.
+        #[allow(
+            non_camel_case_types,
+            clippy::too_many_arguments,
+            clippy::type_complexity,
+            clippy::used_underscore_binding,
+        )]
+        pub fn rename(
+            vault: ::moverox::ObjectArg<Vault<SUI>>,
+            name: std::string::String,
+            r#ref: std::option::Option<sui::object::ID>,
+            arg3: bool,
+        ) -> ::moverox::MoveCall<
+            (::moverox::ObjectArg<Vault<SUI>>,),
+            (std::string::String, std::option::Option<sui::object::ID>, bool),
+        > {
+            ::moverox::MoveCall {
+                package: ::moverox::types::const_address(b"0x2"),
+                module: ::moverox::types::IdentStr::cast("vault"),
+                function: ::moverox::types::IdentStr::cast("rename"),
+                type_arguments: ::std::vec![],
+                parameters: &[
+                    ::moverox::Parameter {
+                        name: "vault",
+                        kind: ::moverox::ParameterKind::MutRef,
+                    },
+                    ::moverox::Parameter {
+                        name: "name",
+                        kind: ::moverox::ParameterKind::Value,
+                    },
+                    ::moverox::Parameter {
+                        name: "ref",
+                        kind: ::moverox::ParameterKind::Value,
+                    },
+                    ::moverox::Parameter {
+                        name: "_",
+                        kind: ::moverox::ParameterKind::Value,
+                    },
+                ],
+                objects: (vault,),
+                pures: (name, r#ref, arg3),
+            }
+        }
+        /// Call `vault::hash`.
+        #[allow(
+            non_camel_case_types,
+            clippy::too_many_arguments,
+            clippy::type_complexity,
+            clippy::used_underscore_binding,
+        )]
+        pub fn hash(data: vector<u8>) -> ::moverox::MoveCall<(), (vector<u8>,)> {
+            ::moverox::MoveCall {
+                package: ::moverox::types::const_address(b"0x2"),
+                module: ::moverox::types::IdentStr::cast("vault"),
+                function: ::moverox::types::IdentStr::cast("hash"),
+                type_arguments: ::std::vec![],
+                parameters: &[
+                    ::moverox::Parameter {
+                        name: "data",
+                        kind: ::moverox::ParameterKind::Value,
+                    },
+                ],
+                objects: (),
+                pures: (data,),
+            }
+        }
+    }
+    "#);
+
+    // Without a known package address, it's an argument
+    let rust_code = from_module_with_options(move_module, None, &options);
+    let signature = rust_code
+        .split_inclusive('\n')
+        .skip_while(|line| !line.contains("pub fn balance"))
+        .take_while(|line| !line.contains('{'))
+        .collect::<String>();
+    insta::assert_snapshot!(signature, @r#"
+    pub fn balance<T: ::moverox::traits::MoveType>(
+        package: ::moverox::types::Address,
+        type_args: (<T as ::moverox::traits::MoveType>::TypeTag,),
+        vault: ::moverox::ObjectArg<Vault<T>>,
+    "#);
+
+    // Disabled by default
+    let rust_code = from_module_with_options(move_module, None, &Options::default());
+    assert!(!rust_code.contains("MoveCall"));
+}
+
+#[test]
+fn call_descriptor_objects_by_value() {
+    let move_module = indoc! {"
+    module sui::coin {
+        public struct Coin<phantom T> has key, store {
+            id: UID,
+            balance: Balance<T>,
+        }
+
+        public entry fun join<T>(self: &mut Coin<T>, c: Coin<T>) {}
+    }
+    "};
+    let options = Options {
+        call_descriptors: true,
+        ..Options::default()
+    };
+    let rust_code = from_module_with_options(move_module, None, &options);
+    let signature = rust_code
+        .split_inclusive('\n')
+        .skip_while(|line| !line.contains("pub fn join"))
+        .take_while(|line| !line.contains("pures:"))
+        .collect::<String>();
+    insta::assert_snapshot!(signature, @r#"
+    pub fn join<T: ::moverox::traits::MoveType>(
+        package: ::moverox::types::Address,
+        type_args: (<T as ::moverox::traits::MoveType>::TypeTag,),
+        self_: ::moverox::ObjectArg<Coin<T>>,
+        c: ::moverox::ObjectArg<Coin<T>>,
+    ) -> ::moverox::MoveCall<
+        (::moverox::ObjectArg<Coin<T>>, ::moverox::ObjectArg<Coin<T>>),
+        (),
+    > {
+        ::moverox::MoveCall {
+            package: package,
+            module: ::moverox::types::IdentStr::cast("coin"),
+            function: ::moverox::types::IdentStr::cast("join"),
+            type_arguments: ::std::vec![
+                ::moverox::traits::MoveTypeTag::to_type_tag(& type_args.0)
+            ],
+            parameters: &[
+                ::moverox::Parameter {
+                    name: "self",
+                    kind: ::moverox::ParameterKind::MutRef,
+                },
+                ::moverox::Parameter {
+                    name: "c",
+                    kind: ::moverox::ParameterKind::Owned,
+                },
+            ],
+            objects: (self_, c),
+    "#);
+}
+
+#[test]
+fn call_descriptor_framework_types_need_framework_addresses() {
+    let move_module = indoc! {"
+    module package::registry {
+        use package::object::ID;
+
+        public struct TxContext has drop {}
+
+        public fun register(id: &ID, sui_id: &sui::object::ID, ctx: &TxContext, name: &Option<u8>) {}
+    }
+    "};
+    let options = Options {
+        call_descriptors: true,
+        ..Options::default()
+    };
+    let rust_code = from_module_with_options(move_module, None, &options);
+    let signature = rust_code
+        .split_inclusive('\n')
+        .skip_while(|line| !line.contains("pub fn register"))
+        .take_while(|line| !line.contains('{'))
+        .collect::<String>();
+    insta::assert_snapshot!(signature, @r#"
+    pub fn register(
+        package: ::moverox::types::Address,
+        id: ::moverox::ObjectArg<package::object::ID>,
+        sui_id: sui::object::ID,
+        ctx: ::moverox::ObjectArg<TxContext>,
+        name: Option<u8>,
+    ) -> ::moverox::MoveCall<
+        (::moverox::ObjectArg<package::object::ID>, ::moverox::ObjectArg<TxContext>),
+        (sui::object::ID, Option<u8>),
+    "#);
+}
+
+#[test]
+fn call_descriptor_parameters_dont_shadow_generated_ones() {
+    let move_module = indoc! {"
+    module package::registry {
+        public fun register<T>(package: address, type_args: vector<u8>, _: u64, arg2: u64) {}
+    }
+    "};
+    let options = Options {
+        call_descriptors: true,
+        ..Options::default()
+    };
+    // Without a known package address, `package` is a parameter too
+    let rust_code = from_module_with_options(move_module, None, &options);
+    let signature = rust_code
+        .split_inclusive('\n')
+        .skip_while(|line| !line.contains("pub fn register"))
+        .take_while(|line| !line.contains('{'))
+        .collect::<String>();
+    insta::assert_snapshot!(signature, @r#"
+    pub fn register<T: ::moverox::traits::MoveType>(
+        package: ::moverox::types::Address,
+        type_args: (<T as ::moverox::traits::MoveType>::TypeTag,),
+        package_: address,
+        type_args_: vector<u8>,
+        arg2: u64,
+        arg2_: u64,
+    "#);
+}
+
+#[test]
+fn module_with_return_decoders() {
+    let move_module = indoc! {"
+    module package::vault {
+        use std::option::Option;
+        use sui::balance::Balance;
+        use sui::object::ID;
+
+        public struct Vault<phantom T> has key {
+            id: UID,
+            balance: Balance<T>,
+        }
+
+        public fun deposit<T>(vault: &mut Vault<T>, amount: u64) {}
+
+        public fun balance<T>(vault: &Vault<T>): u64 { 0 }
+
+        public fun borrow_balance<T, U>(vault: &Vault<T>, other: &U): (&Balance<T>, Option<ID>) { abort 0 }
+
+        fun private(vault: &Vault<SUI>): u64 { 0 }
+    }
+    "};
+    let options = Options {
+        return_decoders: true,
+        ..Options::default()
+    };
+    let rust_code = from_module_with_options(move_module, None, &options);
+    let functions = rust_code
+        .split_inclusive('\n')
+        .skip_while(|line| !line.contains("`vault::balance`"))
+        .collect::<String>();
+    insta::assert_snapshot!(functions, @r#"
+        /// Decode the return values of a call to `vault::balance`.
+        #[allow(non_camel_case_types, non_snake_case, clippy::type_complexity)]
+        pub fn balance_returns(
+            return_values: &[(::moverox::types::TypeTag, impl ::std::convert::AsRef<[u8]>)],
+        ) -> ::std::result::Result<(u64,), ::moverox::FromReturnValuesError> {
+            ::moverox::check_return_count(return_values, 1)?;
+            Ok((::moverox::parse_return_value(return_values, 0)?,))
+        }
+        /// Decode the return values of a call to `vault::borrow_balance`.
+        #[allow(non_camel_case_types, non_snake_case, clippy::type_complexity)]
+        pub fn borrow_balance_returns<T: ::moverox::traits::MoveType>(
+            return_values: &[(::moverox::types::TypeTag, impl ::std::convert::AsRef<[u8]>)],
+        ) -> ::std::result::Result<
+            (sui::balance::Balance<T>, std::option::Option<sui::object::ID>),
+            ::moverox::FromReturnValuesError,
+        >
+        where
+            sui::balance::Balance<
+                T,
+            >: ::moverox::traits::MoveType + for<'de> ::moverox::serde::Deserialize<'de>,
+            std::option::Option<
+                sui::object::ID,
+            >: ::moverox::traits::MoveType + for<'de> ::moverox::serde::Deserialize<'de>,
+        {
+            ::moverox::check_return_count(return_values, 2)?;
+            Ok((
+                ::moverox::parse_return_value(return_values, 0)?,
+                ::moverox::parse_return_value(return_values, 1)?,
+            ))
+        }
+    }
+    "#);
+
+    // Disabled by default
+    let rust_code = from_module_with_options(move_module, None, &Options::default());
+    assert!(!rust_code.contains("FromReturnValuesError"));
+
+    // Not for a bare `Option`, which is generated as Rust's
+    let move_module = indoc! {"
+    module package::math {
+        public fun checked_add(x: u64, y: u64): Option<u64> { abort 0 }
+    }
+    "};
+    let rust_code = from_module_with_options(move_module, None, &options);
+    assert!(!rust_code.contains("checked_add_returns"));
+
+    // Nor with the name of another function's call descriptor
+    let move_module = indoc! {"
+    module package::math {
+        public fun sum(x: u64, y: u64): u64 { x + y }
+        public fun sum_returns(x: u64) {}
+    }
+    "};
+    let options = Options {
+        call_descriptors: true,
+        return_decoders: true,
+        ..Options::default()
+    };
+    let err = from_module_with_options(move_module, None, &options);
+    insta::assert_snapshot!(err, @"fun sum: the decoder of its return values would have the same name as the call descriptor of `sum_returns`");
+}
+
+#[test]
+fn package_abort_errors() {
+    let modules: Vec<ModuleSummary> = [
+        "module package::a { const EA: u64 = 0; }",
+        "module package::b {}",
+    ]
+    .into_iter()
+    .map(|s| ModuleSummary::new(&s.to_token_iter().parse_all().unwrap()).unwrap())
+    .collect();
+    let package = LiteralString::from_str("0x2");
+    let rust_code = abort_errors(&modules, &quote!(::moverox), Some(&package))
+        .unwrap()
+        .to_string();
+    insta::assert_snapshot!(prettyplease::unparse(&syn::parse_file(&rust_code).unwrap()), @r#"
+    /// Abort errors of this package's modules.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    #[allow(non_camel_case_types)]
+    pub enum AbortError {
+        a(a::Error),
+    }
+    impl AbortError {
+        /// Decode a Move abort with `code` in `module` at `address`.
+        ///
+        /// Returns `None` if `address` isn't this package's.
+        pub fn from_abort(
+            address: &::moverox::types::Address,
+            module: &str,
+            code: u64,
+        ) -> Option<Self> {
+            if *address != ::moverox::types::const_address(b"0x2") {
+                return None;
+            }
+            match module {
+                "a" => a::Error::try_from(code).ok().map(Self::a),
+                _ => None,
+            }
+        }
+        /// Name of the module that aborted.
+        pub const fn module(self) -> &'static str {
+            match self {
+                Self::a(_) => "a",
+            }
+        }
+        /// The abort code.
+        pub const fn code(self) -> u64 {
+            match self {
+                Self::a(error) => error.code(),
+            }
+        }
+        /// Name of the constant declaring the abort code.
+        pub const fn name(self) -> &'static str {
+            match self {
+                Self::a(error) => error.name(),
+            }
+        }
+        /// Documentation of the constant declaring the abort code, or its name if undocumented.
+        pub const fn message(self) -> &'static str {
+            match self {
+                Self::a(error) => error.message(),
+            }
+        }
+    }
+    impl ::std::fmt::Display for AbortError {
+        fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+            write!(f, "{}::{}: {}", self.module(), self.name(), self.message())
+        }
+    }
+    impl ::std::error::Error for AbortError {}
+    "#);
+}
+
+#[test]
+fn package_abort_errors_clashing_with_modules() {
+    let modules: Vec<ModuleSummary> = [
+        "module package::a { const EA: u64 = 0; }",
+        "module package::AbortError {}",
+    ]
+    .into_iter()
+    .map(|s| ModuleSummary::new(&s.to_token_iter().parse_all().unwrap()).unwrap())
+    .collect();
+    let err = abort_errors(&modules, &quote!(::moverox), None).unwrap_err();
+    insta::assert_snapshot!(err, @"module AbortError: clashes with the package's `AbortError` enum");
+}
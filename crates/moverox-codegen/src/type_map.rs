@@ -0,0 +1,82 @@
+use std::collections::HashMap;
+
+use move_syn::Address;
+use proc_macro2::{Ident, TokenStream};
+
+/// Mapping of Move datatypes to custom Rust types for the fields of the generated datatypes.
+///
+/// Like in [`AddressMap`](crate::AddressMap), Move types are looked up by the name of their
+/// address (`std::string::String`) or by its value (`0x1::string::String`), so a type with a named
+/// address is only found by its value if both are inserted.
+#[derive(Clone, Debug, Default)]
+pub struct TypeMap {
+    named: HashMap<(Ident, String), TypeMapping>,
+    numeric: HashMap<([u8; 32], String), TypeMapping>,
+}
+
+/// A Rust type for a Move datatype; see [`TypeMap`].
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct TypeMapping {
+    /// Path of the Rust type, e.g., `::std::string::String`.
+    ///
+    /// The type arguments of the Move type follow it, e.g., `::std::option::Option<T>` for
+    /// `0x1::option::Option<T>`.
+    pub rust_type: TokenStream,
+    /// Path of a module with the `serialize` and `deserialize` functions for the Rust type, for
+    /// `#[serde(with = ...)]`, if it isn't BCS-compatible with the Move type by itself.
+    ///
+    /// Only applies to fields whose type is the Move type itself, not to ones where it's a type
+    /// argument, e.g., `vector<T>`.
+    pub serde_with: Option<String>,
+}
+
+impl TypeMapping {
+    pub const fn new(rust_type: TokenStream, serde_with: Option<String>) -> Self {
+        Self {
+            rust_type,
+            serde_with,
+        }
+    }
+}
+
+impl TypeMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Map the datatype `module::Name` at a named address, returning the previous mapping, if
+    /// any.
+    pub fn insert_named(
+        &mut self,
+        named: Ident,
+        datatype: String,
+        mapping: TypeMapping,
+    ) -> Option<TypeMapping> {
+        self.named.insert((named, datatype), mapping)
+    }
+
+    /// Map the datatype `module::Name` at a numerical address, returning the previous mapping, if
+    /// any.
+    pub fn insert_numeric(
+        &mut self,
+        address: [u8; 32],
+        datatype: String,
+        mapping: TypeMapping,
+    ) -> Option<TypeMapping> {
+        self.numeric.insert((address, datatype), mapping)
+    }
+
+    /// Rust type for the Move datatype `module::item` at `address`.
+    pub fn get(&self, address: &Address, module: &Ident, item: &Ident) -> Option<&TypeMapping> {
+        let datatype = format!("{module}::{item}");
+        match address {
+            Address::Named(ident) => self.named.get(&(ident.clone(), datatype)),
+            Address::Numeric(_) => self.numeric.get(&(address.to_bytes()?, datatype)),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.named.is_empty() && self.numeric.is_empty()
+    }
+}
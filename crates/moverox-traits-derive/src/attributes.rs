@@ -13,6 +13,7 @@ pub(crate) struct MoveAttributes {
     pub(crate) thecrate: Option<Path>,
     pub(crate) address: Option<String>,
     pub(crate) module: Option<Ident>,
+    pub(crate) name: Option<String>,
     #[darling(default)]
     pub(crate) nameless: bool,
 }
@@ -18,6 +18,12 @@ impl Datatype {
         let ast: DeriveInput = syn::parse2(item)?;
         ensure_nonempty_struct(&ast)?;
         let attrs = MoveAttributes::from_derive_input(&ast)?;
+        if attrs.nameless && attrs.name.is_some() {
+            return Err(syn::Error::new(
+                ast.ident.span(),
+                "`name` and `nameless` can't be used together",
+            ));
+        }
         validate_datatype_generics(&ast.generics)?;
         let type_tag = TypeTagStruct::new(&ast, &attrs);
         Ok(Self {
@@ -26,9 +26,12 @@ mod type_tag;
 ///   Deserialization of the latter will fail if the package addresses do not match.
 /// - `#[move_(module = "...")]`: sets a static module name for the generated type tag.
 ///   Deserialization of the latter will fail if the module names do not match.
+/// - `#[move_(name = "...")]`: sets the Move datatype name for the generated type tag, if it's not
+///   the Rust type's name, e.g., for a Rust type renamed to avoid a collision.
 /// - `#[move_(nameless)]`: make the datatype name dynamic for the generated type tag. Upon the
 ///   deserializing the latter, any Move datatype name will be accepted. Otherwise, deserialization
-///   will fail if the incoming datatype name is not equal to the Rust type's name.
+///   will fail if the incoming datatype name is not equal to the Move datatype's name, i.e., the
+///   `#[move_(name = "...")]` one or else the Rust type's name.
 ///
 /// # Type tag derivation
 ///
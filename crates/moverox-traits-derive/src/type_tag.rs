@@ -36,7 +36,7 @@ impl TypeTagStruct {
             name: if attrs.nameless {
                 None
             } else {
-                Some(ast.ident.to_string())
+                Some(attrs.name.clone().unwrap_or_else(|| ast.ident.to_string()))
             },
             generics: datatype_generics_to_typetag_generics(ast.generics.clone(), attrs.thecrate()),
             thecrate: attrs.thecrate(),
@@ -0,0 +1,9 @@
+use moverox_traits::MoveDatatype;
+
+#[derive(MoveDatatype)]
+#[move_(name = "Witness", nameless)]
+pub struct Otw {
+    dummy_field: bool,
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use moverox_traits::{ConstName as _, MoveDatatype};
+
+#[derive(MoveDatatype)]
+#[move_(address = "0x2", module = coin, name = "Coin")]
+pub struct SuiCoin {
+    id: u64,
+    value: u64,
+}
+
+fn main() {
+    assert_eq!(SuiCoinTypeTag::NAME.as_str(), "Coin");
+}
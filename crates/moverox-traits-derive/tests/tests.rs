@@ -9,7 +9,9 @@ fn tests() {
     t.pass("tests/sources/no_type_bounds.rs");
     t.pass("tests/sources/enums.rs");
     t.pass("tests/sources/struct_with_type_default.rs");
+    t.pass("tests/sources/renamed_struct.rs");
     t.compile_fail("tests/sources/empty_braced_struct.rs");
     t.compile_fail("tests/sources/empty_enum.rs");
     t.compile_fail("tests/sources/empty_tuple_struct.rs");
+    t.compile_fail("tests/sources/named_and_nameless.rs");
 }
@@ -310,6 +310,25 @@ impl U256 {
         Self(PrimitiveU256::from_little_endian(slice))
     }
 
+    /// U256 from 32 big endian bytes, usable in `const` contexts
+    pub const fn from_be_bytes(bytes: &[u8; U256_NUM_BYTES]) -> Self {
+        let mut limbs = [0u64; 4];
+        let mut i = 0;
+        while i < limbs.len() {
+            // Least significant limb first
+            let start = U256_NUM_BYTES - 8 * (i + 1);
+            let mut limb = [0u8; 8];
+            let mut j = 0;
+            while j < limb.len() {
+                limb[j] = bytes[start + j];
+                j += 1;
+            }
+            limbs[i] = u64::from_be_bytes(limb);
+            i += 1;
+        }
+        Self(PrimitiveU256(limbs))
+    }
+
     /// U256 to 32 little endian bytes
     pub fn to_le_bytes(self) -> [u8; U256_NUM_BYTES] {
         let mut bytes = [0u8; U256_NUM_BYTES];
@@ -553,3 +572,15 @@ fn wrapping_add() {
 
     assert!(a.wrapping_add(b) == U256::from(99u8));
 }
+
+#[test]
+fn from_be_bytes() {
+    let mut bytes = [0u8; U256_NUM_BYTES];
+    bytes[0] = 1;
+    bytes[31] = 2;
+    let mut le_bytes = bytes;
+    le_bytes.reverse();
+    const MAX: U256 = U256::from_be_bytes(&[0xff; U256_NUM_BYTES]);
+    assert_eq!(U256::from_be_bytes(&bytes), U256::from_le_bytes(&le_bytes));
+    assert_eq!(MAX, U256::max_value());
+}
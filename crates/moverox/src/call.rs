@@ -0,0 +1,143 @@
+use std::marker::PhantomData;
+
+use moverox_traits::HasKey;
+use moverox_types::{Address, IdentStr, TypeTag};
+
+/// A call to a Move function with typed arguments.
+///
+/// `moverox-build` can generate a function returning one of these for each `public` or `entry`
+/// function of a Move package, so that the arguments are checked against the Move signature at
+/// compile time. Arguments of [pure](ParameterKind::Value) types, like `u64`, `&vector<u8>` or
+/// `&String`, are the Rust equivalents of their Move types, while the others are
+/// [objects](ObjectArg), whether passed by reference (`&T` or `&mut T`) or by value (`T`). The
+/// implicit `TxContext` argument is left out.
+///
+/// # Examples
+/// ```
+/// use moverox::{MoveCall, ObjectArg, Parameter, ParameterKind};
+/// use moverox::types::{IdentStr, const_address};
+///
+/// struct Vault;
+///
+/// // public fun withdraw(vault: &mut Vault, amount: u64, ctx: &mut TxContext)
+/// fn withdraw(vault: ObjectArg<Vault>, amount: u64) -> MoveCall<(ObjectArg<Vault>,), (u64,)> {
+///     MoveCall {
+///         package: const_address(b"0x1234"),
+///         module: IdentStr::cast("vault"),
+///         function: IdentStr::cast("withdraw"),
+///         type_arguments: vec![],
+///         parameters: &[
+///             Parameter { name: "vault", kind: ParameterKind::MutRef },
+///             Parameter { name: "amount", kind: ParameterKind::Value },
+///         ],
+///         objects: (vault,),
+///         pures: (amount,),
+///     }
+/// }
+///
+/// let call = withdraw(ObjectArg::new(const_address(b"0xabc")), 100);
+/// assert_eq!(call.objects.0.id(), const_address(b"0xabc"));
+/// assert_eq!(call.pures, (100,));
+/// assert_eq!(call.parameters[0].kind, ParameterKind::MutRef);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct MoveCall<Objects, Pures> {
+    /// Address of the package declaring the function.
+    pub package: Address,
+    pub module: &'static IdentStr,
+    pub function: &'static IdentStr,
+    /// Type tags of the type arguments to the function, in declaration order.
+    pub type_arguments: Vec<TypeTag>,
+    /// Parameters of the function in declaration order, without the `TxContext`.
+    ///
+    /// Tells how to interleave [`objects`](Self::objects) and [`pures`](Self::pures) back into
+    /// the function's argument list.
+    pub parameters: &'static [Parameter],
+    /// Tuple of the object arguments, in declaration order.
+    pub objects: Objects,
+    /// Tuple of the pure arguments, in declaration order.
+    pub pures: Pures,
+}
+
+/// A parameter of a Move function.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Parameter {
+    pub name: &'static str,
+    pub kind: ParameterKind,
+}
+
+/// How an argument is passed to a Move function.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ParameterKind {
+    /// `&T`, for an object
+    Ref,
+    /// `&mut T`, for an object
+    MutRef,
+    /// `T`, for an object
+    Owned,
+    /// A pure value, i.e., a primitive, `String`, `ID`, or a `vector` or `Option` of those, even by
+    /// reference
+    Value,
+}
+
+impl ParameterKind {
+    /// Whether the argument is an [`ObjectArg`].
+    pub const fn is_object(self) -> bool {
+        matches!(self, Self::Ref | Self::MutRef | Self::Owned)
+    }
+}
+
+/// The ID of an object of Move type `T` passed to a Move function.
+pub struct ObjectArg<T> {
+    id: Address,
+    _type: PhantomData<fn() -> T>,
+}
+
+impl<T> ObjectArg<T> {
+    pub const fn new(id: Address) -> Self {
+        Self {
+            id,
+            _type: PhantomData,
+        }
+    }
+
+    pub const fn id(&self) -> Address {
+        self.id
+    }
+}
+
+impl<T: HasKey> From<&T> for ObjectArg<T> {
+    fn from(object: &T) -> Self {
+        Self::new(object.address())
+    }
+}
+
+// Not derived so as to not require `T` to implement these traits
+
+impl<T> Clone for ObjectArg<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for ObjectArg<T> {}
+
+impl<T> std::fmt::Debug for ObjectArg<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("ObjectArg").field(&self.id).finish()
+    }
+}
+
+impl<T> PartialEq for ObjectArg<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl<T> Eq for ObjectArg<T> {}
+
+impl<T> std::hash::Hash for ObjectArg<T> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
@@ -0,0 +1,198 @@
+/// A Move abort code decoded as a Move 2024 "clever error".
+///
+/// Aborting with a constant annotated `#[error]` packs the source line of the abort and the
+/// indices of the constant's name and value in the compiled module's identifier and constant pools
+/// into the abort code:
+///
+/// ```text
+/// | tag (1 bit) | reserved (15 bits) | line number (16 bits) | identifier index (16 bits) | constant index (16 bits) |
+/// ```
+///
+/// Use [`CleverAbort::resolve`] with the `CLEVER_ERRORS` table generated for a module to find out
+/// which constant it aborted with.
+///
+/// # Examples
+/// ```
+/// use moverox::{CleverAbort, ErrorConstant};
+///
+/// const CLEVER_ERRORS: &[ErrorConstant] = &[
+///     ErrorConstant::new("ENotEnough", "Not enough funds", 0),
+///     ErrorConstant::new("ETooMuch", "Too much funds", 1).with_code(3),
+/// ];
+///
+/// let abort = CleverAbort::from_abort_code(0x8000_002a_0001_0001).unwrap();
+/// assert_eq!(abort.line_number(), 42);
+/// assert_eq!(abort.constant_index(), Some(1));
+///
+/// let error = abort.resolve("vault", CLEVER_ERRORS).unwrap();
+/// assert_eq!(error.name(), "ETooMuch");
+/// assert_eq!(error.code(), Some(3));
+/// assert_eq!(error.to_string(), "vault::ETooMuch at line 42: Too much funds");
+///
+/// // Regular abort codes aren't clever errors
+/// assert_eq!(CleverAbort::from_abort_code(3), None);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CleverAbort {
+    line_number: u16,
+    identifier_index: Option<u16>,
+    constant_index: Option<u16>,
+}
+
+impl CleverAbort {
+    const TAG: u64 = 1 << 63;
+    /// Index value for when the abort isn't from an `#[error]` constant, e.g., a bare `assert!`.
+    const UNAVAILABLE: u16 = u16::MAX;
+
+    /// Decode a raw Move abort code, returning `None` if it isn't a clever error.
+    pub const fn from_abort_code(code: u64) -> Option<Self> {
+        if code & Self::TAG == 0 {
+            return None;
+        }
+        Some(Self {
+            line_number: (code >> 32) as u16,
+            identifier_index: Self::available((code >> 16) as u16),
+            constant_index: Self::available(code as u16),
+        })
+    }
+
+    const fn available(index: u16) -> Option<u16> {
+        if index == Self::UNAVAILABLE {
+            None
+        } else {
+            Some(index)
+        }
+    }
+
+    /// Line of the abort in the Move source file.
+    pub const fn line_number(self) -> u16 {
+        self.line_number
+    }
+
+    /// Index of the error constant's name in the module's identifier pool.
+    pub const fn identifier_index(self) -> Option<u16> {
+        self.identifier_index
+    }
+
+    /// Index of the error constant's value in the module's constant pool.
+    pub const fn constant_index(self) -> Option<u16> {
+        self.constant_index
+    }
+
+    /// Find the constant this aborted with among the `constants` of `module`.
+    ///
+    /// Returns `None` if the abort doesn't refer to any of them. Constants with equal values share
+    /// an index in the constant pool, in which case the first one declared is returned.
+    pub fn resolve(
+        self,
+        module: &'static str,
+        constants: &'static [ErrorConstant],
+    ) -> Option<CleverError> {
+        let index = self.constant_index?;
+        let constant = constants.iter().find(|c| c.constant_index == index)?;
+        Some(CleverError {
+            module,
+            constant,
+            line_number: self.line_number,
+        })
+    }
+}
+
+/// A Move constant annotated `#[error]`, whose value is the error message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ErrorConstant {
+    name: &'static str,
+    message: &'static str,
+    constant_index: u16,
+    code: Option<u64>,
+}
+
+impl ErrorConstant {
+    /// Constant `name` with `message` as value, at `constant_index` in its module's constant pool.
+    pub const fn new(name: &'static str, message: &'static str, constant_index: u16) -> Self {
+        Self {
+            name,
+            message,
+            constant_index,
+            code: None,
+        }
+    }
+
+    /// Set the `<N>` in `#[error(code = <N>)]`.
+    #[must_use]
+    pub const fn with_code(mut self, code: u64) -> Self {
+        self.code = Some(code);
+        self
+    }
+
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub const fn message(&self) -> &'static str {
+        self.message
+    }
+
+    pub const fn constant_index(&self) -> u16 {
+        self.constant_index
+    }
+
+    /// The `<N>` in `#[error(code = <N>)]`, if set.
+    pub const fn code(&self) -> Option<u64> {
+        self.code
+    }
+}
+
+/// A clever error resolved to the `#[error]` constant it aborted with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CleverError {
+    module: &'static str,
+    constant: &'static ErrorConstant,
+    line_number: u16,
+}
+
+impl CleverError {
+    /// Name of the module that aborted.
+    pub const fn module(&self) -> &'static str {
+        self.module
+    }
+
+    /// Name of the error constant.
+    pub const fn name(&self) -> &'static str {
+        self.constant.name
+    }
+
+    /// Value of the error constant.
+    pub const fn message(&self) -> &'static str {
+        self.constant.message
+    }
+
+    /// The `<N>` in `#[error(code = <N>)]`, if set.
+    pub const fn code(&self) -> Option<u64> {
+        self.constant.code
+    }
+
+    /// Line of the abort in the Move source file.
+    pub const fn line_number(&self) -> u16 {
+        self.line_number
+    }
+
+    pub const fn constant(&self) -> &'static ErrorConstant {
+        self.constant
+    }
+}
+
+impl std::fmt::Display for CleverError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}::{} at line {}: {}",
+            self.module,
+            self.name(),
+            self.line_number,
+            self.message()
+        )
+    }
+}
+
+impl std::error::Error for CleverError {}
@@ -4,11 +4,17 @@
 
 pub use {moverox_traits as traits, moverox_types as types, serde};
 
+mod call;
+mod clever_error;
 #[cfg(feature = "bcs")]
 mod instance;
 mod macros;
 mod otw;
+#[cfg(feature = "bcs")]
+mod return_values;
 
+pub use call::{MoveCall, ObjectArg, Parameter, ParameterKind};
+pub use clever_error::{CleverAbort, CleverError, ErrorConstant};
 #[cfg(feature = "bcs")]
 pub use instance::{
     FromRawDatatypeError,
@@ -17,3 +23,5 @@ pub use instance::{
     parse_move_instance,
 };
 pub use otw::{Otw, OtwTypeTag};
+#[cfg(feature = "bcs")]
+pub use return_values::{FromReturnValuesError, check_return_count, parse_return_value};
@@ -0,0 +1,86 @@
+use moverox_traits::MoveType;
+use moverox_types::TypeTag;
+use serde::Deserialize;
+
+use crate::{FromRawInstanceError, parse_move_instance};
+
+/// Check that there are as many return values as the function returns.
+///
+/// Meant to be called by the return value decoders that `moverox-build` can generate, before
+/// decoding each return value with [`parse_return_value`].
+pub const fn check_return_count<B: AsRef<[u8]>>(
+    return_values: &[(TypeTag, B)],
+    expected: usize,
+) -> Result<(), FromReturnValuesError> {
+    let actual = return_values.len();
+    if actual != expected {
+        return Err(FromReturnValuesError::Count { expected, actual });
+    }
+    Ok(())
+}
+
+/// Parse the return value at `index` of a function call, e.g., from a dev-inspect transaction.
+///
+/// The type tag of the return value is checked against `T` before deserializing its BCS bytes,
+/// like in [`parse_move_instance`].
+///
+/// # Examples
+/// ```
+/// use moverox::types::TypeTag;
+/// use moverox::{FromReturnValuesError, check_return_count, parse_return_value};
+///
+/// // public fun balances(): (u64, bool)
+/// fn balances_returns(
+///     return_values: &[(TypeTag, Vec<u8>)],
+/// ) -> Result<(u64, bool), FromReturnValuesError> {
+///     check_return_count(return_values, 2)?;
+///     Ok((
+///         parse_return_value(return_values, 0)?,
+///         parse_return_value(return_values, 1)?,
+///     ))
+/// }
+///
+/// let values = [
+///     (TypeTag::U64, 42_u64.to_le_bytes().to_vec()),
+///     (TypeTag::Bool, vec![1]),
+/// ];
+/// assert_eq!(balances_returns(&values).unwrap(), (42, true));
+///
+/// let values = [(TypeTag::U64, vec![]), (TypeTag::U64, vec![])];
+/// let error = balances_returns(&values).unwrap_err();
+/// assert!(matches!(error, FromReturnValuesError::Value { index: 0, .. }));
+///
+/// let error = balances_returns(&values[..1]).unwrap_err();
+/// assert_eq!(error.to_string(), "Expected 2 return values, got 1");
+/// ```
+pub fn parse_return_value<T, B>(
+    return_values: &[(TypeTag, B)],
+    index: usize,
+) -> Result<T, FromReturnValuesError>
+where
+    T: MoveType + for<'de> Deserialize<'de>,
+    B: AsRef<[u8]>,
+{
+    let (type_tag, bytes) = return_values
+        .get(index)
+        .ok_or(FromReturnValuesError::Count {
+            expected: index + 1,
+            actual: return_values.len(),
+        })?;
+    let (_, value) = parse_move_instance::<T>(type_tag, bytes.as_ref())
+        .map_err(|source| FromReturnValuesError::Value { index, source })?;
+    Ok(value)
+}
+
+/// Error for [`parse_return_value`] and [`check_return_count`].
+#[derive(thiserror::Error, Debug)]
+pub enum FromReturnValuesError {
+    #[error("Expected {expected} return values, got {actual}")]
+    Count { expected: usize, actual: usize },
+    #[error("Return value {index}: {source}")]
+    Value {
+        /// Position of the return value in the function's return types.
+        index: usize,
+        source: FromRawInstanceError,
+    },
+}